@@ -1,3 +1,6 @@
 fn main() {
   tonic_build::compile_protos("proto/concordium.proto").unwrap_or_else(|e| panic!("Failed to compile protos {:?}", e));
+
+  tonic_build::compile_protos("proto/v2/concordium.proto")
+    .unwrap_or_else(|e| panic!("Failed to compile protos v2 {:?}", e));
 }
@@ -0,0 +1,8 @@
+CREATE TABLE block_cache (
+  id          INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  block_hash  TEXT NOT NULL,
+  kind        TEXT NOT NULL,
+  content     TEXT NOT NULL,
+
+  UNIQUE (block_hash, kind)
+);
@@ -0,0 +1,18 @@
+CREATE TABLE account_rewards_old (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  account_id INTEGER NOT NULL REFERENCES accounts (id),
+  block_hash TEXT NOT NULL,
+  epoch_ms BIGINT NOT NULL,
+  kind TEXT NOT NULL,
+  amount TEXT NOT NULL DEFAULT '0',
+
+  UNIQUE (account_id, block_hash, kind)
+);
+
+INSERT INTO account_rewards_old (id, account_id, block_hash, epoch_ms, kind, amount)
+SELECT id, account_id, block_hash, epoch_ms, kind, amount FROM account_rewards
+WHERE kind <> 'kind_finalization';
+
+DROP TABLE account_rewards;
+
+ALTER TABLE account_rewards_old RENAME TO account_rewards;
@@ -0,0 +1,20 @@
+-- SQLite cannot add a constraint to an existing column so the table is rebuilt
+-- to restrict the reward kinds, including the new finalization one.
+
+CREATE TABLE account_rewards_new (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  account_id INTEGER NOT NULL REFERENCES accounts (id),
+  block_hash TEXT NOT NULL,
+  epoch_ms BIGINT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('kind_baker', 'kind_transaction_fee', 'kind_finalization')),
+  amount TEXT NOT NULL DEFAULT '0',
+
+  UNIQUE (account_id, block_hash, kind)
+);
+
+INSERT INTO account_rewards_new (id, account_id, block_hash, epoch_ms, kind, amount)
+SELECT id, account_id, block_hash, epoch_ms, kind, amount FROM account_rewards;
+
+DROP TABLE account_rewards;
+
+ALTER TABLE account_rewards_new RENAME TO account_rewards;
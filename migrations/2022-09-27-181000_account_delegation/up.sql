@@ -0,0 +1,25 @@
+ALTER TABLE accounts ADD COLUMN staking TEXT NOT NULL DEFAULT 'none' CHECK (staking IN ('none', 'baker', 'delegator'));
+ALTER TABLE accounts ADD COLUMN pool_id BIGINT CHECK (pool_id >= 0);
+
+-- Refresh the accounts to learn about their staking.
+UPDATE accounts SET pending_update = 1;
+
+-- The table is rebuilt to allow the rewards of the delegators.
+
+CREATE TABLE account_rewards_new (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  account_id INTEGER NOT NULL REFERENCES accounts (id),
+  block_hash TEXT NOT NULL,
+  epoch_ms BIGINT NOT NULL,
+  kind TEXT NOT NULL CHECK (kind IN ('kind_baker', 'kind_transaction_fee', 'kind_finalization', 'kind_delegation')),
+  amount TEXT NOT NULL DEFAULT '0',
+
+  UNIQUE (account_id, block_hash, kind)
+);
+
+INSERT INTO account_rewards_new (id, account_id, block_hash, epoch_ms, kind, amount)
+SELECT id, account_id, block_hash, epoch_ms, kind, amount FROM account_rewards;
+
+DROP TABLE account_rewards;
+
+ALTER TABLE account_rewards_new RENAME TO account_rewards;
@@ -0,0 +1,20 @@
+CREATE TABLE pools (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  baker_id BIGINT NOT NULL CHECK (baker_id >= 0),
+  epoch_ms BIGINT NOT NULL,
+  open_status TEXT NOT NULL CHECK (open_status IN ('open_for_all', 'closed_for_new', 'closed_for_all')),
+  baking_commission DOUBLE NOT NULL,
+  finalization_commission DOUBLE NOT NULL,
+  transaction_commission DOUBLE NOT NULL,
+  equity_capital TEXT NOT NULL,
+  delegated_capital TEXT NOT NULL,
+  delegated_capital_cap TEXT NOT NULL,
+  blocks_baked BIGINT,
+  finalization_live BOOLEAN CHECK (finalization_live IN (0, 1)),
+  transaction_fees_earned TEXT,
+  effective_stake TEXT,
+  lottery_power DOUBLE
+);
+
+CREATE INDEX pools_baker_id_epoch_ms_idx
+ON pools (baker_id, epoch_ms);
@@ -0,0 +1,3 @@
+ALTER TABLE blocks DROP COLUMN protocol_version;
+
+ALTER TABLE blocks DROP COLUMN genesis_index;
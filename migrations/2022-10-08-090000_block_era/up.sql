@@ -0,0 +1,8 @@
+-- The era of the blocks indexed before is unknown and marked with the
+-- protocol version 0.
+ALTER TABLE blocks ADD COLUMN genesis_index INTEGER NOT NULL DEFAULT 0 CHECK (genesis_index >= 0);
+
+ALTER TABLE blocks ADD COLUMN protocol_version INTEGER NOT NULL DEFAULT 0 CHECK (protocol_version >= 0);
+
+-- The information of the cached blocks is missing the era.
+DELETE FROM block_cache WHERE kind = 'info';
@@ -0,0 +1,19 @@
+CREATE TABLE account_transactions (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  account_id INTEGER NOT NULL REFERENCES accounts (id),
+  block_hash TEXT NOT NULL,
+  transaction_hash TEXT NOT NULL,
+  epoch_ms BIGINT NOT NULL,
+  kind TEXT NOT NULL,
+  amount TEXT NOT NULL,
+  counterparty TEXT,
+  cost TEXT NOT NULL,
+
+  UNIQUE (account_id, transaction_hash)
+);
+
+CREATE INDEX account_transactions_account_id_epoch_ms_idx
+ON account_transactions (account_id, epoch_ms);
+
+-- The summaries of the cached blocks are missing the transactions.
+DELETE FROM block_cache WHERE kind = 'summary';
@@ -0,0 +1,11 @@
+CREATE TABLE account_snapshots (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  account_id INTEGER NOT NULL REFERENCES accounts (id),
+  epoch_ms BIGINT NOT NULL,
+  balance TEXT NOT NULL,
+  stake TEXT NOT NULL,
+  lottery_power DOUBLE NOT NULL
+);
+
+CREATE INDEX account_snapshots_account_id_epoch_ms_idx
+ON account_snapshots (account_id, epoch_ms);
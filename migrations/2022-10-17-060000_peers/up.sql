@@ -0,0 +1,10 @@
+CREATE TABLE peers (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  node_id TEXT NOT NULL UNIQUE,
+  packets_sent BIGINT NOT NULL,
+  packets_received BIGINT NOT NULL,
+  latency BIGINT NOT NULL,
+  connected_ms BIGINT NOT NULL,
+  last_seen_ms BIGINT NOT NULL,
+  disconnected_ms BIGINT
+);
@@ -0,0 +1,13 @@
+CREATE TABLE alerts (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  rule TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  message TEXT NOT NULL,
+  state TEXT NOT NULL,
+  fired_ms BIGINT NOT NULL,
+  resolved_ms BIGINT
+);
+
+-- An alert is fired only once for a rule and a subject until it is resolved.
+CREATE UNIQUE INDEX alerts_rule_subject_firing_idx
+ON alerts (rule, subject) WHERE state = 'firing';
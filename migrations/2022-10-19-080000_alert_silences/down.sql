@@ -0,0 +1,4 @@
+DROP TABLE silences;
+
+ALTER TABLE alerts DROP COLUMN acked_by;
+ALTER TABLE alerts DROP COLUMN acked_ms;
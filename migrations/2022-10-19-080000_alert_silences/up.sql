@@ -0,0 +1,16 @@
+ALTER TABLE alerts ADD COLUMN acked_ms BIGINT;
+ALTER TABLE alerts ADD COLUMN acked_by INTEGER;
+
+-- A silence mutes the notifications of the alerts matching the rule and the
+-- subject during a time window. A missing rule or subject matches any.
+CREATE TABLE silences (
+  id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+  rule TEXT,
+  subject TEXT,
+  reason TEXT NOT NULL,
+  created_by INTEGER,
+  starts_ms BIGINT NOT NULL,
+  ends_ms BIGINT NOT NULL
+);
+
+CREATE INDEX silences_ends_ms_idx ON silences (ends_ms);
@@ -0,0 +1,2 @@
+INSERT INTO blocks (height, hash, slot_time_ms, baker)
+VALUES (2840311, '994dbdd7f9493286ed05706e154c3366d83281a76bdb7a058a5f4c7859a9f9a8', 1651978740000, 2);
@@ -0,0 +1,4 @@
+-- The start block of the indexing is now defined by the network profile of the
+-- configuration, and the block inserted by the first migration is removed.
+DELETE FROM blocks
+WHERE height = 2840311 AND hash = '994dbdd7f9493286ed05706e154c3366d83281a76bdb7a058a5f4c7859a9f9a8';
@@ -0,0 +1,221 @@
+use crate::model::{Block, Pool};
+use serde::Serialize;
+
+/// Duration of a day in milliseconds, which is also the duration of a payday
+/// on the mainnet.
+pub const DAY_MS: i64 = 86_400_000;
+
+/// The production of a baker over a period of time.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct Period {
+  from_ms: i64,
+  to_ms: i64,
+  lottery_power: f64,
+  total_blocks: usize,
+  expected_blocks: f64,
+  actual_blocks: usize,
+}
+
+/// An analysis of the block production of a baker that compares the number of
+/// blocks it was expected to produce with the blocks actually produced.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct Performance {
+  baker_id: u64,
+  lottery_power: f64,
+  expected_blocks: f64,
+  actual_blocks: usize,
+  luck: Option<f64>,
+  deviation: Option<f64>,
+  periods: Vec<Period>,
+}
+
+/// It analyzes the production of the baker over the time window split into
+/// periods of the given duration. The expected number of blocks of a period is
+/// the share of the finalized blocks given by the lottery power of the baker
+/// in the last snapshot of its pool taken before the end of the period. The
+/// given lottery power is used when the history has none.
+///
+/// The production of a baker follows a Poisson distribution, so the deviation
+/// is expressed in standard deviations, which is the square root of the
+/// expected number of blocks.
+pub fn analyze(
+  baker_id: u64,
+  lottery_power: f64,
+  history: &[Pool],
+  blocks: &[Block],
+  from_ms: i64,
+  to_ms: i64,
+  period_ms: i64,
+) -> Performance {
+  let mut periods = Vec::new();
+  let mut start_ms = from_ms;
+
+  while start_ms < to_ms {
+    let end_ms = (start_ms + period_ms).min(to_ms);
+
+    let in_period: Vec<&Block> = blocks
+      .iter()
+      .filter(|b| b.get_slot_time_ms() >= start_ms && b.get_slot_time_ms() < end_ms)
+      .collect();
+
+    let period_power = history
+      .iter()
+      .filter(|pool| pool.get_epoch_ms() < end_ms)
+      .filter_map(|pool| {
+        pool
+          .get_payday()
+          .map(|payday| (pool.get_epoch_ms(), payday.lottery_power))
+      })
+      .max_by_key(|(epoch_ms, _)| *epoch_ms)
+      .map(|(_, power)| power)
+      .unwrap_or(lottery_power);
+
+    periods.push(Period {
+      from_ms: start_ms,
+      to_ms: end_ms,
+      lottery_power: period_power,
+      total_blocks: in_period.len(),
+      expected_blocks: in_period.len() as f64 * period_power,
+      actual_blocks: in_period.iter().filter(|b| b.get_baker() == baker_id as i64).count(),
+    });
+
+    start_ms = end_ms;
+  }
+
+  let expected_blocks: f64 = periods.iter().map(|p| p.expected_blocks).sum();
+  let actual_blocks: usize = periods.iter().map(|p| p.actual_blocks).sum();
+
+  let (luck, deviation) = if expected_blocks > 0.0 {
+    (
+      Some(actual_blocks as f64 / expected_blocks),
+      Some((actual_blocks as f64 - expected_blocks) / expected_blocks.sqrt()),
+    )
+  } else {
+    (None, None)
+  };
+
+  Performance {
+    baker_id,
+    lottery_power,
+    expected_blocks,
+    actual_blocks,
+    luck,
+    deviation,
+    periods,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::{CommissionRates, PoolCapital, PoolPayday};
+  use rust_decimal::Decimal;
+
+  fn block(height: i64, slot_time_ms: i64, baker: i64) -> Block {
+    Block::new(height as i32, height, ":hash:", slot_time_ms, baker)
+  }
+
+  fn pool(epoch_ms: i64, lottery_power: Option<f64>) -> Pool {
+    let payday = lottery_power.map(|lottery_power| PoolPayday {
+      blocks_baked: 0,
+      finalization_live: true,
+      transaction_fees_earned: Decimal::ZERO,
+      effective_stake: Decimal::ZERO,
+      lottery_power,
+    });
+
+    Pool::new(
+      1,
+      42,
+      epoch_ms,
+      "open_for_all",
+      CommissionRates {
+        baking: 0.1,
+        finalization: 1.0,
+        transaction: 0.1,
+      },
+      PoolCapital {
+        equity: Decimal::ZERO,
+        delegated: Decimal::ZERO,
+        delegated_cap: Decimal::ZERO,
+      },
+      payday,
+    )
+  }
+
+  #[test]
+  fn test_analyze() {
+    let mut blocks = vec![];
+    for i in 0..100 {
+      // The baker produces every 10th block of the first day, and nothing
+      // during the second.
+      let baker = if i < 50 && i % 10 == 0 { 42 } else { 1 };
+
+      blocks.push(block(i, i * 2 * DAY_MS / 100, baker));
+    }
+
+    let res = analyze(42, 0.1, &[], &blocks, 0, 2 * DAY_MS, DAY_MS);
+
+    assert_eq!(2, res.periods.len());
+    assert_eq!(
+      Period {
+        from_ms: 0,
+        to_ms: DAY_MS,
+        lottery_power: 0.1,
+        total_blocks: 50,
+        expected_blocks: 5.0,
+        actual_blocks: 5,
+      },
+      res.periods[0]
+    );
+    assert_eq!(0, res.periods[1].actual_blocks);
+    assert_eq!(10.0, res.expected_blocks);
+    assert_eq!(5, res.actual_blocks);
+    assert_eq!(Some(0.5), res.luck);
+    assert!(matches!(res.deviation, Some(d) if (d + 5.0 / 10f64.sqrt()).abs() < 1e-9));
+  }
+
+  #[test]
+  fn test_analyze_partial_period() {
+    let blocks = vec![block(1, 100, 42), block(2, 200, 1)];
+
+    let res = analyze(42, 0.5, &[], &blocks, 0, 150, 100);
+
+    assert_eq!(2, res.periods.len());
+    assert_eq!(50, res.periods[1].to_ms - res.periods[1].from_ms);
+    assert_eq!(1, res.actual_blocks);
+    assert_eq!(Some(2.0), res.luck);
+  }
+
+  #[test]
+  fn test_analyze_history() {
+    let blocks: Vec<Block> = (0..40).map(|i| block(i, i * DAY_MS / 10, 1)).collect();
+
+    // The history starts during the second day, and the snapshot of the third
+    // day is missing the statistics of the payday.
+    let history = vec![
+      pool(DAY_MS + DAY_MS / 2, Some(0.2)),
+      pool(2 * DAY_MS + 1, None),
+      pool(3 * DAY_MS + 1, Some(0.3)),
+      pool(4 * DAY_MS + 1, Some(0.4)),
+    ];
+
+    let res = analyze(42, 0.1, &history, &blocks, 0, 4 * DAY_MS, DAY_MS);
+
+    assert_eq!(
+      vec![0.1, 0.2, 0.2, 0.3],
+      res.periods.iter().map(|p| p.lottery_power).collect::<Vec<_>>()
+    );
+    assert!((res.expected_blocks - 8.0).abs() < 1e-9);
+    assert_eq!(0.1, res.lottery_power);
+  }
+
+  #[test]
+  fn test_analyze_no_lottery_power() {
+    let res = analyze(42, 0.0, &[], &[block(1, 100, 1)], 0, DAY_MS, DAY_MS);
+
+    assert_eq!(0.0, res.expected_blocks);
+    assert_eq!(None, res.luck);
+    assert_eq!(None, res.deviation);
+  }
+}
@@ -78,6 +78,10 @@ impl Claims {
     }
   }
 
+  pub fn get_user_id(&self) -> Option<i32> {
+    self.user_id
+  }
+
   /// It returns the timestamp in milliseconds of the expiration of the token.
   pub fn expiration(&self) -> i64 {
     self.exp * 1000
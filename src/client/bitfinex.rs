@@ -1,7 +1,7 @@
 use reqwest::Client;
 use serde::{de::DeserializeOwned, Deserialize};
 
-use super::Result;
+use super::{ClientMetrics, Result};
 use crate::model::Pair;
 
 const TICKERS_URL: &'static str = "https://api-pub.bitfinex.com/v2/tickers";
@@ -18,6 +18,8 @@ pub struct Price {
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait PriceClient {
+  fn get_metrics(&self) -> ClientMetrics;
+
   async fn get_prices(&self, pairs: Vec<Pair>) -> Result<Vec<Price>>;
 }
 
@@ -78,6 +80,10 @@ impl<E: Executor> BitfinexClient<E> {
 
 #[async_trait]
 impl<E: Executor> PriceClient for BitfinexClient<E> {
+  fn get_metrics(&self) -> ClientMetrics {
+    ClientMetrics::default()
+  }
+
   async fn get_prices(&self, pairs: Vec<Pair>) -> Result<Vec<Price>> {
     // Build the symbols that can be understood by Bitfinex.
     let symbols = pairs
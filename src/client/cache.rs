@@ -0,0 +1,454 @@
+use super::node::{
+  AccountInfo, Baker, Block, BlockInfo, BlockStream, BlockSummary, ConsensusStatus, NodeClient, NodeInfo, NodeStats,
+  PoolStatus,
+};
+use super::{ClientMetrics, DynNodeClient, Result};
+use crate::repository::{DynBlockCacheRepository, NewCachedBlock};
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+const KIND_BLOCK_INFO: &str = "info";
+const KIND_BLOCK_SUMMARY: &str = "summary";
+
+/// Number of entries stored on disk between two garbage collections.
+const GC_INTERVAL: u64 = 100;
+
+/// A map of a bounded size which evicts the oldest entries first.
+struct Bounded<T> {
+  capacity: usize,
+  entries: HashMap<String, T>,
+  order: VecDeque<String>,
+}
+
+impl<T: Clone> Bounded<T> {
+  fn new(capacity: usize) -> Self {
+    Self {
+      capacity,
+      entries: HashMap::new(),
+      order: VecDeque::new(),
+    }
+  }
+
+  fn get(&self, key: &str) -> Option<T> {
+    self.entries.get(key).cloned()
+  }
+
+  fn insert(&mut self, key: &str, value: T) {
+    if self.capacity == 0 || self.entries.contains_key(key) {
+      return;
+    }
+
+    if self.entries.len() >= self.capacity {
+      if let Some(oldest) = self.order.pop_front() {
+        self.entries.remove(&oldest);
+      }
+    }
+
+    self.order.push_back(key.to_string());
+    self.entries.insert(key.to_string(), value);
+  }
+}
+
+/// A node client that keeps the data of finalized blocks, which never changes,
+/// so that it is fetched only once from the node. The data is kept in memory
+/// and optionally persisted in a repository.
+pub struct CachedClient {
+  inner: DynNodeClient,
+  infos: Mutex<Bounded<BlockInfo>>,
+  summaries: Mutex<Bounded<BlockSummary>>,
+  repository: Option<DynBlockCacheRepository>,
+  disk_capacity: i64,
+  stores: AtomicU64,
+  hits: AtomicU64,
+  misses: AtomicU64,
+}
+
+impl CachedClient {
+  /// It creates a cache of the given capacity in front of the client. When a
+  /// repository is provided, the data is also persisted up to the disk
+  /// capacity.
+  pub fn new(
+    inner: DynNodeClient,
+    capacity: usize,
+    repository: Option<DynBlockCacheRepository>,
+    disk_capacity: i64,
+  ) -> DynNodeClient {
+    Arc::new(Self {
+      inner,
+      infos: Mutex::new(Bounded::new(capacity)),
+      summaries: Mutex::new(Bounded::new(capacity)),
+      repository,
+      disk_capacity,
+      stores: AtomicU64::new(0),
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+    })
+  }
+
+  /// It looks for the data in memory first, and then in the repository.
+  async fn lookup<T>(&self, memory: &Mutex<Bounded<T>>, data_kind: &str, hash: &str) -> Option<T>
+  where
+    T: Clone + DeserializeOwned,
+  {
+    if let Some(value) = memory.lock().unwrap().get(hash) {
+      return Some(value);
+    }
+
+    let repository = self.repository.as_ref()?;
+
+    let content = match repository.get(hash, data_kind).await {
+      Ok(content) => content?,
+      Err(e) => {
+        warn!("unable to read the block cache: {}", e);
+        return None;
+      }
+    };
+
+    match serde_json::from_str::<T>(&content) {
+      Ok(value) => {
+        memory.lock().unwrap().insert(hash, value.clone());
+        Some(value)
+      }
+      Err(e) => {
+        warn!("unable to decode the block cache entry of `{}`: {}", hash, e);
+        None
+      }
+    }
+  }
+
+  /// It keeps the data in memory and in the repository if any. Failing to
+  /// persist the data is not fatal as it can be fetched again.
+  async fn keep<T>(&self, memory: &Mutex<Bounded<T>>, data_kind: &str, hash: &str, value: &T)
+  where
+    T: Clone + Serialize,
+  {
+    memory.lock().unwrap().insert(hash, value.clone());
+
+    let repository = match &self.repository {
+      Some(repository) => repository,
+      None => return,
+    };
+
+    let entry = match serde_json::to_string(value) {
+      Ok(content) => NewCachedBlock {
+        block_hash: hash.to_string(),
+        kind: data_kind.to_string(),
+        content,
+      },
+      Err(e) => {
+        warn!("unable to encode the block cache entry of `{}`: {}", hash, e);
+        return;
+      }
+    };
+
+    if let Err(e) = repository.store(entry).await {
+      warn!("unable to write the block cache: {}", e);
+      return;
+    }
+
+    if self.stores.fetch_add(1, Ordering::Relaxed) % GC_INTERVAL == GC_INTERVAL - 1 {
+      if let Err(e) = repository.garbage_collect(self.disk_capacity).await {
+        warn!("unable to clean the block cache: {}", e);
+      }
+    }
+  }
+
+  fn record(&self, hit: bool) {
+    if hit {
+      self.hits.fetch_add(1, Ordering::Relaxed);
+    } else {
+      self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+}
+
+#[async_trait]
+impl NodeClient for CachedClient {
+  fn get_endpoint(&self) -> String {
+    self.inner.get_endpoint()
+  }
+
+  fn get_metrics(&self) -> ClientMetrics {
+    let metrics = ClientMetrics {
+      cache_hits: self.hits.load(Ordering::Relaxed),
+      cache_misses: self.misses.load(Ordering::Relaxed),
+      ..ClientMetrics::default()
+    };
+
+    metrics + self.inner.get_metrics()
+  }
+
+  async fn get_node_info(&self) -> Result<NodeInfo> {
+    self.inner.get_node_info().await
+  }
+
+  async fn get_node_uptime(&self) -> Result<u64> {
+    self.inner.get_node_uptime().await
+  }
+
+  async fn get_node_stats(&self) -> Result<NodeStats> {
+    self.inner.get_node_stats().await
+  }
+
+  async fn get_last_block(&self) -> Result<Block> {
+    self.inner.get_last_block().await
+  }
+
+  async fn get_consensus_status(&self) -> Result<ConsensusStatus> {
+    self.inner.get_consensus_status().await
+  }
+
+  async fn get_genesis_hash(&self) -> Result<String> {
+    self.inner.get_genesis_hash().await
+  }
+
+  async fn get_finalized_blocks(&self) -> Result<BlockStream> {
+    self.inner.get_finalized_blocks().await
+  }
+
+  async fn get_block_at_height(&self, genesis_index: Option<u32>, height: i64) -> Result<Option<String>> {
+    self.inner.get_block_at_height(genesis_index, height).await
+  }
+
+  /// It returns the information of the block from the cache when available.
+  /// The information is cached only once the block is finalized.
+  async fn get_block_info(&self, block_hash: &str) -> Result<BlockInfo> {
+    if let Some(info) = self.lookup(&self.infos, KIND_BLOCK_INFO, block_hash).await {
+      self.record(true);
+      return Ok(info);
+    }
+
+    self.record(false);
+
+    let info = self.inner.get_block_info(block_hash).await?;
+
+    if info.finalized {
+      self.keep(&self.infos, KIND_BLOCK_INFO, block_hash, &info).await;
+    }
+
+    Ok(info)
+  }
+
+  /// It returns the summary of the block from the cache when available. The
+  /// summary is cached only once the block is known to be finalized.
+  async fn get_block_summary(&self, block_hash: &str) -> Result<BlockSummary> {
+    if let Some(summary) = self.lookup(&self.summaries, KIND_BLOCK_SUMMARY, block_hash).await {
+      self.record(true);
+      return Ok(summary);
+    }
+
+    self.record(false);
+
+    let summary = self.inner.get_block_summary(block_hash).await?;
+
+    let finalized = match self.lookup(&self.infos, KIND_BLOCK_INFO, block_hash).await {
+      Some(info) => info.finalized,
+      None => self.get_block_info(block_hash).await?.finalized,
+    };
+
+    if finalized {
+      self
+        .keep(&self.summaries, KIND_BLOCK_SUMMARY, block_hash, &summary)
+        .await;
+    }
+
+    Ok(summary)
+  }
+
+  async fn get_account_info(&self, block: &str, address: &str) -> Result<AccountInfo> {
+    self.inner.get_account_info(block, address).await
+  }
+
+  async fn get_baker(&self, block: &str, address: &str) -> Result<Option<Baker>> {
+    self.inner.get_baker(block, address).await
+  }
+
+  async fn get_pool_status(&self, block: &str, baker_id: u64) -> Result<Option<PoolStatus>> {
+    self.inner.get_pool_status(block, baker_id).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::node::{Event, MockNodeClient};
+  use crate::repository::{AsyncPool, SqliteBlockCacheRepository};
+  use chrono::Utc;
+  use mockall::predicate::*;
+
+  fn block_info(finalized: bool) -> BlockInfo {
+    BlockInfo {
+      block_hash: ":hash:".to_string(),
+      block_height: 42,
+      finalized,
+      block_baker: Some(1),
+      block_slot_time: Utc::now(),
+      genesis_index: 0,
+      era_block_height: 42,
+      protocol_version: 1,
+    }
+  }
+
+  fn block_summary() -> BlockSummary {
+    BlockSummary {
+      special_events: vec![Event::PaydayAccountReward {
+        account: ":address:".to_string(),
+        baker_reward: rust_decimal::Decimal::from(2),
+        transaction_fees: rust_decimal::Decimal::ZERO,
+        finalization_reward: rust_decimal::Decimal::ZERO,
+      }],
+      transaction_summaries: vec![],
+    }
+  }
+
+  #[test]
+  fn test_bounded() {
+    let mut map = Bounded::new(2);
+
+    map.insert("a", 1);
+    map.insert("b", 2);
+    map.insert("c", 3);
+
+    assert_eq!(None, map.get("a"));
+    assert_eq!(Some(2), map.get("b"));
+    assert_eq!(Some(3), map.get("c"));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_info() {
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_block_info()
+      .with(eq(":hash:"))
+      .times(1)
+      .returning(|_| Ok(block_info(true)));
+
+    client.expect_get_metrics().returning(ClientMetrics::default);
+
+    let client = CachedClient::new(Arc::new(client), 10, None, 0);
+
+    assert!(matches!(client.get_block_info(":hash:").await, Ok(info) if info.block_height == 42));
+    assert!(matches!(client.get_block_info(":hash:").await, Ok(info) if info.block_height == 42));
+
+    let metrics = client.get_metrics();
+    assert_eq!(1, metrics.cache_hits);
+    assert_eq!(1, metrics.cache_misses);
+  }
+
+  #[tokio::test]
+  async fn test_get_block_info_not_finalized() {
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_block_info()
+      .times(2)
+      .returning(|_| Ok(block_info(false)));
+
+    client
+      .expect_get_block_summary()
+      .times(1)
+      .returning(|_| Ok(block_summary()));
+
+    client.expect_get_metrics().returning(ClientMetrics::default);
+
+    let client = CachedClient::new(Arc::new(client), 10, None, 0);
+
+    assert!(matches!(client.get_block_info(":hash:").await, Ok(_)));
+    assert!(matches!(client.get_block_summary(":hash:").await, Ok(_)));
+
+    assert_eq!(0, client.get_metrics().cache_hits);
+  }
+
+  #[tokio::test]
+  async fn test_get_block_summary() {
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_block_summary()
+      .with(eq(":hash:"))
+      .times(1)
+      .returning(|_| Ok(block_summary()));
+
+    client
+      .expect_get_block_info()
+      .with(eq(":hash:"))
+      .times(1)
+      .returning(|_| Ok(block_info(true)));
+
+    let client = CachedClient::new(Arc::new(client), 10, None, 0);
+
+    assert!(matches!(client.get_block_summary(":hash:").await, Ok(_)));
+    assert!(matches!(client.get_block_summary(":hash:").await, Ok(s) if s.special_events.len() == 1));
+
+    // The information has been cached when checking the finalization.
+    assert!(matches!(client.get_block_info(":hash:").await, Ok(_)));
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_persistence() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository: DynBlockCacheRepository = Arc::new(SqliteBlockCacheRepository::new(pool));
+
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_block_info()
+      .times(1)
+      .returning(|_| Ok(block_info(true)));
+
+    let client = CachedClient::new(Arc::new(client), 10, Some(repository.clone()), 100);
+
+    assert!(matches!(client.get_block_info(":hash:").await, Ok(_)));
+
+    // A new cache with an empty memory finds the data in the repository.
+    let client = CachedClient::new(Arc::new(MockNodeClient::new()), 10, Some(repository), 100);
+
+    let res = client.get_block_info(":hash:").await;
+
+    assert!(matches!(res, Ok(info) if info.block_hash == ":hash:" && info.finalized));
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_persistence_undecodable() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository: DynBlockCacheRepository = Arc::new(SqliteBlockCacheRepository::new(pool));
+
+    repository
+      .store(NewCachedBlock {
+        block_hash: ":hash:".to_string(),
+        kind: KIND_BLOCK_INFO.to_string(),
+        content: "{}".to_string(),
+      })
+      .await
+      .unwrap();
+
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_block_info()
+      .times(1)
+      .returning(|_| Ok(block_info(true)));
+
+    let client = CachedClient::new(Arc::new(client), 10, Some(repository.clone()), 100);
+
+    assert!(matches!(client.get_block_info(":hash:").await, Ok(_)));
+
+    // The entry that could not be decoded has been replaced.
+    let client = CachedClient::new(Arc::new(MockNodeClient::new()), 10, Some(repository), 100);
+
+    let res = client.get_block_info(":hash:").await;
+
+    assert!(matches!(res, Ok(info) if info.block_hash == ":hash:"));
+  }
+}
@@ -0,0 +1,272 @@
+use super::node::{
+  AccountInfo, Baker, Block, BlockInfo, BlockStream, BlockSummary, ConsensusStatus, NodeClient, NodeInfo, NodeStats,
+  PoolStatus,
+};
+use super::{ClientMetrics, DynNodeClient, Error, Result};
+use log::warn;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tonic::Code;
+
+/// A period after which an endpoint that failed is considered healthy again so
+/// that it can be probed by the next call.
+const RECOVERY_PERIOD: Duration = Duration::from_secs(30);
+
+/// A health record of an endpoint which counts the consecutive failures.
+#[derive(Default, Clone)]
+struct Health {
+  failures: u32,
+  last_failure: Option<Instant>,
+}
+
+impl Health {
+  /// It returns a score of the endpoint where a lower value means healthier.
+  fn score(&self, now: Instant) -> u32 {
+    match self.last_failure {
+      Some(t) if now.duration_since(t) < RECOVERY_PERIOD => self.failures,
+      _ => 0,
+    }
+  }
+}
+
+/// A node client that distributes the calls over a list of endpoints. The calls
+/// are sent to the healthiest endpoint and it fails over to the next ones when
+/// the node is unavailable. Endpoints are ordered by priority.
+pub struct FailoverClient {
+  endpoints: Vec<DynNodeClient>,
+  health: Mutex<Vec<Health>>,
+}
+
+impl FailoverClient {
+  pub fn new(endpoints: Vec<DynNodeClient>) -> DynNodeClient {
+    let health = Mutex::new(vec![Health::default(); endpoints.len()]);
+
+    Arc::new(Self {
+      endpoints,
+      health,
+    })
+  }
+
+  /// It returns the indices of the endpoints ordered from the healthiest to
+  /// the least healthy. The priority is kept for endpoints of equal health.
+  fn ranking(&self) -> Vec<usize> {
+    let now = Instant::now();
+    let health = self.health.lock().unwrap();
+
+    let mut indices: Vec<usize> = (0..self.endpoints.len()).collect();
+    indices.sort_by_key(|i| health[*i].score(now));
+
+    indices
+  }
+
+  fn report(&self, index: usize, healthy: bool) {
+    let mut health = self.health.lock().unwrap();
+
+    if healthy {
+      health[index] = Health::default();
+    } else {
+      health[index].failures += 1;
+      health[index].last_failure = Some(Instant::now());
+    }
+  }
+
+  /// It executes the call on the healthiest endpoint and tries the next ones
+  /// as long as the node is reported as unavailable.
+  async fn call<'a, T, F, Fut>(&'a self, f: F) -> Result<T>
+  where
+    F: Fn(&'a DynNodeClient) -> Fut,
+    Fut: Future<Output = Result<T>>,
+  {
+    let mut last_error = Error::Grpc(tonic::Status::unavailable("no endpoint available"));
+
+    for index in self.ranking() {
+      let endpoint = &self.endpoints[index];
+
+      match f(endpoint).await {
+        Err(Error::Grpc(status)) if status.code() == Code::Unavailable => {
+          warn!("node endpoint `{}` is unavailable: {}", endpoint.get_endpoint(), status);
+
+          self.report(index, false);
+          last_error = Error::Grpc(status);
+        }
+        res => {
+          self.report(index, true);
+          return res;
+        }
+      }
+    }
+
+    Err(last_error)
+  }
+}
+
+#[async_trait]
+impl NodeClient for FailoverClient {
+  /// It returns the endpoint that will receive the next call.
+  fn get_endpoint(&self) -> String {
+    match self.ranking().first() {
+      Some(index) => self.endpoints[*index].get_endpoint(),
+      None => String::default(),
+    }
+  }
+
+  fn get_metrics(&self) -> ClientMetrics {
+    self
+      .endpoints
+      .iter()
+      .fold(ClientMetrics::default(), |acc, c| acc + c.get_metrics())
+  }
+
+  async fn get_node_info(&self) -> Result<NodeInfo> {
+    self.call(|c| c.get_node_info()).await
+  }
+
+  async fn get_node_uptime(&self) -> Result<u64> {
+    self.call(|c| c.get_node_uptime()).await
+  }
+
+  async fn get_node_stats(&self) -> Result<NodeStats> {
+    self.call(|c| c.get_node_stats()).await
+  }
+
+  async fn get_last_block(&self) -> Result<Block> {
+    self.call(|c| c.get_last_block()).await
+  }
+
+  async fn get_consensus_status(&self) -> Result<ConsensusStatus> {
+    self.call(|c| c.get_consensus_status()).await
+  }
+
+  async fn get_genesis_hash(&self) -> Result<String> {
+    self.call(|c| c.get_genesis_hash()).await
+  }
+
+  async fn get_finalized_blocks(&self) -> Result<BlockStream> {
+    self.call(|c| c.get_finalized_blocks()).await
+  }
+
+  async fn get_block_at_height(&self, genesis_index: Option<u32>, height: i64) -> Result<Option<String>> {
+    self.call(|c| c.get_block_at_height(genesis_index, height)).await
+  }
+
+  async fn get_block_info(&self, block_hash: &str) -> Result<BlockInfo> {
+    self.call(|c| c.get_block_info(block_hash)).await
+  }
+
+  async fn get_block_summary(&self, block_hash: &str) -> Result<BlockSummary> {
+    self.call(|c| c.get_block_summary(block_hash)).await
+  }
+
+  async fn get_account_info(&self, block: &str, address: &str) -> Result<AccountInfo> {
+    self.call(|c| c.get_account_info(block, address)).await
+  }
+
+  async fn get_baker(&self, block: &str, address: &str) -> Result<Option<Baker>> {
+    self.call(|c| c.get_baker(block, address)).await
+  }
+
+  async fn get_pool_status(&self, block: &str, baker_id: u64) -> Result<Option<PoolStatus>> {
+    self.call(|c| c.get_pool_status(block, baker_id)).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::node::MockNodeClient;
+  use crate::client::retry::{RetryClient, RetryPolicy};
+  use tonic::Status;
+
+  fn endpoint(name: &'static str, uptime: Result<u64>) -> MockNodeClient {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_endpoint().returning(move || name.to_string());
+
+    client.expect_get_node_uptime().times(1).return_once(move || uptime);
+
+    client
+  }
+
+  #[tokio::test]
+  async fn test_call_healthiest() {
+    let first = endpoint("first", Ok(42));
+
+    let mut second = MockNodeClient::new();
+    second.expect_get_endpoint().returning(|| "second".to_string());
+
+    let client = FailoverClient::new(vec![Arc::new(first), Arc::new(second)]);
+
+    assert_eq!("first", client.get_endpoint());
+    assert!(matches!(client.get_node_uptime().await, Ok(42)));
+    assert_eq!("first", client.get_endpoint());
+  }
+
+  #[tokio::test]
+  async fn test_failover() {
+    let first = endpoint("first", Err(Error::Grpc(Status::unavailable("down"))));
+    let second = endpoint("second", Ok(42));
+
+    let client = FailoverClient::new(vec![Arc::new(first), Arc::new(second)]);
+
+    assert!(matches!(client.get_node_uptime().await, Ok(42)));
+
+    // The endpoint in use has switched to the healthy one.
+    assert_eq!("second", client.get_endpoint());
+  }
+
+  #[tokio::test]
+  async fn test_failover_with_retry() {
+    let first = endpoint("first", Err(Error::Grpc(Status::unavailable("down"))));
+    let second = endpoint("second", Ok(42));
+
+    let client = RetryClient::new(
+      FailoverClient::new(vec![Arc::new(first), Arc::new(second)]),
+      RetryPolicy::default(),
+    );
+
+    // The unavailable endpoint is not retried before failing over.
+    assert!(matches!(client.get_node_uptime().await, Ok(42)));
+    assert_eq!(0, client.retries());
+  }
+
+  #[tokio::test]
+  async fn test_no_failover_on_other_errors() {
+    let first = endpoint("first", Err(Error::Grpc(Status::not_found("oops"))));
+
+    let mut second = MockNodeClient::new();
+    second.expect_get_endpoint().returning(|| "second".to_string());
+
+    let client = FailoverClient::new(vec![Arc::new(first), Arc::new(second)]);
+
+    let res = client.get_node_uptime().await;
+
+    assert!(matches!(res, Err(Error::Grpc(status)) if status.code() == Code::NotFound));
+    assert_eq!("first", client.get_endpoint());
+  }
+
+  #[tokio::test]
+  async fn test_all_unavailable() {
+    let first = endpoint("first", Err(Error::Grpc(Status::unavailable("down"))));
+    let second = endpoint("second", Err(Error::Grpc(Status::unavailable("down"))));
+
+    let client = FailoverClient::new(vec![Arc::new(first), Arc::new(second)]);
+
+    let res = client.get_node_uptime().await;
+
+    assert!(matches!(res, Err(Error::Grpc(status)) if status.code() == Code::Unavailable));
+  }
+
+  #[test]
+  fn test_health_recovery() {
+    let now = Instant::now();
+
+    let health = Health {
+      failures: 3,
+      last_failure: Some(now),
+    };
+
+    assert_eq!(3, health.score(now));
+    assert_eq!(0, health.score(now + RECOVERY_PERIOD));
+  }
+}
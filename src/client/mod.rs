@@ -1,16 +1,25 @@
 pub mod bitfinex;
+pub mod cache;
+pub mod failover;
 pub mod node;
+pub mod node_v2;
+pub mod retry;
+pub mod tls;
 
 use self::bitfinex::PriceClient;
 use self::node::NodeClient;
 use std::fmt;
+use std::ops::Add;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum Error {
   Http(reqwest::Error),
   Grpc(tonic::Status),
   Json(serde_json::Error),
+  Decode(String),
+  Timeout(Duration),
 }
 
 impl fmt::Display for Error {
@@ -22,6 +31,8 @@ impl fmt::Display for Error {
         Some(e) => write!(f, "grpc: {:?}", e),
       },
       Self::Json(e) => write!(f, "client error: encoding: {}", e),
+      Self::Decode(e) => write!(f, "client error: decoding: {}", e),
+      Self::Timeout(d) => write!(f, "client error: deadline of {:?} exceeded", d),
     }
   }
 }
@@ -48,6 +59,37 @@ impl From<serde_json::Error> for Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// It converts a block height into the value expected by the node, or returns
+/// an invalid argument error when it is negative.
+fn block_height(height: i64) -> Result<u64> {
+  u64::try_from(height).map_err(|_| {
+    let message = format!("invalid block height `{}`", height);
+
+    Error::Grpc(tonic::Status::invalid_argument(message))
+  })
+}
+
+/// A set of counters about the calls of a node client, reported alongside the
+/// status of the node.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClientMetrics {
+  pub retries: u64,
+  pub cache_hits: u64,
+  pub cache_misses: u64,
+}
+
+impl Add for ClientMetrics {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    Self {
+      retries: self.retries + other.retries,
+      cache_hits: self.cache_hits + other.cache_hits,
+      cache_misses: self.cache_misses + other.cache_misses,
+    }
+  }
+}
+
 pub type DynNodeClient = Arc<dyn NodeClient + Sync + Send>;
 
-pub type BoxedPriceClient = Box<dyn PriceClient + Sync + Send>;
+pub type DynPriceClient = Arc<dyn PriceClient + Sync + Send>;
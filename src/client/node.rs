@@ -1,15 +1,18 @@
-use super::{DynNodeClient, Result};
+use super::{block_height, ClientMetrics, DynNodeClient, Error, Result};
 use ccd::node_info_response::IsInBakingCommittee;
 use ccd::p2p_client::P2pClient;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio_stream::Stream;
 use tonic::codegen::InterceptedService;
 use tonic::service::Interceptor;
 use tonic::{
   metadata::AsciiMetadataValue,
-  transport::{Channel, Uri},
+  transport::{Channel, Endpoint, Uri},
   Request, Status,
 };
 
@@ -23,13 +26,50 @@ pub struct Block {
   pub height: i64,
 }
 
+/// A stream of the blocks finalized by the node.
+pub type BlockStream = Pin<Box<dyn Stream<Item = Result<Block>> + Send>>;
+
+/// The state of the node in the committee of the bakers. A baker added with
+/// wrong keys is not baking even though it looks registered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BakerCommittee {
+  NotInCommittee,
+  AddedButNotActive,
+  AddedButWrongKeys,
+  Active,
+}
+
+impl BakerCommittee {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::NotInCommittee => "not_in_committee",
+      Self::AddedButNotActive => "added_but_not_active",
+      Self::AddedButWrongKeys => "added_but_wrong_keys",
+      Self::Active => "active",
+    }
+  }
+}
+
 #[derive(Debug)]
 pub struct NodeInfo {
   pub node_id: Option<String>,
   pub baker_id: Option<u64>,
-  pub is_baker_committee: bool,
+  pub baker_committee: BakerCommittee,
   pub is_finalizer_committee: bool,
   pub peer_type: String,
+  pub consensus_baker_running: bool,
+  pub consensus_running: bool,
+  pub consensus_type: String,
+}
+
+/// The statistics of the network traffic between the node and one of its
+/// peers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerStats {
+  pub node_id: String,
+  pub packets_sent: u64,
+  pub packets_received: u64,
+  pub latency: u64,
 }
 
 #[derive(Debug)]
@@ -38,9 +78,30 @@ pub struct NodeStats {
   pub avg_bps_in: u64,
   pub avg_bps_out: u64,
   pub peer_count: usize,
+  pub peers: Vec<PeerStats>,
 }
 
-#[derive(Deserialize, Debug)]
+impl NodeStats {
+  /// It creates the statistics of the node from the list of its peers. The
+  /// average latency is zero when the node has no peer.
+  pub fn new(peers: Vec<PeerStats>, avg_bps_in: u64, avg_bps_out: u64) -> Self {
+    let avg_latency = if peers.is_empty() {
+      0.0
+    } else {
+      peers.iter().map(|p| p.latency).sum::<u64>() as f64 / peers.len() as f64
+    };
+
+    Self {
+      avg_latency,
+      avg_bps_in,
+      avg_bps_out,
+      peer_count: peers.len(),
+      peers,
+    }
+  }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BlockInfo {
   pub block_hash: String,
@@ -50,22 +111,189 @@ pub struct BlockInfo {
 
   #[serde(with = "serde_with::rust::display_fromstr")]
   pub block_slot_time: DateTime<Utc>,
+
+  /// Index of the genesis of the era of the block. A new era starts after each
+  /// protocol update.
+  pub genesis_index: u32,
+
+  /// Height of the block relative to the genesis block of its era.
+  pub era_block_height: i64,
+
+  /// The legacy API does not include the protocol version in the information
+  /// of a block, so it is completed afterwards.
+  #[serde(default)]
+  pub protocol_version: u32,
 }
 
-#[derive(Deserialize, Debug)]
+/// The part of a block summary that describes the protocol of the block. It is
+/// only present from the protocol version 4.
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct Event {
-  pub tag: String,
-  pub account: Option<String>,
-  pub baker_reward: Option<Decimal>,
-  pub transaction_fees: Option<Decimal>,
-  pub finalization_reward: Option<Decimal>,
+struct SummaryProtocol {
+  protocol_version: Option<u32>,
 }
 
-#[derive(Deserialize, Debug)]
+/// An amount of micro CCD distributed to an account.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AccountAmount {
+  pub address: String,
+  pub amount: Decimal,
+}
+
+/// A special event of a block which describes the minting and the distribution
+/// of the rewards. The amounts are expressed in micro CCD.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "tag")]
+pub enum Event {
+  /// Minting of new CCD.
+  #[serde(rename_all = "camelCase")]
+  Mint {
+    mint_baking_reward: Decimal,
+    mint_finalization_reward: Decimal,
+    mint_platform_development_charge: Decimal,
+    foundation_account: String,
+  },
+
+  /// Distribution of the rewards to the baker of the block, before the
+  /// delegation protocol.
+  #[serde(rename_all = "camelCase")]
+  BlockReward {
+    transaction_fees: Decimal,
+    #[serde(rename = "oldGASAccount")]
+    old_gas_account: Decimal,
+    #[serde(rename = "newGASAccount")]
+    new_gas_account: Decimal,
+    baker_reward: Decimal,
+    foundation_charge: Decimal,
+    baker: String,
+    foundation_account: String,
+  },
+
+  /// Distribution of the baking rewards at the end of an epoch, before the
+  /// delegation protocol.
+  #[serde(rename_all = "camelCase")]
+  BakingRewards {
+    baker_rewards: Vec<AccountAmount>,
+    remainder: Decimal,
+  },
+
+  /// Distribution of the finalization rewards, before the delegation protocol.
+  #[serde(rename_all = "camelCase")]
+  FinalizationRewards {
+    finalization_rewards: Vec<AccountAmount>,
+    remainder: Decimal,
+  },
+
+  /// Payment of the development charge to the foundation on a payday.
+  #[serde(rename_all = "camelCase")]
+  PaydayFoundationReward {
+    foundation_account: String,
+    development_charge: Decimal,
+  },
+
+  /// Payment of the rewards to an account on a payday.
+  #[serde(rename_all = "camelCase")]
+  PaydayAccountReward {
+    account: String,
+    transaction_fees: Decimal,
+    baker_reward: Decimal,
+    finalization_reward: Decimal,
+  },
+
+  /// Accrual of the rewards of a block to the pool of its baker.
+  #[serde(rename_all = "camelCase")]
+  BlockAccrueReward {
+    transaction_fees: Decimal,
+    #[serde(rename = "oldGASAccount")]
+    old_gas_account: Decimal,
+    #[serde(rename = "newGASAccount")]
+    new_gas_account: Decimal,
+    baker_reward: Decimal,
+    passive_reward: Decimal,
+    foundation_charge: Decimal,
+    baker_id: u64,
+  },
+
+  /// Payment of the rewards to a pool on a payday. The pool owner is missing
+  /// for the passive delegation.
+  #[serde(rename_all = "camelCase")]
+  PaydayPoolReward {
+    pool_owner: Option<u64>,
+    transaction_fees: Decimal,
+    baker_reward: Decimal,
+    finalization_reward: Decimal,
+  },
+
+  /// An event introduced by a later protocol and unknown to the daemon.
+  #[serde(other)]
+  Unknown,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BlockSummary {
   pub special_events: Vec<Event>,
+  pub transaction_summaries: Vec<TransactionSummary>,
+}
+
+/// An address of either an account or a smart contract instance.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "address")]
+pub enum Address {
+  AddressAccount(String),
+  AddressContract(ContractAddress),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ContractAddress {
+  pub index: u64,
+  pub subindex: u64,
+}
+
+/// An event produced by a transaction. Only the transfers of CCD are
+/// described, the other events are ignored.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "tag")]
+pub enum TransactionEvent {
+  /// Transfer of an amount of micro CCD between two addresses.
+  Transferred {
+    amount: Decimal,
+    from: Address,
+    to: Address,
+  },
+
+  #[serde(other)]
+  Other,
+}
+
+/// The outcome of a transaction which is either a success with the events it
+/// produced, or a rejection.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "outcome", rename_all = "camelCase")]
+pub enum TransactionResult {
+  Success { events: Vec<TransactionEvent> },
+  Reject,
+}
+
+/// The type of a transaction, like an `accountTransaction` with the `transfer`
+/// contents.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TransactionType {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub contents: Option<String>,
+}
+
+/// A summary of a transaction included in a block. The sender is only known
+/// for the account transactions, and the cost is expressed in micro CCD.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct TransactionSummary {
+  pub hash: String,
+  pub sender: Option<String>,
+  pub cost: Decimal,
+  #[serde(rename = "type")]
+  pub kind: TransactionType,
+  pub result: TransactionResult,
 }
 
 #[derive(Deserialize, Debug)]
@@ -76,6 +304,26 @@ pub struct AccountBaker {
   pub baker_id: u32,
 }
 
+/// The pool an account delegates its stake to. The passive delegation is not
+/// attached to any baker.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "delegateType")]
+pub enum DelegationTarget {
+  Passive,
+  #[serde(rename_all = "camelCase")]
+  Baker {
+    baker_id: u64,
+  },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDelegation {
+  pub staked_amount: Decimal,
+  pub restake_earnings: bool,
+  pub delegation_target: DelegationTarget,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountInfo {
@@ -84,6 +332,23 @@ pub struct AccountInfo {
   pub account_index: u32,
   pub account_address: String,
   pub account_baker: Option<AccountBaker>,
+  pub account_delegation: Option<AccountDelegation>,
+}
+
+impl AccountInfo {
+  /// It returns the amount staked by the account either as a baker or as a
+  /// delegator.
+  pub fn get_staked_amount(&self) -> Decimal {
+    if let Some(baker) = &self.account_baker {
+      return baker.staked_amount;
+    }
+
+    self
+      .account_delegation
+      .as_ref()
+      .map(|d| d.staked_amount)
+      .unwrap_or(Decimal::ZERO)
+  }
 }
 
 #[derive(Deserialize, Debug)]
@@ -94,22 +359,131 @@ pub struct Baker {
   pub baker_lottery_power: f64,
 }
 
+/// The rates of the commissions that a baker takes on the rewards of its pool.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommissionRates {
+  pub transaction_commission: f64,
+  pub finalization_commission: f64,
+  pub baking_commission: f64,
+}
+
+/// The status of a pool regarding new delegators.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OpenStatus {
+  OpenForAll,
+  ClosedForNew,
+  ClosedForAll,
+}
+
+impl OpenStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::OpenForAll => "open_for_all",
+      Self::ClosedForNew => "closed_for_new",
+      Self::ClosedForAll => "closed_for_all",
+    }
+  }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolInfo {
+  pub open_status: OpenStatus,
+  pub commission_rates: CommissionRates,
+}
+
+/// The statistics of a pool for the current reward period.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentPaydayStatus {
+  pub blocks_baked: u64,
+  pub finalization_live: bool,
+  pub transaction_fees_earned: Decimal,
+  pub effective_stake: Decimal,
+  pub lottery_power: f64,
+}
+
+/// The status of the pool of a baker. The amounts are expressed in micro CCD.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolStatus {
+  pub baker_id: u64,
+  pub baker_address: String,
+  pub baker_equity_capital: Decimal,
+  pub delegated_capital: Decimal,
+  pub delegated_capital_cap: Decimal,
+  pub pool_info: PoolInfo,
+  pub current_payday_status: Option<CurrentPaydayStatus>,
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct BirkParameters {
   bakers: Vec<Baker>,
 }
 
+#[serde_with::serde_as]
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct ConsensusInfo {
+  genesis_block: String,
   last_finalized_block: String,
   last_finalized_block_height: i64,
+  #[serde(default)]
+  best_block_height: i64,
+  #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+  #[serde(default)]
+  last_finalized_time: Option<DateTime<Utc>>,
+  #[serde_as(as = "Option<serde_with::DisplayFromStr>")]
+  #[serde(default)]
+  current_era_genesis_time: Option<DateTime<Utc>>,
+  #[serde(default)]
+  slot_duration: u64,
+  #[serde(default)]
+  epoch_duration: u64,
+  #[serde(default)]
+  protocol_version: u64,
+  #[serde(default)]
+  genesis_index: u32,
+}
+
+/// The state of the consensus as seen by the node.
+#[derive(Debug, Clone)]
+pub struct ConsensusStatus {
+  pub best_block_height: i64,
+  pub last_finalized_block_height: i64,
+  pub last_finalized_time: Option<DateTime<Utc>>,
+  pub era_genesis_time: Option<DateTime<Utc>>,
+  pub slot_duration_ms: u64,
+  pub epoch_duration_ms: u64,
+  pub protocol_version: u64,
+}
+
+impl ConsensusStatus {
+  /// It returns the epoch at the given time, counted from the genesis block of
+  /// the current era, if the node knows it.
+  pub fn get_epoch(&self, now: DateTime<Utc>) -> Option<u64> {
+    let genesis_time = self.era_genesis_time?;
+
+    if self.epoch_duration_ms == 0 || now < genesis_time {
+      return None;
+    }
+
+    Some((now - genesis_time).num_milliseconds() as u64 / self.epoch_duration_ms)
+  }
 }
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait NodeClient {
+  /// It returns the URI of the node endpoint in use by the client.
+  fn get_endpoint(&self) -> String;
+
+  /// It returns the counters about the calls made by the client.
+  fn get_metrics(&self) -> ClientMetrics;
+
   async fn get_node_info(&self) -> Result<NodeInfo>;
 
   async fn get_node_uptime(&self) -> Result<u64>;
@@ -118,7 +492,20 @@ pub trait NodeClient {
 
   async fn get_last_block(&self) -> Result<Block>;
 
-  async fn get_block_at_height(&self, height: i64) -> Result<Option<String>>;
+  /// It returns the state of the consensus, notably how far the node is
+  /// from the best block it knows about.
+  async fn get_consensus_status(&self) -> Result<ConsensusStatus>;
+
+  /// It returns the hash of the genesis block of the network of the node.
+  async fn get_genesis_hash(&self) -> Result<String>;
+
+  /// It returns a stream of the blocks as they are finalized by the node.
+  async fn get_finalized_blocks(&self) -> Result<BlockStream>;
+
+  /// It returns the hash of the finalized block at the height relative to the
+  /// genesis block of the era with the given index, or at the absolute height
+  /// when no index is given.
+  async fn get_block_at_height(&self, genesis_index: Option<u32>, height: i64) -> Result<Option<String>>;
 
   async fn get_block_info(&self, block_hash: &str) -> Result<BlockInfo>;
 
@@ -127,26 +514,92 @@ pub trait NodeClient {
   async fn get_account_info(&self, block: &str, address: &str) -> Result<AccountInfo>;
 
   async fn get_baker(&self, block: &str, address: &str) -> Result<Option<Baker>>;
+
+  /// It returns the status of the pool of the baker if it exists at the given
+  /// block.
+  async fn get_pool_status(&self, block: &str, baker_id: u64) -> Result<Option<PoolStatus>>;
 }
 
 pub struct Client {
+  uri: Uri,
   client: P2pClient<InterceptedService<Channel, Authorization>>,
+  /// Protocol version of each era, as given by the summary of a block or by
+  /// the consensus status for the current era.
+  protocols: Mutex<HashMap<u32, u32>>,
 }
 
 impl Client {
-  pub fn new(uri: &Uri, token: &AsciiMetadataValue) -> DynNodeClient {
-    let channel = Channel::builder(uri.clone()).connect_lazy();
+  pub fn new(endpoint: &Endpoint, token: &AsciiMetadataValue) -> DynNodeClient {
+    let channel = endpoint.connect_lazy();
 
     let client = P2pClient::with_interceptor(channel, Authorization::new(token));
 
     Arc::new(Client {
+      uri: endpoint.uri().clone(),
       client,
+      protocols: Mutex::new(HashMap::new()),
     })
   }
+
+  /// It returns the protocol version of the era of the block. The summary of
+  /// the block is fetched only for the first block of an era, as a protocol
+  /// update always starts a new one. The summaries before the protocol version
+  /// 4 do not have it, and the consensus status is used instead when the block
+  /// is in the current era.
+  async fn get_protocol_version(&self, block_hash: &str, genesis_index: u32) -> Result<u32> {
+    let cached = self.protocols.lock().unwrap().get(&genesis_index).copied();
+
+    if let Some(version) = cached {
+      return Ok(version);
+    }
+
+    let mut client = self.client.clone();
+
+    let request = Request::new(ccd::BlockHash {
+      block_hash: block_hash.into(),
+    });
+
+    let response = client.get_block_summary(request).await?.into_inner();
+
+    let summary: SummaryProtocol = serde_json::from_str(&response.value)?;
+
+    let version = match summary.protocol_version {
+      Some(version) => version,
+      None => {
+        let response = client
+          .get_consensus_status(Request::new(ccd::Empty {}))
+          .await?
+          .into_inner();
+
+        let info: ConsensusInfo = serde_json::from_str(response.value.as_str())?;
+
+        if info.genesis_index != genesis_index || info.protocol_version == 0 {
+          return Err(Error::Decode(format!(
+            "protocol version of the era {} is unknown",
+            genesis_index
+          )));
+        }
+
+        info.protocol_version as u32
+      }
+    };
+
+    self.protocols.lock().unwrap().insert(genesis_index, version);
+
+    Ok(version)
+  }
 }
 
 #[async_trait]
 impl NodeClient for Client {
+  fn get_endpoint(&self) -> String {
+    self.uri.to_string()
+  }
+
+  fn get_metrics(&self) -> ClientMetrics {
+    ClientMetrics::default()
+  }
+
   /// It returns the information about the status of the node.
   async fn get_node_info(&self) -> Result<NodeInfo> {
     let mut client = self.client.clone();
@@ -158,9 +611,12 @@ impl NodeClient for Client {
     Ok(NodeInfo {
       node_id: response.node_id,
       baker_id: response.consensus_baker_id,
-      is_baker_committee: is_in_baker_committee(response.consensus_baker_committee),
+      baker_committee: baker_committee(response.consensus_baker_committee),
       is_finalizer_committee: response.consensus_finalizer_committee,
       peer_type: response.peer_type,
+      consensus_baker_running: response.consensus_baker_running,
+      consensus_running: response.consensus_running,
+      consensus_type: response.consensus_type,
     })
   }
 
@@ -185,12 +641,18 @@ impl NodeClient for Client {
 
     let response = client.peer_stats(request).await?.into_inner();
 
-    Ok(NodeStats {
-      avg_latency: compute_avg_latency(&response),
-      avg_bps_in: response.avg_bps_in,
-      avg_bps_out: response.avg_bps_out,
-      peer_count: response.peerstats.len(),
-    })
+    let peers = response
+      .peerstats
+      .into_iter()
+      .map(|stat| PeerStats {
+        node_id: stat.node_id,
+        packets_sent: stat.packets_sent,
+        packets_received: stat.packets_received,
+        latency: stat.latency,
+      })
+      .collect();
+
+    Ok(NodeStats::new(peers, response.avg_bps_in, response.avg_bps_out))
   }
 
   /// It fetches the current consensus status of the Concordium network of the
@@ -212,22 +674,64 @@ impl NodeClient for Client {
     Ok(block)
   }
 
-  /// It returns the hash of the block at the given height. It expects only
-  /// one hash per height but returns the first if multiple are found.
-  async fn get_block_at_height(&self, height: i64) -> Result<Option<String>> {
+  async fn get_consensus_status(&self) -> Result<ConsensusStatus> {
+    let mut client = self.client.clone();
+
+    let request = Request::new(ccd::Empty {});
+
+    let response = client.get_consensus_status(request).await?.into_inner();
+
+    let info: ConsensusInfo = serde_json::from_str(response.value.as_str())?;
+
+    Ok(ConsensusStatus {
+      best_block_height: info.best_block_height,
+      last_finalized_block_height: info.last_finalized_block_height,
+      last_finalized_time: info.last_finalized_time,
+      era_genesis_time: info.current_era_genesis_time,
+      slot_duration_ms: info.slot_duration,
+      epoch_duration_ms: info.epoch_duration,
+      protocol_version: info.protocol_version,
+    })
+  }
+
+  /// It returns the hash of the genesis block from the consensus status.
+  async fn get_genesis_hash(&self) -> Result<String> {
+    let mut client = self.client.clone();
+
+    let request = Request::new(ccd::Empty {});
+
+    let response = client.get_consensus_status(request).await?.into_inner();
+
+    let info: ConsensusInfo = serde_json::from_str(response.value.as_str())?;
+
+    Ok(info.genesis_block)
+  }
+
+  /// The legacy API does not provide a stream of the finalized blocks.
+  async fn get_finalized_blocks(&self) -> Result<BlockStream> {
+    Err(Error::Grpc(Status::unimplemented(
+      "finalized blocks are only streamed by the API v2",
+    )))
+  }
+
+  /// It returns the hash of the block at the given height of the era. It
+  /// expects only one hash per height but returns the first if multiple are
+  /// found.
+  async fn get_block_at_height(&self, genesis_index: Option<u32>, height: i64) -> Result<Option<String>> {
     let mut client = self.client.clone();
 
+    // The height relative to the first genesis block is the absolute height.
     let request = Request::new(ccd::BlockHeight {
-      block_height: u64::try_from(height).unwrap(),
-      from_genesis_index: 0,
-      restrict_to_genesis_index: false,
+      block_height: block_height(height)?,
+      from_genesis_index: genesis_index.unwrap_or(0),
+      restrict_to_genesis_index: genesis_index.is_some(),
     });
 
     let response = client.get_blocks_at_height(request).await?.into_inner();
 
-    let mut hashes: Vec<String> = serde_json::from_str(&response.value)?;
+    let hashes: Vec<String> = serde_json::from_str(&response.value)?;
 
-    Ok(hashes.pop())
+    Ok(hashes.into_iter().next())
   }
 
   /// It returns the information about the block with the given hash.
@@ -240,7 +744,11 @@ impl NodeClient for Client {
 
     let response = client.get_block_info(request).await?.into_inner();
 
-    Ok(serde_json::from_str(&response.value)?)
+    let mut info: BlockInfo = serde_json::from_str(&response.value)?;
+
+    info.protocol_version = self.get_protocol_version(block_hash, info.genesis_index).await?;
+
+    Ok(info)
   }
 
   /// It returns the summary of the block like special events.
@@ -292,15 +800,35 @@ impl NodeClient for Client {
 
     Ok(None)
   }
+
+  /// It returns the status of the pool of the baker if it exists at the given
+  /// block.
+  async fn get_pool_status(&self, block: &str, baker_id: u64) -> Result<Option<PoolStatus>> {
+    let mut client = self.client.clone();
+
+    let request = Request::new(ccd::GetPoolStatusRequest {
+      block_hash: block.to_string(),
+      passive_delegation: false,
+      baker_id,
+    });
+
+    let response = client.get_pool_status(request).await?.into_inner();
+
+    let status: Option<PoolStatus> = serde_json::from_str(response.value.as_str())?;
+
+    Ok(status)
+  }
 }
 
+/// An interceptor that adds the authentication token to the metadata of every
+/// request sent to the node.
 #[derive(Clone)]
-struct Authorization {
+pub(super) struct Authorization {
   token: AsciiMetadataValue,
 }
 
 impl Authorization {
-  fn new(token: &AsciiMetadataValue) -> Self {
+  pub(super) fn new(token: &AsciiMetadataValue) -> Self {
     let mut token = token.clone();
     token.set_sensitive(true);
 
@@ -318,28 +846,26 @@ impl Interceptor for Authorization {
   }
 }
 
-fn is_in_baker_committee(value: i32) -> bool {
-  value == i32::from(IsInBakingCommittee::ActiveInCommittee)
-}
-
-fn compute_avg_latency(stats: &ccd::PeerStatsResponse) -> f64 {
-  let mut t = 0;
-
-  for stat in &stats.peerstats {
-    t += stat.latency;
+/// It converts the state of the node in the committee of the bakers. An
+/// unknown value is considered as not in the committee.
+fn baker_committee(value: i32) -> BakerCommittee {
+  match IsInBakingCommittee::from_i32(value) {
+    Some(IsInBakingCommittee::AddedButNotActiveInCommittee) => BakerCommittee::AddedButNotActive,
+    Some(IsInBakingCommittee::AddedButWrongKeys) => BakerCommittee::AddedButWrongKeys,
+    Some(IsInBakingCommittee::ActiveInCommittee) => BakerCommittee::Active,
+    Some(IsInBakingCommittee::NotInCommittee) | None => BakerCommittee::NotInCommittee,
   }
-
-  let size = stats.peerstats.len() as f64;
-
-  t as f64 / size
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::client::tls::TlsConfig;
   use crate::client::Error;
+  use chrono::TimeZone;
   use mockall::predicate::*;
   use std::str::FromStr;
+  use tonic::transport::{Certificate, Identity, ServerTlsConfig};
   use tonic::{Request, Response, Status};
 
   type JsonResponse = std::result::Result<Response<ccd::JsonResponse>, Status>;
@@ -361,6 +887,7 @@ mod tests {
           async fn get_block_summary(&self, request: Request<ccd::BlockHash>) -> JsonResponse;
           async fn get_account_info(&self, request: Request<ccd::GetAddressInfoRequest>) -> JsonResponse;
           async fn get_birk_parameters(&self, request: Request<ccd::BlockHash>) -> JsonResponse;
+          async fn get_pool_status(&self, request: Request<ccd::GetPoolStatusRequest>) -> JsonResponse;
       }
   }
 
@@ -385,7 +912,7 @@ mod tests {
     let uri = Uri::from_str(&format!("http://[::1]:{}", port)).unwrap();
     let token = AsciiMetadataValue::from_static("rpcadmin");
 
-    Ok(Client::new(&uri, &token))
+    Ok(Client::new(&Endpoint::from(uri), &token))
   }
 
   #[tokio::test]
@@ -399,18 +926,118 @@ mod tests {
         peer_type: "Node".to_string(),
         consensus_baker_running: true,
         consensus_running: true,
-        consensus_type: "".to_string(),
-        consensus_baker_committee: 3,
-        consensus_finalizer_committee: true,
+        consensus_type: "Active".to_string(),
+        consensus_baker_committee: 2,
+        consensus_finalizer_committee: false,
         consensus_baker_id: Some(42),
       }))
     });
 
     let client = init(service).await.unwrap();
 
-    let res = client.get_node_info().await;
+    let res = client.get_node_info().await.unwrap();
 
-    assert!(matches!(res, Ok(_)));
+    assert_eq!(BakerCommittee::AddedButWrongKeys, res.baker_committee);
+    assert!(res.consensus_baker_running);
+    assert!(res.consensus_running);
+    assert_eq!("Active", res.consensus_type);
+  }
+
+  #[test]
+  fn test_baker_committee() {
+    assert_eq!(BakerCommittee::NotInCommittee, baker_committee(0));
+    assert_eq!(BakerCommittee::AddedButNotActive, baker_committee(1));
+    assert_eq!(BakerCommittee::Active, baker_committee(3));
+    assert_eq!(BakerCommittee::NotInCommittee, baker_committee(42));
+    assert_eq!("added_but_wrong_keys", BakerCommittee::AddedButWrongKeys.as_str());
+  }
+
+  /// It makes sure that the client reaches a node behind TLS which requires a
+  /// client certificate, both signed by a private authority.
+  #[tokio::test]
+  async fn test_tls() {
+    let mut params = rcgen::CertificateParams::new(vec![]);
+    params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca = rcgen::Certificate::from_params(params).unwrap();
+
+    let server_cert =
+      rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec!["node.test".to_string()])).unwrap();
+    let client_cert =
+      rcgen::Certificate::from_params(rcgen::CertificateParams::new(vec!["client.test".to_string()])).unwrap();
+
+    let tls = ServerTlsConfig::new()
+      .identity(Identity::from_pem(
+        server_cert.serialize_pem_with_signer(&ca).unwrap(),
+        server_cert.serialize_private_key_pem(),
+      ))
+      .client_ca_root(Certificate::from_pem(ca.serialize_pem().unwrap()));
+
+    let mut service = MockService::new();
+
+    service.expect_peer_uptime().times(1).returning(|_| {
+      Ok(Response::new(ccd::NumberResponse {
+        value: 42,
+      }))
+    });
+
+    let socket = tokio::net::TcpSocket::new_v6().unwrap();
+    socket.bind("[::1]:0".parse().unwrap()).unwrap();
+
+    let port = socket.local_addr().unwrap().port();
+
+    let listener = tokio_stream::wrappers::TcpListenerStream::new(socket.listen(1).unwrap());
+
+    tokio::spawn(async {
+      tonic::transport::Server::builder()
+        .tls_config(tls)
+        .unwrap()
+        .add_service(ccd::p2p_server::P2pServer::new(service))
+        .serve_with_incoming(listener)
+        .await
+        .unwrap();
+    });
+
+    let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir(&dir).unwrap();
+
+    std::fs::write(dir.join("ca.pem"), ca.serialize_pem().unwrap()).unwrap();
+    std::fs::write(
+      dir.join("client.pem"),
+      client_cert.serialize_pem_with_signer(&ca).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(dir.join("client.key"), client_cert.serialize_private_key_pem()).unwrap();
+
+    let cfg = TlsConfig {
+      ca_certificate: Some(dir.join("ca.pem")),
+      certificate: Some(dir.join("client.pem")),
+      key: Some(dir.join("client.key")),
+      domain_name: Some("node.test".to_string()),
+    };
+
+    let uri = Uri::from_str(&format!("https://[::1]:{}", port)).unwrap();
+    let endpoint = cfg.endpoint(&uri).unwrap();
+
+    let client = Client::new(&endpoint, &AsciiMetadataValue::from_static("rpcadmin"));
+
+    let res = client.get_node_uptime().await;
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(matches!(res, Ok(42)));
+
+    // The node is not trusted without the authority.
+    let cfg = TlsConfig {
+      domain_name: Some("node.test".to_string()),
+      ..TlsConfig::default()
+    };
+
+    let client = Client::new(
+      &cfg.endpoint(&uri).unwrap(),
+      &AsciiMetadataValue::from_static("rpcadmin"),
+    );
+
+    assert!(matches!(client.get_node_uptime().await, Err(Error::Grpc(_))));
   }
 
   #[tokio::test]
@@ -457,9 +1084,19 @@ mod tests {
 
     let client = init(service).await.unwrap();
 
-    let res = client.get_node_stats().await;
+    let res = client.get_node_stats().await.unwrap();
+
+    assert_eq!(150.0, res.avg_latency);
+    assert_eq!(2, res.peers.len());
+    assert_eq!("peer-2", res.peers[1].node_id);
+  }
+
+  #[test]
+  fn test_node_stats_without_peers() {
+    let stats = NodeStats::new(vec![], 10, 20);
 
-    assert!(matches!(res, Ok(node_stats) if node_stats.avg_latency == 150.0));
+    assert_eq!(0.0, stats.avg_latency);
+    assert_eq!(0, stats.peer_count);
   }
 
   #[tokio::test]
@@ -469,6 +1106,7 @@ mod tests {
     service.expect_get_consensus_status().times(1).returning(|_| {
       Ok(Response::new(ccd::JsonResponse {
         value: r#"{
+                        "genesisBlock": ":genesis:",
                         "lastFinalizedBlock": ":hash:",
                         "lastFinalizedBlockHeight": 123
                     }"#
@@ -483,13 +1121,91 @@ mod tests {
     assert!(matches!(res, Ok(block) if block.hash == ":hash:"),);
   }
 
+  #[tokio::test]
+  async fn test_get_consensus_status() {
+    let mut service = MockService::new();
+
+    service.expect_get_consensus_status().times(1).returning(|_| {
+      Ok(Response::new(ccd::JsonResponse {
+        value: r#"{
+                        "genesisBlock": ":genesis:",
+                        "lastFinalizedBlock": ":hash:",
+                        "lastFinalizedBlockHeight": 123,
+                        "bestBlockHeight": 125,
+                        "lastFinalizedTime": "2022-10-17T06:00:00Z",
+                        "currentEraGenesisTime": "2022-06-24T09:00:00Z",
+                        "slotDuration": 250,
+                        "epochDuration": 3600000,
+                        "protocolVersion": 4
+                    }"#
+          .to_string(),
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_consensus_status().await.unwrap();
+
+    assert_eq!(125, res.best_block_height);
+    assert_eq!(123, res.last_finalized_block_height);
+    assert_eq!(4, res.protocol_version);
+    assert!(matches!(res.last_finalized_time, Some(t) if t.timestamp() == 1665986400));
+  }
+
+  #[test]
+  fn test_consensus_status_epoch() {
+    let time = |secs: i64| Utc.timestamp_millis_opt(secs * 1000).unwrap();
+    let genesis = time(1000);
+
+    let mut status = ConsensusStatus {
+      best_block_height: 0,
+      last_finalized_block_height: 0,
+      last_finalized_time: None,
+      era_genesis_time: Some(genesis),
+      slot_duration_ms: 250,
+      epoch_duration_ms: 10_000,
+      protocol_version: 4,
+    };
+
+    assert_eq!(Some(2), status.get_epoch(time(1025)));
+    assert_eq!(None, status.get_epoch(time(500)));
+
+    status.era_genesis_time = None;
+    assert_eq!(None, status.get_epoch(time(1025)));
+  }
+
+  #[tokio::test]
+  async fn test_get_genesis_hash() {
+    let mut service = MockService::default();
+
+    service.expect_get_consensus_status().times(1).returning(|_| {
+      Ok(Response::new(ccd::JsonResponse {
+        value: r#"{
+                      "genesisBlock": ":genesis:",
+                      "lastFinalizedBlock": ":hash:",
+                      "lastFinalizedBlockHeight": 123
+                  }"#
+          .to_string(),
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_genesis_hash().await;
+
+    assert!(matches!(res, Ok(hash) if hash == ":genesis:"));
+  }
+
   #[tokio::test]
   async fn test_get_block_at_height() {
     let mut service = MockService::default();
 
     service
       .expect_get_blocks_at_height()
-      .withf(|r| r.get_ref().block_height == 42)
+      .withf(|r| {
+        let req = r.get_ref();
+        req.block_height == 42 && req.from_genesis_index == 3 && req.restrict_to_genesis_index
+      })
       .times(1)
       .returning(|_| {
         Ok(Response::new(ccd::JsonResponse {
@@ -499,11 +1215,44 @@ mod tests {
 
     let client = init(service).await.unwrap();
 
-    let res = client.get_block_at_height(42).await;
+    let res = client.get_block_at_height(Some(3), 42).await;
 
     assert!(matches!(res, Ok(hash) if hash == Some(":hash:".to_string())));
   }
 
+  #[tokio::test]
+  async fn test_get_block_at_negative_height() {
+    let client = init(MockService::default()).await.unwrap();
+
+    let res = client.get_block_at_height(None, -1).await;
+
+    assert!(matches!(res, Err(Error::Grpc(status)) if status.code() == tonic::Code::InvalidArgument));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_at_absolute_height() {
+    let mut service = MockService::default();
+
+    service
+      .expect_get_blocks_at_height()
+      .withf(|r| {
+        let req = r.get_ref();
+        req.block_height == 42 && req.from_genesis_index == 0 && !req.restrict_to_genesis_index
+      })
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::JsonResponse {
+          value: "[]".to_string(),
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_block_at_height(None, 42).await;
+
+    assert!(matches!(res, Ok(None)));
+  }
+
   #[tokio::test]
   async fn test_get_block_info() {
     let mut service = MockService::default();
@@ -511,7 +1260,7 @@ mod tests {
     service
       .expect_get_block_info()
       .withf(|r| r.get_ref().block_hash == ":hash:")
-      .times(1)
+      .times(2)
       .returning(|_| {
         Ok(Response::new(ccd::JsonResponse {
           value: r#"{
@@ -519,17 +1268,96 @@ mod tests {
                         "blockHeight": 3,
                         "finalized": true,
                         "blockBaker": 42,
-                        "blockSlotTime": "2022-08-19T09:03:40Z"
+                        "blockSlotTime": "2022-08-19T09:03:40Z",
+                        "genesisIndex": 3,
+                        "eraBlockHeight": 1
                     }"#
             .to_string(),
         }))
       });
 
+    service
+      .expect_get_block_summary()
+      .withf(|r| r.get_ref().block_hash == ":hash:")
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::JsonResponse {
+          value: r#"{ "protocolVersion": 4, "specialEvents": [] }"#.to_string(),
+        }))
+      });
+
     let client = init(service).await.unwrap();
 
     let res = client.get_block_info(":hash:").await;
 
-    assert!(matches!(res, Ok(info) if info.finalized));
+    assert!(matches!(res, Ok(info) if info.finalized && info.genesis_index == 3 && info.protocol_version == 4));
+
+    // The protocol version of the era is known and the summary is not fetched
+    // again.
+    let res = client.get_block_info(":hash:").await;
+
+    assert!(matches!(res, Ok(info) if info.protocol_version == 4));
+  }
+
+  fn block_info_service(consensus_genesis_index: u32) -> MockService {
+    let mut service = MockService::default();
+
+    service.expect_get_block_info().times(1).returning(|_| {
+      Ok(Response::new(ccd::JsonResponse {
+        value: r#"{
+                      "blockHash": ":hash:",
+                      "blockHeight": 3,
+                      "finalized": true,
+                      "blockBaker": 42,
+                      "blockSlotTime": "2022-08-19T09:03:40Z",
+                      "genesisIndex": 2,
+                      "eraBlockHeight": 1
+                  }"#
+          .to_string(),
+      }))
+    });
+
+    service.expect_get_block_summary().times(1).returning(|_| {
+      Ok(Response::new(ccd::JsonResponse {
+        value: r#"{ "specialEvents": [] }"#.to_string(),
+      }))
+    });
+
+    service.expect_get_consensus_status().times(1).returning(move |_| {
+      Ok(Response::new(ccd::JsonResponse {
+        value: format!(
+          r#"{{
+                "genesisBlock": ":genesis:",
+                "lastFinalizedBlock": ":hash:",
+                "lastFinalizedBlockHeight": 3,
+                "protocolVersion": 3,
+                "genesisIndex": {}
+            }}"#,
+          consensus_genesis_index
+        ),
+      }))
+    });
+
+    service
+  }
+
+  #[tokio::test]
+  async fn test_get_block_info_without_protocol() {
+    let client = init(block_info_service(2)).await.unwrap();
+
+    let res = client.get_block_info(":hash:").await;
+
+    assert!(matches!(res, Ok(info) if info.protocol_version == 3));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_info_unknown_protocol() {
+    // The block is in a previous era of which the version cannot be known.
+    let client = init(block_info_service(3)).await.unwrap();
+
+    let res = client.get_block_info(":hash:").await;
+
+    assert!(matches!(res, Err(Error::Decode(_))));
   }
 
   #[tokio::test]
@@ -543,7 +1371,101 @@ mod tests {
       .returning(|_| {
         Ok(Response::new(ccd::JsonResponse {
           value: r#"{
-                        "specialEvents": []
+                        "specialEvents": [
+                            {
+                                "tag": "Mint",
+                                "mintBakingReward": "1000",
+                                "mintFinalizationReward": "500",
+                                "mintPlatformDevelopmentCharge": "100",
+                                "foundationAccount": "foundation"
+                            },
+                            {
+                                "tag": "BlockReward",
+                                "transactionFees": "10",
+                                "oldGASAccount": "20",
+                                "newGASAccount": "30",
+                                "bakerReward": "40",
+                                "foundationCharge": "1",
+                                "baker": "baker",
+                                "foundationAccount": "foundation"
+                            },
+                            {
+                                "tag": "BakingRewards",
+                                "bakerRewards": [{ "address": "baker", "amount": "15" }],
+                                "remainder": "2"
+                            },
+                            {
+                                "tag": "FinalizationRewards",
+                                "finalizationRewards": [{ "address": "finalizer", "amount": "5" }],
+                                "remainder": "0"
+                            },
+                            {
+                                "tag": "PaydayFoundationReward",
+                                "foundationAccount": "foundation",
+                                "developmentCharge": "3"
+                            },
+                            {
+                                "tag": "PaydayAccountReward",
+                                "account": "baker",
+                                "transactionFees": "125",
+                                "bakerReward": "25",
+                                "finalizationReward": "5"
+                            },
+                            {
+                                "tag": "BlockAccrueReward",
+                                "transactionFees": "10",
+                                "oldGASAccount": "20",
+                                "newGASAccount": "30",
+                                "bakerReward": "40",
+                                "passiveReward": "2",
+                                "foundationCharge": "1",
+                                "bakerId": 42
+                            },
+                            {
+                                "tag": "PaydayPoolReward",
+                                "poolOwner": null,
+                                "transactionFees": "1",
+                                "bakerReward": "2",
+                                "finalizationReward": "3"
+                            },
+                            {
+                                "tag": "ValidatorSuspended",
+                                "bakerId": 42
+                            }
+                        ],
+                        "transactionSummaries": [
+                            {
+                                "hash": ":tx-1:",
+                                "sender": "alice",
+                                "cost": "12",
+                                "type": { "type": "accountTransaction", "contents": "transfer" },
+                                "result": {
+                                    "outcome": "success",
+                                    "events": [
+                                        {
+                                            "tag": "Transferred",
+                                            "amount": "1000",
+                                            "from": { "type": "AddressAccount", "address": "alice" },
+                                            "to": { "type": "AddressAccount", "address": "bob" }
+                                        },
+                                        {
+                                            "tag": "TransferMemo",
+                                            "memo": "00"
+                                        }
+                                    ]
+                                }
+                            },
+                            {
+                                "hash": ":tx-2:",
+                                "sender": "alice",
+                                "cost": "5",
+                                "type": { "type": "accountTransaction", "contents": "configureBaker" },
+                                "result": {
+                                    "outcome": "reject",
+                                    "rejectReason": { "tag": "OutOfEnergy" }
+                                }
+                            }
+                        ]
                     }"#
             .to_string(),
         }))
@@ -551,9 +1473,55 @@ mod tests {
 
     let client = init(service).await.unwrap();
 
-    let res = client.get_block_summary(":hash:").await;
+    let res = client.get_block_summary(":hash:").await.unwrap();
+
+    assert_eq!(9, res.special_events.len());
+    assert_eq!(Event::Unknown, res.special_events[8]);
+
+    assert!(matches!(
+      &res.special_events[3],
+      Event::FinalizationRewards { finalization_rewards, .. } if finalization_rewards[0].address == "finalizer"
+    ));
+
+    assert_eq!(
+      Event::PaydayAccountReward {
+        account: "baker".to_string(),
+        transaction_fees: Decimal::from(125),
+        baker_reward: Decimal::from(25),
+        finalization_reward: Decimal::from(5),
+      },
+      res.special_events[5]
+    );
+
+    assert!(matches!(
+      res.special_events[7],
+      Event::PaydayPoolReward {
+        pool_owner: None,
+        ..
+      }
+    ));
+
+    assert_eq!(2, res.transaction_summaries.len());
+
+    assert_eq!(
+      TransactionResult::Success {
+        events: vec![
+          TransactionEvent::Transferred {
+            amount: Decimal::from(1000),
+            from: Address::AddressAccount("alice".to_string()),
+            to: Address::AddressAccount("bob".to_string()),
+          },
+          TransactionEvent::Other,
+        ],
+      },
+      res.transaction_summaries[0].result
+    );
 
-    assert!(matches!(res, Ok(_)));
+    assert_eq!(
+      Some("configureBaker".to_string()),
+      res.transaction_summaries[1].kind.contents
+    );
+    assert_eq!(TransactionResult::Reject, res.transaction_summaries[1].result);
   }
 
   #[tokio::test]
@@ -586,13 +1554,70 @@ mod tests {
     let res = client.get_account_info("hash", "addr").await.unwrap();
 
     assert_eq!("256", res.account_amount.to_string());
+    assert_eq!("12.5", res.get_staked_amount().to_string());
     assert_eq!("12.5", res.account_baker.unwrap().staked_amount.to_string());
+    assert!(res.account_delegation.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_get_account_info_delegator() {
+    let mut service = MockService::default();
+
+    service.expect_get_account_info().times(2).returning(move |request| {
+      let target = match request.get_ref().address.as_str() {
+        "passive" => r#"{ "delegateType": "Passive" }"#,
+        _ => r#"{ "delegateType": "Baker", "bakerId": 42 }"#,
+      };
+
+      Ok(Response::new(ccd::JsonResponse {
+        value: format!(
+          r#"{{
+              "accountNonce": 1,
+              "accountAmount": "256",
+              "accountIndex": 2,
+              "accountAddress": "address",
+              "accountDelegation": {{
+                  "stakedAmount": "100",
+                  "restakeEarnings": false,
+                  "delegationTarget": {}
+              }}
+          }}"#,
+          target
+        ),
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_account_info("hash", "addr").await.unwrap();
+
+    assert!(res.account_baker.is_none());
+    assert_eq!("100", res.get_staked_amount().to_string());
+
+    let delegation = res.account_delegation.unwrap();
+    assert!(!delegation.restake_earnings);
+    assert_eq!(
+      DelegationTarget::Baker {
+        baker_id: 42
+      },
+      delegation.delegation_target
+    );
+
+    let res = client.get_account_info("hash", "passive").await.unwrap();
+
+    assert!(matches!(
+      res.account_delegation,
+      Some(AccountDelegation {
+        delegation_target: DelegationTarget::Passive,
+        ..
+      })
+    ));
   }
 
   #[tokio::test]
   async fn test_get_account_info_no_network() {
     let client = Client::new(
-      &Uri::from_static("http://[::1]:8888"),
+      &Endpoint::from_static("http://[::1]:8888"),
       &AsciiMetadataValue::from_static(""),
     );
 
@@ -669,4 +1694,74 @@ mod tests {
 
     assert!(matches!(res, Ok(baker) if matches!(&baker, None)));
   }
+
+  #[tokio::test]
+  async fn test_get_pool_status() {
+    let mut service = MockService::default();
+
+    service
+      .expect_get_pool_status()
+      .withf(|request| request.get_ref().block_hash == ":hash:" && request.get_ref().baker_id == 42)
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::JsonResponse {
+          value: r#"{
+                    "poolType": "BakerPool",
+                    "bakerId": 42,
+                    "bakerAddress": ":address:",
+                    "bakerEquityCapital": "1000",
+                    "delegatedCapital": "250",
+                    "delegatedCapitalCap": "3000",
+                    "poolInfo": {
+                        "openStatus": "openForAll",
+                        "metadataUrl": "",
+                        "commissionRates": {
+                            "transactionCommission": 0.1,
+                            "finalizationCommission": 1.0,
+                            "bakingCommission": 0.1
+                        }
+                    },
+                    "bakerStakePendingChange": { "pendingChangeType": "NoChange" },
+                    "currentPaydayStatus": {
+                        "blocksBaked": 12,
+                        "finalizationLive": true,
+                        "transactionFeesEarned": "25",
+                        "effectiveStake": "1250",
+                        "lotteryPower": 0.002,
+                        "bakerEquityCapital": "1000",
+                        "delegatedCapital": "250"
+                    },
+                    "allPoolTotalCapital": "100000"
+                }"#
+            .to_string(),
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_pool_status(":hash:", 42).await.unwrap().unwrap();
+
+    assert_eq!(42, res.baker_id);
+    assert_eq!(Decimal::from(250), res.delegated_capital);
+    assert_eq!(OpenStatus::OpenForAll, res.pool_info.open_status);
+    assert_eq!(1.0, res.pool_info.commission_rates.finalization_commission);
+    assert!(matches!(res.current_payday_status, Some(s) if s.blocks_baked == 12));
+  }
+
+  #[tokio::test]
+  async fn test_get_pool_status_not_found() {
+    let mut service = MockService::default();
+
+    service.expect_get_pool_status().times(1).returning(|_| {
+      Ok(Response::new(ccd::JsonResponse {
+        value: "null".to_string(),
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_pool_status(":hash:", 42).await;
+
+    assert!(matches!(res, Ok(None)));
+  }
 }
@@ -0,0 +1,1625 @@
+use super::node::{
+  AccountAmount, AccountBaker, AccountDelegation, AccountInfo, Address, Authorization, Baker, BakerCommittee, Block,
+  BlockInfo, BlockStream, BlockSummary, CommissionRates, CurrentPaydayStatus, DelegationTarget, Event, NodeClient,
+  NodeInfo, NodeStats, OpenStatus, PeerStats, PoolInfo, PoolStatus, TransactionEvent, TransactionResult,
+  TransactionSummary, TransactionType,
+};
+use super::{block_height, ClientMetrics, DynNodeClient, Error, Result};
+use ccd::account_staking_info::StakingInfo;
+use ccd::account_transaction_effects::Effect;
+use ccd::block_item_summary::Details as ItemDetails;
+use ccd::block_special_event::Event as SpecialEvent;
+use ccd::blocks_at_height_request::BlocksAtHeight;
+use ccd::delegation_target::Target;
+use ccd::node_info::baker_consensus_info::{PassiveCommitteeInfo, Status as CommitteeStatus};
+use ccd::node_info::node::ConsensusStatus;
+use ccd::node_info::Details;
+use ccd::queries_client::QueriesClient;
+use chrono::{DateTime, TimeZone, Utc};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tokio_stream::StreamExt;
+use tonic::codegen::InterceptedService;
+use tonic::{
+  metadata::AsciiMetadataValue,
+  transport::{Channel, Endpoint, Uri},
+  Code, Request,
+};
+
+mod ccd {
+  tonic::include_proto!("concordium.v2");
+}
+
+/// The version byte of a Base58Check encoded account address.
+const ADDRESS_VERSION: u8 = 1;
+
+const PEER_TYPE_NODE: &str = "Node";
+const PEER_TYPE_BOOTSTRAPPER: &str = "Bootstrapper";
+
+const CONSENSUS_TYPE_ACTIVE: &str = "Active";
+const CONSENSUS_TYPE_PASSIVE: &str = "Passive";
+
+const TRANSACTION_TYPE_ACCOUNT: &str = "accountTransaction";
+const TRANSACTION_TRANSFER: &str = "transfer";
+const TRANSACTION_TRANSFER_WITH_MEMO: &str = "transferWithMemo";
+
+/// The denominator of the fractions of the commission rates.
+const COMMISSION_DENOMINATOR: f64 = 100_000.0;
+
+/// An implementation of the node client that is using the typed gRPC API v2 of
+/// the Concordium node.
+pub struct Client {
+  uri: Uri,
+  client: QueriesClient<InterceptedService<Channel, Authorization>>,
+}
+
+impl Client {
+  pub fn new(endpoint: &Endpoint, token: &AsciiMetadataValue) -> DynNodeClient {
+    let channel = endpoint.connect_lazy();
+
+    let client = QueriesClient::with_interceptor(channel, Authorization::new(token));
+
+    Arc::new(Client {
+      uri: endpoint.uri().clone(),
+      client,
+    })
+  }
+
+  async fn node_info(&self) -> Result<ccd::NodeInfo> {
+    let mut client = self.client.clone();
+
+    let response = client.get_node_info(Request::new(ccd::Empty {})).await?;
+
+    Ok(response.into_inner())
+  }
+}
+
+#[async_trait]
+impl NodeClient for Client {
+  fn get_endpoint(&self) -> String {
+    self.uri.to_string()
+  }
+
+  fn get_metrics(&self) -> ClientMetrics {
+    ClientMetrics::default()
+  }
+
+  /// It returns the information about the status of the node.
+  async fn get_node_info(&self) -> Result<NodeInfo> {
+    let response = self.node_info().await?;
+
+    let mut info = NodeInfo {
+      node_id: response.network_info.and_then(|n| n.node_id).map(|id| id.value),
+      baker_id: None,
+      baker_committee: BakerCommittee::NotInCommittee,
+      is_finalizer_committee: false,
+      peer_type: PEER_TYPE_NODE.to_string(),
+      consensus_baker_running: false,
+      consensus_running: false,
+      consensus_type: String::new(),
+    };
+
+    let consensus_status = match response.details {
+      Some(Details::Bootstrapper(_)) => {
+        info.peer_type = PEER_TYPE_BOOTSTRAPPER.to_string();
+        None
+      }
+      Some(Details::Node(node)) => node.consensus_status,
+      None => None,
+    };
+
+    match consensus_status {
+      Some(ConsensusStatus::Passive(_)) => {
+        info.consensus_running = true;
+        info.consensus_type = CONSENSUS_TYPE_PASSIVE.to_string();
+      }
+      Some(ConsensusStatus::Active(baker)) => {
+        info.consensus_running = true;
+        info.consensus_baker_running = true;
+        info.consensus_type = CONSENSUS_TYPE_ACTIVE.to_string();
+        info.baker_id = baker.baker_id.map(|id| id.value);
+
+        info.baker_committee = match baker.status {
+          Some(CommitteeStatus::PassiveCommitteeInfo(value)) => match PassiveCommitteeInfo::from_i32(value) {
+            Some(PassiveCommitteeInfo::AddedButNotActiveInCommittee) => BakerCommittee::AddedButNotActive,
+            Some(PassiveCommitteeInfo::AddedButWrongKeys) => BakerCommittee::AddedButWrongKeys,
+            _ => BakerCommittee::NotInCommittee,
+          },
+          Some(CommitteeStatus::ActiveBakerCommitteeInfo(_)) => BakerCommittee::Active,
+          Some(CommitteeStatus::ActiveFinalizerCommitteeInfo(_)) => {
+            info.is_finalizer_committee = true;
+            BakerCommittee::Active
+          }
+          None => BakerCommittee::NotInCommittee,
+        };
+      }
+      Some(ConsensusStatus::NotRunning(_)) | None => {}
+    }
+
+    Ok(info)
+  }
+
+  /// It asks to the node for the uptime and the value in milliseconds.
+  async fn get_node_uptime(&self) -> Result<u64> {
+    let response = self.node_info().await?;
+
+    Ok(response.peer_uptime.map(|d| d.value).unwrap_or(0))
+  }
+
+  /// It returns the statistics of the blockchain node.
+  async fn get_node_stats(&self) -> Result<NodeStats> {
+    let mut client = self.client.clone();
+
+    let peers = client
+      .get_peers_info(Request::new(ccd::Empty {}))
+      .await?
+      .into_inner()
+      .peers;
+
+    let network = self.node_info().await?.network_info.unwrap_or_default();
+
+    // The statistics of a peer are optional in the response, and a peer
+    // without them has nothing to report.
+    let peers = peers
+      .into_iter()
+      .filter_map(|peer| {
+        peer.network_stats.map(|stats| PeerStats {
+          node_id: peer.peer_id.map(|id| id.value).unwrap_or_default(),
+          packets_sent: stats.packets_sent,
+          packets_received: stats.packets_received,
+          latency: stats.latency,
+        })
+      })
+      .collect();
+
+    Ok(NodeStats::new(peers, network.avg_bps_in, network.avg_bps_out))
+  }
+
+  /// It fetches the current consensus status of the Concordium network of the
+  /// node and returns the hash of the last finalized block.
+  async fn get_last_block(&self) -> Result<Block> {
+    let mut client = self.client.clone();
+
+    let response = client
+      .get_consensus_info(Request::new(ccd::Empty {}))
+      .await?
+      .into_inner();
+
+    Ok(Block {
+      hash: encode_hash(required(response.last_finalized_block, "last_finalized_block")?),
+      height: required(response.last_finalized_block_height, "last_finalized_block_height")?.value as i64,
+    })
+  }
+
+  async fn get_consensus_status(&self) -> Result<super::node::ConsensusStatus> {
+    let mut client = self.client.clone();
+
+    let response = client
+      .get_consensus_info(Request::new(ccd::Empty {}))
+      .await?
+      .into_inner();
+
+    Ok(super::node::ConsensusStatus {
+      best_block_height: required(response.best_block_height, "best_block_height")?.value as i64,
+      last_finalized_block_height: required(response.last_finalized_block_height, "last_finalized_block_height")?.value
+        as i64,
+      last_finalized_time: timestamp(response.last_finalized_time),
+      era_genesis_time: timestamp(response.current_era_genesis_time),
+      slot_duration_ms: response.slot_duration.map(|d| d.value).unwrap_or(0),
+      epoch_duration_ms: response.epoch_duration.map(|d| d.value).unwrap_or(0),
+      // The versions of the protocol are numbered from 1 but enumerated from 0.
+      protocol_version: response.protocol_version as u64 + 1,
+    })
+  }
+
+  /// It returns the hash of the genesis block from the consensus information.
+  async fn get_genesis_hash(&self) -> Result<String> {
+    let mut client = self.client.clone();
+
+    let response = client
+      .get_consensus_info(Request::new(ccd::Empty {}))
+      .await?
+      .into_inner();
+
+    Ok(encode_hash(required(response.genesis_block, "genesis_block")?))
+  }
+
+  async fn get_finalized_blocks(&self) -> Result<BlockStream> {
+    let mut client = self.client.clone();
+
+    let stream = client
+      .get_finalized_blocks(Request::new(ccd::Empty {}))
+      .await?
+      .into_inner();
+
+    Ok(Box::pin(stream.map(|res| {
+      let block = res?;
+
+      Ok(Block {
+        hash: encode_hash(required(block.hash, "hash")?),
+        height: required(block.height, "height")?.value as i64,
+      })
+    })))
+  }
+
+  /// It returns the hash of the block at the given height of the era. It
+  /// expects only one hash per height but returns the first if multiple are
+  /// found.
+  async fn get_block_at_height(&self, genesis_index: Option<u32>, height: i64) -> Result<Option<String>> {
+    let mut client = self.client.clone();
+
+    let height = block_height(height)?;
+
+    let blocks_at_height = match genesis_index {
+      Some(genesis_index) => BlocksAtHeight::Relative(ccd::blocks_at_height_request::Relative {
+        genesis_index: Some(ccd::GenesisIndex {
+          value: genesis_index,
+        }),
+        height: Some(ccd::BlockHeight {
+          value: height,
+        }),
+        restrict: true,
+      }),
+      None => BlocksAtHeight::Absolute(ccd::blocks_at_height_request::Absolute {
+        height: Some(ccd::AbsoluteBlockHeight {
+          value: height,
+        }),
+      }),
+    };
+
+    let request = Request::new(ccd::BlocksAtHeightRequest {
+      blocks_at_height: Some(blocks_at_height),
+    });
+
+    let response = client.get_blocks_at_height(request).await?.into_inner();
+
+    Ok(response.blocks.into_iter().next().map(encode_hash))
+  }
+
+  /// It returns the information about the block with the given hash.
+  async fn get_block_info(&self, block_hash: &str) -> Result<BlockInfo> {
+    let mut client = self.client.clone();
+
+    let response = client.get_block_info(given_block(block_hash)?).await?.into_inner();
+
+    let slot_time = required(response.slot_time, "slot_time")?.value as i64;
+
+    let block_slot_time = Utc
+      .timestamp_millis_opt(slot_time)
+      .single()
+      .ok_or_else(|| Error::Decode(format!("invalid slot time `{}`", slot_time)))?;
+
+    Ok(BlockInfo {
+      block_hash: encode_hash(required(response.hash, "hash")?),
+      block_height: required(response.height, "height")?.value as i64,
+      finalized: response.finalized,
+      block_baker: response.baker.map(|b| b.value as i64),
+      block_slot_time,
+      genesis_index: required(response.genesis_index, "genesis_index")?.value,
+      era_block_height: required(response.era_block_height, "era_block_height")?.value as i64,
+      // The versions are enumerated from zero for the protocol version 1.
+      protocol_version: response.protocol_version as u32 + 1,
+    })
+  }
+
+  /// It returns the summary of the block like special events.
+  async fn get_block_summary(&self, block_hash: &str) -> Result<BlockSummary> {
+    let mut client = self.client.clone();
+
+    let mut stream = client
+      .get_block_special_events(given_block(block_hash)?)
+      .await?
+      .into_inner();
+
+    let mut special_events = Vec::new();
+
+    while let Some(event) = stream.message().await? {
+      if let Some(event) = event.event {
+        special_events.push(into_event(event)?);
+      }
+    }
+
+    let mut stream = client
+      .get_block_transaction_events(given_block(block_hash)?)
+      .await?
+      .into_inner();
+
+    let mut transaction_summaries = Vec::new();
+
+    while let Some(item) = stream.message().await? {
+      if let Some(summary) = into_transaction(item)? {
+        transaction_summaries.push(summary);
+      }
+    }
+
+    Ok(BlockSummary {
+      special_events,
+      transaction_summaries,
+    })
+  }
+
+  /// It returns the details of the account like its balance and the staked
+  /// amount.
+  async fn get_account_info(&self, block_hash: &str, address: &str) -> Result<AccountInfo> {
+    let mut client = self.client.clone();
+
+    let request = Request::new(ccd::AccountInfoRequest {
+      block_hash: Some(given_block(block_hash)?.into_inner()),
+      account_identifier: Some(ccd::AccountIdentifierInput {
+        account_identifier_input: Some(ccd::account_identifier_input::AccountIdentifierInput::Address(
+          decode_address(address)?,
+        )),
+      }),
+    });
+
+    let response = client.get_account_info(request).await?.into_inner();
+
+    let mut account_baker = None;
+    let mut account_delegation = None;
+
+    match response.stake.and_then(|s| s.staking_info) {
+      Some(StakingInfo::Baker(baker)) => {
+        let baker_id = baker
+          .baker_info
+          .and_then(|info| info.baker_id)
+          .map(|id| id.value)
+          .unwrap_or_default();
+
+        account_baker = Some(AccountBaker {
+          staked_amount: amount(baker.staked_amount),
+          restake_earnings: baker.restake_earnings,
+          baker_id: u32::try_from(baker_id).map_err(|_| Error::Decode(format!("invalid baker id `{}`", baker_id)))?,
+        })
+      }
+      Some(StakingInfo::Delegator(delegator)) => {
+        let target = required(delegator.target.and_then(|t| t.target), "target")?;
+
+        account_delegation = Some(AccountDelegation {
+          staked_amount: amount(delegator.staked_amount),
+          restake_earnings: delegator.restake_earnings,
+          delegation_target: match target {
+            Target::Passive(_) => DelegationTarget::Passive,
+            Target::Baker(id) => DelegationTarget::Baker {
+              baker_id: id.value,
+            },
+          },
+        })
+      }
+      None => {}
+    };
+
+    Ok(AccountInfo {
+      account_nonce: response.sequence_number.map(|n| n.value as u32).unwrap_or_default(),
+      account_amount: amount(response.amount),
+      account_index: response.index.map(|i| i.value as u32).unwrap_or_default(),
+      account_address: encode_address(required(response.address, "address")?),
+      account_baker,
+      account_delegation,
+    })
+  }
+
+  /// It returns the baker of the account address if it exists in the
+  /// consensus.
+  async fn get_baker(&self, block: &str, address: &str) -> Result<Option<Baker>> {
+    let mut client = self.client.clone();
+
+    let response = client.get_election_info(given_block(block)?).await?.into_inner();
+
+    for baker in response.baker_election_info {
+      let baker_account = match baker.account {
+        Some(account) => encode_address(account),
+        None => continue,
+      };
+
+      if baker_account == address {
+        return Ok(Some(Baker {
+          baker_account,
+          baker_id: baker.baker.map(|id| id.value).unwrap_or_default(),
+          baker_lottery_power: baker.lottery_power,
+        }));
+      }
+    }
+
+    Ok(None)
+  }
+
+  /// It returns the status of the pool of the baker if it exists at the given
+  /// block.
+  async fn get_pool_status(&self, block: &str, baker_id: u64) -> Result<Option<PoolStatus>> {
+    let mut client = self.client.clone();
+
+    let request = Request::new(ccd::PoolInfoRequest {
+      block_hash: Some(given_block(block)?.into_inner()),
+      baker: Some(ccd::BakerId {
+        value: baker_id,
+      }),
+    });
+
+    let response = match client.get_pool_info(request).await {
+      Ok(response) => response.into_inner(),
+      Err(status) if status.code() == Code::NotFound => return Ok(None),
+      Err(status) => return Err(status.into()),
+    };
+
+    let pool_info = required(response.pool_info, "pool_info")?;
+
+    let open_status = match pool_info.open_status() {
+      ccd::OpenStatus::OpenForAll => OpenStatus::OpenForAll,
+      ccd::OpenStatus::ClosedForNew => OpenStatus::ClosedForNew,
+      ccd::OpenStatus::ClosedForAll => OpenStatus::ClosedForAll,
+    };
+
+    let rates = required(pool_info.commission_rates, "commission_rates")?;
+
+    Ok(Some(PoolStatus {
+      baker_id: required(response.baker, "baker")?.value,
+      baker_address: encode_address(required(response.address, "address")?),
+      baker_equity_capital: amount(response.equity_capital),
+      delegated_capital: amount(response.delegated_capital),
+      delegated_capital_cap: amount(response.delegated_capital_cap),
+      pool_info: PoolInfo {
+        open_status,
+        commission_rates: CommissionRates {
+          transaction_commission: fraction(rates.transaction),
+          finalization_commission: fraction(rates.finalization),
+          baking_commission: fraction(rates.baking),
+        },
+      },
+      current_payday_status: response.current_payday_info.map(|info| CurrentPaydayStatus {
+        blocks_baked: info.blocks_baked,
+        finalization_live: info.finalization_live,
+        transaction_fees_earned: amount(info.transaction_fees_earned),
+        effective_stake: amount(info.effective_stake),
+        lottery_power: info.lottery_power,
+      }),
+    }))
+  }
+}
+
+/// It converts a special event of the API v2 into the event of a block summary
+/// which follows the JSON representation of the legacy API.
+fn into_event(event: SpecialEvent) -> Result<Event> {
+  let event = match event {
+    SpecialEvent::Mint(ev) => Event::Mint {
+      mint_baking_reward: amount(ev.mint_baking_reward),
+      mint_finalization_reward: amount(ev.mint_finalization_reward),
+      mint_platform_development_charge: amount(ev.mint_platform_development_charge),
+      foundation_account: encode_address(required(ev.foundation_account, "foundation_account")?),
+    },
+    SpecialEvent::BlockReward(ev) => Event::BlockReward {
+      transaction_fees: amount(ev.transaction_fees),
+      old_gas_account: amount(ev.old_gas_account),
+      new_gas_account: amount(ev.new_gas_account),
+      baker_reward: amount(ev.baker_reward),
+      foundation_charge: amount(ev.foundation_charge),
+      baker: encode_address(required(ev.baker, "baker")?),
+      foundation_account: encode_address(required(ev.foundation_account, "foundation_account")?),
+    },
+    SpecialEvent::BakingRewards(ev) => Event::BakingRewards {
+      baker_rewards: account_amounts(ev.baker_rewards)?,
+      remainder: amount(ev.remainder),
+    },
+    SpecialEvent::FinalizationRewards(ev) => Event::FinalizationRewards {
+      finalization_rewards: account_amounts(ev.finalization_rewards)?,
+      remainder: amount(ev.remainder),
+    },
+    SpecialEvent::PaydayFoundationReward(ev) => Event::PaydayFoundationReward {
+      foundation_account: encode_address(required(ev.foundation_account, "foundation_account")?),
+      development_charge: amount(ev.development_charge),
+    },
+    SpecialEvent::PaydayAccountReward(ev) => Event::PaydayAccountReward {
+      account: encode_address(required(ev.account, "account")?),
+      transaction_fees: amount(ev.transaction_fees),
+      baker_reward: amount(ev.baker_reward),
+      finalization_reward: amount(ev.finalization_reward),
+    },
+    SpecialEvent::BlockAccrueReward(ev) => Event::BlockAccrueReward {
+      transaction_fees: amount(ev.transaction_fees),
+      old_gas_account: amount(ev.old_gas_account),
+      new_gas_account: amount(ev.new_gas_account),
+      baker_reward: amount(ev.baker_reward),
+      passive_reward: amount(ev.passive_reward),
+      foundation_charge: amount(ev.foundation_charge),
+      baker_id: required(ev.baker, "baker")?.value,
+    },
+    SpecialEvent::PaydayPoolReward(ev) => Event::PaydayPoolReward {
+      pool_owner: ev.pool_owner.map(|id| id.value),
+      transaction_fees: amount(ev.transaction_fees),
+      baker_reward: amount(ev.baker_reward),
+      finalization_reward: amount(ev.finalization_reward),
+    },
+  };
+
+  Ok(event)
+}
+
+/// It converts a block item of the API v2 into the summary of a transaction
+/// which follows the JSON representation of the legacy API. Only the account
+/// transactions are converted, and the events are known for the transfers
+/// only.
+fn into_transaction(item: ccd::BlockItemSummary) -> Result<Option<TransactionSummary>> {
+  let details = match item.details {
+    Some(ItemDetails::AccountTransaction(details)) => details,
+    None => return Ok(None),
+  };
+
+  let sender = encode_address(required(details.sender, "sender")?);
+
+  let (contents, result) = match details.effects.and_then(|e| e.effect) {
+    Some(Effect::None(_)) => (None, TransactionResult::Reject),
+    Some(Effect::AccountTransfer(transfer)) => {
+      let contents = match transfer.memo {
+        Some(_) => TRANSACTION_TRANSFER_WITH_MEMO,
+        None => TRANSACTION_TRANSFER,
+      };
+
+      let event = TransactionEvent::Transferred {
+        amount: amount(transfer.amount),
+        from: Address::AddressAccount(sender.clone()),
+        to: Address::AddressAccount(encode_address(required(transfer.receiver, "receiver")?)),
+      };
+
+      (
+        Some(contents.to_string()),
+        TransactionResult::Success {
+          events: vec![event],
+        },
+      )
+    }
+    // The effects that are not declared are successful but their events are
+    // unknown.
+    None => (
+      None,
+      TransactionResult::Success {
+        events: vec![],
+      },
+    ),
+  };
+
+  Ok(Some(TransactionSummary {
+    hash: hex::encode(required(item.hash, "hash")?.value),
+    sender: Some(sender),
+    cost: amount(details.cost),
+    kind: TransactionType {
+      kind: TRANSACTION_TYPE_ACCOUNT.to_string(),
+      contents,
+    },
+    result,
+  }))
+}
+
+/// It converts the amounts distributed to a list of accounts.
+fn account_amounts(value: Option<ccd::block_special_event::AccountAmounts>) -> Result<Vec<AccountAmount>> {
+  value
+    .map(|amounts| amounts.entries)
+    .unwrap_or_default()
+    .into_iter()
+    .map(|entry| {
+      Ok(AccountAmount {
+        address: encode_address(required(entry.account, "account")?),
+        amount: amount(entry.amount),
+      })
+    })
+    .collect()
+}
+
+/// It returns the value of a field that the node is expected to always set, or
+/// an error when it is missing from the response.
+fn required<T>(value: Option<T>, field: &str) -> Result<T> {
+  value.ok_or_else(|| Error::Decode(format!("missing field `{}`", field)))
+}
+
+/// It converts an amount of micro CCD into a decimal. A missing amount is
+/// considered as zero.
+fn amount(value: Option<ccd::Amount>) -> Decimal {
+  Decimal::from(value.map(|a| a.value).unwrap_or(0))
+}
+
+/// It converts a fraction of a commission into a rate. A missing fraction is
+/// considered as zero.
+fn fraction(value: Option<ccd::AmountFraction>) -> f64 {
+  value.map(|f| f.parts_per_hundred_thousand as f64).unwrap_or(0.0) / COMMISSION_DENOMINATOR
+}
+
+/// It builds the request input of a block given its hash in hexadecimal.
+fn given_block(block_hash: &str) -> Result<Request<ccd::BlockHashInput>> {
+  let value = hex::decode(block_hash).map_err(|e| Error::Decode(format!("block hash: {}", e)))?;
+
+  Ok(Request::new(ccd::BlockHashInput {
+    block_hash_input: Some(ccd::block_hash_input::BlockHashInput::Given(ccd::BlockHash {
+      value,
+    })),
+  }))
+}
+
+/// It converts a timestamp in milliseconds into a date, if it is given.
+fn timestamp(value: Option<ccd::Timestamp>) -> Option<DateTime<Utc>> {
+  value.and_then(|t| Utc.timestamp_millis_opt(t.value as i64).single())
+}
+
+fn encode_hash(hash: ccd::BlockHash) -> String {
+  hex::encode(hash.value)
+}
+
+fn encode_address(address: ccd::AccountAddress) -> String {
+  bs58::encode(address.value)
+    .with_check_version(ADDRESS_VERSION)
+    .into_string()
+}
+
+fn decode_address(address: &str) -> Result<ccd::AccountAddress> {
+  let mut value = bs58::decode(address)
+    .with_check(Some(ADDRESS_VERSION))
+    .into_vec()
+    .map_err(|e| Error::Decode(format!("account address: {}", e)))?;
+
+  // The decoded value is prefixed by the version byte.
+  value.remove(0);
+
+  Ok(ccd::AccountAddress {
+    value,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::pin::Pin;
+  use std::str::FromStr;
+  use tokio_stream::Stream;
+  use tonic::{Response, Status};
+
+  const HASH: &str = "994dbdd7f9493286ed05706e154c3366d83281a76bdb7a058a5f4c7859a9f9a8";
+  const ADDRESS: &str = "2wkH4kHMn2WPndf8CxmsoFkX93ouZMJUwTBFSZpDCeNeGWa7dj";
+
+  type ServiceResult<T> = std::result::Result<Response<T>, Status>;
+  type EventStream = Pin<Box<dyn Stream<Item = std::result::Result<ccd::BlockSpecialEvent, Status>> + Send>>;
+  type TransactionStream = Pin<Box<dyn Stream<Item = std::result::Result<ccd::BlockItemSummary, Status>> + Send>>;
+  type FinalizedStream = Pin<Box<dyn Stream<Item = std::result::Result<ccd::FinalizedBlockInfo, Status>> + Send>>;
+
+  mockall::mock! {
+      pub Service {}
+
+      #[async_trait]
+      impl ccd::queries_server::Queries for Service {
+          type GetBlockSpecialEventsStream = EventStream;
+          type GetFinalizedBlocksStream = FinalizedStream;
+          type GetBlockTransactionEventsStream = TransactionStream;
+
+          async fn get_account_info(&self, request: Request<ccd::AccountInfoRequest>) -> ServiceResult<ccd::AccountInfo>;
+          async fn get_finalized_blocks(&self, request: Request<ccd::Empty>) -> ServiceResult<FinalizedStream>;
+          async fn get_consensus_info(&self, request: Request<ccd::Empty>) -> ServiceResult<ccd::ConsensusInfo>;
+          async fn get_blocks_at_height(&self, request: Request<ccd::BlocksAtHeightRequest>) -> ServiceResult<ccd::BlocksAtHeightResponse>;
+          async fn get_block_info(&self, request: Request<ccd::BlockHashInput>) -> ServiceResult<ccd::BlockInfo>;
+          async fn get_election_info(&self, request: Request<ccd::BlockHashInput>) -> ServiceResult<ccd::ElectionInfo>;
+          async fn get_block_special_events(&self, request: Request<ccd::BlockHashInput>) -> ServiceResult<EventStream>;
+          async fn get_block_transaction_events(&self, request: Request<ccd::BlockHashInput>) -> ServiceResult<TransactionStream>;
+          async fn get_pool_info(&self, request: Request<ccd::PoolInfoRequest>) -> ServiceResult<ccd::PoolInfoResponse>;
+          async fn get_node_info(&self, request: Request<ccd::Empty>) -> ServiceResult<ccd::NodeInfo>;
+          async fn get_peers_info(&self, request: Request<ccd::Empty>) -> ServiceResult<ccd::PeersInfo>;
+      }
+  }
+
+  async fn init(srvc: MockService) -> std::io::Result<DynNodeClient> {
+    let svc = ccd::queries_server::QueriesServer::new(srvc);
+
+    let socket = tokio::net::TcpSocket::new_v6()?;
+    socket.bind("[::1]:0".parse().unwrap())?;
+
+    let port = socket.local_addr()?.port();
+
+    let listener = tokio_stream::wrappers::TcpListenerStream::new(socket.listen(1)?);
+
+    tokio::spawn(async {
+      tonic::transport::Server::builder()
+        .add_service(svc)
+        .serve_with_incoming(listener)
+        .await
+        .unwrap();
+    });
+
+    let uri = Uri::from_str(&format!("http://[::1]:{}", port)).unwrap();
+    let token = AsciiMetadataValue::from_static("rpcadmin");
+
+    Ok(Client::new(&Endpoint::from(uri), &token))
+  }
+
+  fn block_hash() -> ccd::BlockHash {
+    ccd::BlockHash {
+      value: hex::decode(HASH).unwrap(),
+    }
+  }
+
+  fn is_given_block(input: &ccd::BlockHashInput) -> bool {
+    matches!(
+      &input.block_hash_input,
+      Some(ccd::block_hash_input::BlockHashInput::Given(h)) if *h == block_hash(),
+    )
+  }
+
+  fn node_info(details: Details) -> ccd::NodeInfo {
+    ccd::NodeInfo {
+      peer_version: "4.5.0".into(),
+      local_time: None,
+      peer_uptime: Some(ccd::Duration {
+        value: 42,
+      }),
+      network_info: Some(ccd::node_info::NetworkInfo {
+        node_id: Some(ccd::PeerId {
+          value: "deadbeef".into(),
+        }),
+        peer_total_sent: 0,
+        peer_total_received: 0,
+        avg_bps_in: 10,
+        avg_bps_out: 20,
+      }),
+      details: Some(details),
+    }
+  }
+
+  #[test]
+  fn test_address_encoding() {
+    let address = decode_address(ADDRESS).unwrap();
+
+    assert_eq!(32, address.value.len());
+    assert_eq!(ADDRESS, encode_address(address));
+
+    assert!(matches!(decode_address("oops"), Err(Error::Decode(_))));
+  }
+
+  #[tokio::test]
+  async fn test_get_node_info() {
+    let mut service = MockService::new();
+
+    service.expect_get_node_info().times(1).returning(|_| {
+      Ok(Response::new(node_info(Details::Node(ccd::node_info::Node {
+        consensus_status: Some(ConsensusStatus::Active(ccd::node_info::BakerConsensusInfo {
+          baker_id: Some(ccd::BakerId {
+            value: 42,
+          }),
+          status: Some(CommitteeStatus::ActiveFinalizerCommitteeInfo(
+            ccd::node_info::baker_consensus_info::ActiveFinalizerCommitteeInfo {},
+          )),
+        })),
+      }))))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_node_info().await.unwrap();
+
+    assert_eq!(Some("deadbeef".to_string()), res.node_id);
+    assert_eq!(Some(42), res.baker_id);
+    assert_eq!(BakerCommittee::Active, res.baker_committee);
+    assert!(res.is_finalizer_committee);
+    assert_eq!(PEER_TYPE_NODE, res.peer_type);
+    assert!(res.consensus_running && res.consensus_baker_running);
+    assert_eq!(CONSENSUS_TYPE_ACTIVE, res.consensus_type);
+  }
+
+  #[tokio::test]
+  async fn test_get_node_info_wrong_keys() {
+    let mut service = MockService::new();
+
+    service.expect_get_node_info().times(1).returning(|_| {
+      Ok(Response::new(node_info(Details::Node(ccd::node_info::Node {
+        consensus_status: Some(ConsensusStatus::Active(ccd::node_info::BakerConsensusInfo {
+          baker_id: Some(ccd::BakerId {
+            value: 42,
+          }),
+          status: Some(CommitteeStatus::PassiveCommitteeInfo(
+            PassiveCommitteeInfo::AddedButWrongKeys as i32,
+          )),
+        })),
+      }))))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_node_info().await.unwrap();
+
+    assert_eq!(BakerCommittee::AddedButWrongKeys, res.baker_committee);
+    assert!(!res.is_finalizer_committee);
+    assert!(res.consensus_baker_running);
+  }
+
+  #[tokio::test]
+  async fn test_get_node_info_passive() {
+    let mut service = MockService::new();
+
+    service.expect_get_node_info().times(1).returning(|_| {
+      Ok(Response::new(node_info(Details::Node(ccd::node_info::Node {
+        consensus_status: Some(ConsensusStatus::Passive(ccd::Empty {})),
+      }))))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_node_info().await.unwrap();
+
+    assert_eq!(BakerCommittee::NotInCommittee, res.baker_committee);
+    assert!(res.consensus_running && !res.consensus_baker_running);
+    assert_eq!(CONSENSUS_TYPE_PASSIVE, res.consensus_type);
+  }
+
+  #[tokio::test]
+  async fn test_get_node_info_bootstrapper() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_node_info()
+      .times(1)
+      .returning(|_| Ok(Response::new(node_info(Details::Bootstrapper(ccd::Empty {})))));
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_node_info().await;
+
+    assert!(matches!(res, Ok(info) if info.peer_type == PEER_TYPE_BOOTSTRAPPER && !info.consensus_running));
+  }
+
+  #[tokio::test]
+  async fn test_get_node_uptime() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_node_info()
+      .times(1)
+      .returning(|_| Ok(Response::new(node_info(Details::Bootstrapper(ccd::Empty {})))));
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_node_uptime().await;
+
+    assert!(matches!(res, Ok(uptime) if uptime == 42));
+  }
+
+  #[tokio::test]
+  async fn test_get_node_stats() {
+    let mut service = MockService::new();
+
+    service.expect_get_peers_info().times(1).returning(|_| {
+      let peer = |latency| ccd::peers_info::Peer {
+        peer_id: Some(ccd::PeerId {
+          value: format!("peer-{}", latency),
+        }),
+        network_stats: Some(ccd::peers_info::peer::NetworkStats {
+          packets_sent: 0,
+          packets_received: 0,
+          latency,
+        }),
+      };
+
+      Ok(Response::new(ccd::PeersInfo {
+        peers: vec![peer(200), peer(100)],
+      }))
+    });
+
+    service
+      .expect_get_node_info()
+      .times(1)
+      .returning(|_| Ok(Response::new(node_info(Details::Bootstrapper(ccd::Empty {})))));
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_node_stats().await.unwrap();
+
+    assert_eq!(150.0, res.avg_latency);
+    assert_eq!(2, res.peer_count);
+    assert_eq!("peer-200", res.peers[0].node_id);
+    assert_eq!(10, res.avg_bps_in);
+    assert_eq!(20, res.avg_bps_out);
+  }
+
+  #[tokio::test]
+  async fn test_get_last_block() {
+    let mut service = MockService::new();
+
+    service.expect_get_consensus_info().times(1).returning(|_| {
+      Ok(Response::new(ccd::ConsensusInfo {
+        last_finalized_block: Some(block_hash()),
+        last_finalized_block_height: Some(ccd::AbsoluteBlockHeight {
+          value: 123,
+        }),
+        ..Default::default()
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_last_block().await;
+
+    assert!(matches!(res, Ok(block) if block.hash == HASH && block.height == 123));
+  }
+
+  #[tokio::test]
+  async fn test_get_consensus_status() {
+    let mut service = MockService::new();
+
+    service.expect_get_consensus_info().times(1).returning(|_| {
+      Ok(Response::new(ccd::ConsensusInfo {
+        best_block_height: Some(ccd::AbsoluteBlockHeight {
+          value: 125,
+        }),
+        last_finalized_block_height: Some(ccd::AbsoluteBlockHeight {
+          value: 123,
+        }),
+        last_finalized_time: Some(ccd::Timestamp {
+          value: 1665986400000,
+        }),
+        slot_duration: Some(ccd::Duration {
+          value: 250,
+        }),
+        epoch_duration: Some(ccd::Duration {
+          value: 3_600_000,
+        }),
+        protocol_version: ccd::ProtocolVersion::ProtocolVersion4 as i32,
+        ..Default::default()
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_consensus_status().await.unwrap();
+
+    assert_eq!(125, res.best_block_height);
+    assert_eq!(123, res.last_finalized_block_height);
+    assert_eq!(250, res.slot_duration_ms);
+    assert_eq!(4, res.protocol_version);
+    assert!(matches!(res.last_finalized_time, Some(t) if t.timestamp_millis() == 1665986400000));
+    assert!(matches!(res.era_genesis_time, None));
+  }
+
+  #[test]
+  fn test_decode_consensus_info() {
+    use prost::Message;
+
+    // Encoded with the field numbers of the node, including fields that are not
+    // declared here (doubles at 20 and 26, a counter at 23) and must be skipped.
+    let bytes: &[u8] = &[
+      0x3a, 0x02, 0x08, 0x7d, // best_block_height = 125
+      0x42, 0x02, 0x08, 0x7b, // last_finalized_block_height = 123
+      0xa1, 0x01, 0, 0, 0, 0, 0, 0, 0xf0, 0x3f, // block_arrive_latency_ema = 1.0
+      0xb8, 0x01, 0x05, // finalization_count = 5
+      0xc2, 0x01, 0x07, 0x08, 0x80, 0x9e, 0x9c, 0xa4, 0xbe, 0x30, // last_finalized_time
+      0xd1, 0x01, 0, 0, 0, 0, 0, 0, 0x00, 0x40, // block_receive_period_ema = 2.0
+      0xd8, 0x01, 0x03, // protocol_version = 4
+      0xf2, 0x01, 0x07, 0x08, 0x80, 0xc0, 0xbf, 0xe4, 0x91, 0x30, // current_era_genesis_time
+    ];
+
+    let info = ccd::ConsensusInfo::decode(bytes).unwrap();
+
+    assert_eq!(Some(125), info.best_block_height.map(|h| h.value));
+    assert_eq!(Some(123), info.last_finalized_block_height.map(|h| h.value));
+    assert_eq!(Some(1665986400000), info.last_finalized_time.map(|t| t.value));
+    assert_eq!(ccd::ProtocolVersion::ProtocolVersion4 as i32, info.protocol_version);
+    assert_eq!(Some(1654041600000), info.current_era_genesis_time.map(|t| t.value));
+  }
+
+  #[test]
+  fn test_decode_account_transfer() {
+    use prost::Message;
+
+    // A contract update (field 4) followed by an account transfer (field 5) of 100.
+    let bytes: &[u8] = &[0x22, 0x00, 0x2a, 0x04, 0x0a, 0x02, 0x08, 0x64];
+
+    let effects = ccd::AccountTransactionEffects::decode(bytes).unwrap();
+
+    assert!(
+      matches!(effects.effect, Some(Effect::AccountTransfer(t)) if matches!(t.amount, Some(ccd::Amount { value: 100 })))
+    );
+  }
+
+  #[tokio::test]
+  async fn test_get_genesis_hash() {
+    let mut service = MockService::new();
+
+    service.expect_get_consensus_info().times(1).returning(|_| {
+      Ok(Response::new(ccd::ConsensusInfo {
+        genesis_block: Some(block_hash()),
+        ..Default::default()
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_genesis_hash().await;
+
+    assert!(matches!(res, Ok(hash) if hash == HASH));
+  }
+
+  #[tokio::test]
+  async fn test_get_last_block_missing_field() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_consensus_info()
+      .times(1)
+      .returning(|_| Ok(Response::new(ccd::ConsensusInfo::default())));
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_last_block().await;
+
+    assert!(matches!(res, Err(Error::Decode(_))));
+  }
+
+  #[tokio::test]
+  async fn test_get_finalized_blocks() {
+    let mut service = MockService::new();
+
+    service.expect_get_finalized_blocks().times(1).returning(|_| {
+      let blocks = vec![
+        Ok(ccd::FinalizedBlockInfo {
+          hash: Some(ccd::BlockHash {
+            value: hex::decode(HASH).unwrap(),
+          }),
+          height: Some(ccd::AbsoluteBlockHeight {
+            value: 123,
+          }),
+        }),
+        Ok(ccd::FinalizedBlockInfo::default()),
+      ];
+
+      Ok(Response::new(Box::pin(tokio_stream::iter(blocks)) as FinalizedStream))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let mut stream = client.get_finalized_blocks().await.unwrap();
+
+    let res = stream.next().await;
+    assert!(matches!(res, Some(Ok(block)) if block.hash == HASH && block.height == 123));
+
+    assert!(matches!(stream.next().await, Some(Err(Error::Decode(_)))));
+    assert!(matches!(stream.next().await, None));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_at_height() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_blocks_at_height()
+      .withf(|r| {
+        matches!(
+          &r.get_ref().blocks_at_height,
+          Some(BlocksAtHeight::Relative(r))
+            if r.genesis_index.as_ref().unwrap().value == 3 && r.height.as_ref().unwrap().value == 42 && r.restrict,
+        )
+      })
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::BlocksAtHeightResponse {
+          blocks: vec![block_hash()],
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_block_at_height(Some(3), 42).await;
+
+    assert!(matches!(res, Ok(hash) if hash == Some(HASH.to_string())));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_at_negative_height() {
+    let client = init(MockService::new()).await.unwrap();
+
+    let res = client.get_block_at_height(None, -1).await;
+
+    assert!(matches!(res, Err(Error::Grpc(status)) if status.code() == tonic::Code::InvalidArgument));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_at_absolute_height() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_blocks_at_height()
+      .withf(|r| {
+        matches!(
+          &r.get_ref().blocks_at_height,
+          Some(BlocksAtHeight::Absolute(a)) if a.height.as_ref().unwrap().value == 42,
+        )
+      })
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::BlocksAtHeightResponse {
+          blocks: vec![],
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_block_at_height(None, 42).await;
+
+    assert!(matches!(res, Ok(None)));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_info() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_block_info()
+      .withf(|r| is_given_block(r.get_ref()))
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::BlockInfo {
+          hash: Some(block_hash()),
+          height: Some(ccd::AbsoluteBlockHeight {
+            value: 3,
+          }),
+          genesis_index: Some(ccd::GenesisIndex {
+            value: 3,
+          }),
+          era_block_height: Some(ccd::BlockHeight {
+            value: 1,
+          }),
+          slot_time: Some(ccd::Timestamp {
+            value: 1660899820000,
+          }),
+          baker: Some(ccd::BakerId {
+            value: 42,
+          }),
+          finalized: true,
+          protocol_version: ccd::ProtocolVersion::ProtocolVersion4 as i32,
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_block_info(HASH).await.unwrap();
+
+    assert!(res.finalized);
+    assert_eq!(3, res.block_height);
+    assert_eq!(Some(42), res.block_baker);
+    assert_eq!(1660899820000, res.block_slot_time.timestamp_millis());
+    assert_eq!(3, res.genesis_index);
+    assert_eq!(1, res.era_block_height);
+    assert_eq!(4, res.protocol_version);
+  }
+
+  #[tokio::test]
+  async fn test_get_block_info_bad_hash() {
+    let client = init(MockService::new()).await.unwrap();
+
+    let res = client.get_block_info(":hash:").await;
+
+    assert!(matches!(res, Err(Error::Decode(_))));
+  }
+
+  #[tokio::test]
+  async fn test_get_block_summary() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_block_special_events()
+      .withf(|r| is_given_block(r.get_ref()))
+      .times(1)
+      .returning(|_| {
+        let events = vec![
+          Ok(ccd::BlockSpecialEvent {
+            event: Some(SpecialEvent::Mint(ccd::block_special_event::Mint {
+              foundation_account: Some(decode_address(ADDRESS).unwrap()),
+              ..Default::default()
+            })),
+          }),
+          Ok(ccd::BlockSpecialEvent {
+            event: Some(SpecialEvent::FinalizationRewards(
+              ccd::block_special_event::FinalizationRewards {
+                finalization_rewards: Some(ccd::block_special_event::AccountAmounts {
+                  entries: vec![ccd::block_special_event::account_amounts::Entry {
+                    account: Some(decode_address(ADDRESS).unwrap()),
+                    amount: Some(ccd::Amount {
+                      value: 7,
+                    }),
+                  }],
+                }),
+                remainder: None,
+              },
+            )),
+          }),
+          Ok(ccd::BlockSpecialEvent {
+            event: Some(SpecialEvent::PaydayAccountReward(
+              ccd::block_special_event::PaydayAccountReward {
+                account: Some(decode_address(ADDRESS).unwrap()),
+                transaction_fees: Some(ccd::Amount {
+                  value: 125,
+                }),
+                baker_reward: Some(ccd::Amount {
+                  value: 25,
+                }),
+                finalization_reward: Some(ccd::Amount {
+                  value: 5,
+                }),
+              },
+            )),
+          }),
+        ];
+
+        Ok(Response::new(Box::pin(tokio_stream::iter(events)) as EventStream))
+      });
+
+    service
+      .expect_get_block_transaction_events()
+      .withf(|r| is_given_block(r.get_ref()))
+      .times(1)
+      .returning(|_| {
+        let transfer = ccd::account_transaction_effects::AccountTransfer {
+          amount: Some(ccd::Amount {
+            value: 1000,
+          }),
+          receiver: Some(decode_address(ADDRESS).unwrap()),
+          memo: Some(ccd::Memo {
+            value: vec![0],
+          }),
+        };
+
+        let items = vec![
+          Ok(ccd::BlockItemSummary {
+            energy_cost: None,
+            hash: Some(ccd::TransactionHash {
+              value: vec![0xab],
+            }),
+            details: Some(ItemDetails::AccountTransaction(ccd::AccountTransactionDetails {
+              cost: Some(ccd::Amount {
+                value: 12,
+              }),
+              sender: Some(decode_address(ADDRESS).unwrap()),
+              effects: Some(ccd::AccountTransactionEffects {
+                effect: Some(Effect::AccountTransfer(transfer)),
+              }),
+            })),
+          }),
+          Ok(ccd::BlockItemSummary {
+            energy_cost: None,
+            hash: Some(ccd::TransactionHash {
+              value: vec![0xcd],
+            }),
+            details: Some(ItemDetails::AccountTransaction(ccd::AccountTransactionDetails {
+              cost: None,
+              sender: Some(decode_address(ADDRESS).unwrap()),
+              effects: Some(ccd::AccountTransactionEffects {
+                effect: Some(Effect::None(Default::default())),
+              }),
+            })),
+          }),
+          // A block item that is not an account transaction is ignored.
+          Ok(ccd::BlockItemSummary::default()),
+        ];
+
+        Ok(Response::new(Box::pin(tokio_stream::iter(items)) as TransactionStream))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_block_summary(HASH).await.unwrap();
+
+    assert_eq!(2, res.transaction_summaries.len());
+
+    assert_eq!(
+      TransactionSummary {
+        hash: "ab".to_string(),
+        sender: Some(ADDRESS.to_string()),
+        cost: Decimal::from(12),
+        kind: TransactionType {
+          kind: "accountTransaction".to_string(),
+          contents: Some("transferWithMemo".to_string()),
+        },
+        result: TransactionResult::Success {
+          events: vec![TransactionEvent::Transferred {
+            amount: Decimal::from(1000),
+            from: Address::AddressAccount(ADDRESS.to_string()),
+            to: Address::AddressAccount(ADDRESS.to_string()),
+          }],
+        },
+      },
+      res.transaction_summaries[0]
+    );
+
+    assert_eq!(TransactionResult::Reject, res.transaction_summaries[1].result);
+
+    assert_eq!(3, res.special_events.len());
+    assert!(matches!(&res.special_events[0], Event::Mint { foundation_account, .. } if foundation_account == ADDRESS));
+
+    assert_eq!(
+      Event::FinalizationRewards {
+        finalization_rewards: vec![AccountAmount {
+          address: ADDRESS.to_string(),
+          amount: Decimal::from(7),
+        }],
+        remainder: Decimal::ZERO,
+      },
+      res.special_events[1]
+    );
+
+    assert_eq!(
+      Event::PaydayAccountReward {
+        account: ADDRESS.to_string(),
+        transaction_fees: Decimal::from(125),
+        baker_reward: Decimal::from(25),
+        finalization_reward: Decimal::from(5),
+      },
+      res.special_events[2]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_get_block_summary_missing_account() {
+    let mut service = MockService::new();
+
+    service.expect_get_block_special_events().returning(|_| {
+      let events = vec![Ok(ccd::BlockSpecialEvent {
+        event: Some(SpecialEvent::PaydayAccountReward(Default::default())),
+      })];
+
+      Ok(Response::new(Box::pin(tokio_stream::iter(events)) as EventStream))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_block_summary(HASH).await;
+
+    assert!(matches!(res, Err(Error::Decode(_))));
+  }
+
+  #[tokio::test]
+  async fn test_get_account_info() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_account_info()
+      .withf(|r| {
+        let account = r.get_ref().account_identifier.as_ref().unwrap();
+
+        is_given_block(r.get_ref().block_hash.as_ref().unwrap())
+          && matches!(
+            &account.account_identifier_input,
+            Some(ccd::account_identifier_input::AccountIdentifierInput::Address(a)) if encode_address(a.clone()) == ADDRESS,
+          )
+      })
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::AccountInfo {
+          sequence_number: Some(ccd::SequenceNumber {
+            value: 1,
+          }),
+          amount: Some(ccd::Amount {
+            value: 256,
+          }),
+          index: Some(ccd::AccountIndex {
+            value: 2,
+          }),
+          stake: Some(ccd::AccountStakingInfo {
+            staking_info: Some(StakingInfo::Baker(ccd::account_staking_info::StakeBaker {
+              staked_amount: Some(ccd::Amount {
+                value: 12,
+              }),
+              restake_earnings: true,
+              baker_info: Some(ccd::BakerInfo {
+                baker_id: Some(ccd::BakerId {
+                  value: 42,
+                }),
+              }),
+            })),
+          }),
+          address: Some(decode_address(ADDRESS).unwrap()),
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_account_info(HASH, ADDRESS).await.unwrap();
+
+    assert_eq!("256", res.account_amount.to_string());
+    assert_eq!(ADDRESS, res.account_address);
+
+    let baker = res.account_baker.unwrap();
+    assert_eq!("12", baker.staked_amount.to_string());
+    assert_eq!(42, baker.baker_id);
+    assert!(res.account_delegation.is_none());
+  }
+
+  #[tokio::test]
+  async fn test_get_account_info_invalid_baker_id() {
+    let mut service = MockService::new();
+
+    service.expect_get_account_info().times(1).returning(|_| {
+      Ok(Response::new(ccd::AccountInfo {
+        stake: Some(ccd::AccountStakingInfo {
+          staking_info: Some(StakingInfo::Baker(ccd::account_staking_info::StakeBaker {
+            baker_info: Some(ccd::BakerInfo {
+              baker_id: Some(ccd::BakerId {
+                value: u64::from(u32::MAX) + 1,
+              }),
+            }),
+            ..Default::default()
+          })),
+        }),
+        address: Some(decode_address(ADDRESS).unwrap()),
+        ..Default::default()
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_account_info(HASH, ADDRESS).await;
+
+    assert!(matches!(res, Err(Error::Decode(_))));
+  }
+
+  #[tokio::test]
+  async fn test_get_account_info_delegator() {
+    let mut service = MockService::new();
+
+    service.expect_get_account_info().times(1).returning(|_| {
+      Ok(Response::new(ccd::AccountInfo {
+        amount: Some(ccd::Amount {
+          value: 256,
+        }),
+        stake: Some(ccd::AccountStakingInfo {
+          staking_info: Some(StakingInfo::Delegator(ccd::account_staking_info::StakeDelegator {
+            staked_amount: Some(ccd::Amount {
+              value: 100,
+            }),
+            restake_earnings: true,
+            target: Some(ccd::DelegationTarget {
+              target: Some(Target::Baker(ccd::BakerId {
+                value: 42,
+              })),
+            }),
+          })),
+        }),
+        address: Some(decode_address(ADDRESS).unwrap()),
+        ..Default::default()
+      }))
+    });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_account_info(HASH, ADDRESS).await.unwrap();
+
+    assert!(res.account_baker.is_none());
+    assert_eq!(Decimal::from(100), res.get_staked_amount());
+
+    let delegation = res.account_delegation.unwrap();
+    assert!(delegation.restake_earnings);
+    assert_eq!(
+      DelegationTarget::Baker {
+        baker_id: 42
+      },
+      delegation.delegation_target
+    );
+  }
+
+  #[tokio::test]
+  async fn test_get_account_info_no_network() {
+    let client = Client::new(
+      &Endpoint::from_static("http://[::1]:8888"),
+      &AsciiMetadataValue::from_static(""),
+    );
+
+    let res = client.get_account_info(HASH, ADDRESS).await;
+
+    assert!(matches!(res, Err(Error::Grpc(_))));
+  }
+
+  #[tokio::test]
+  async fn test_get_baker() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_election_info()
+      .withf(|r| is_given_block(r.get_ref()))
+      .times(2)
+      .returning(|_| {
+        Ok(Response::new(ccd::ElectionInfo {
+          baker_election_info: vec![ccd::election_info::Baker {
+            baker: Some(ccd::BakerId {
+              value: 1,
+            }),
+            account: Some(decode_address(ADDRESS).unwrap()),
+            lottery_power: 0.02,
+          }],
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_baker(HASH, ADDRESS).await;
+
+    assert!(matches!(res, Ok(Some(baker)) if baker.baker_id == 1 && baker.baker_lottery_power == 0.02));
+
+    let res = client
+      .get_baker(HASH, "2xBvQb4QFBzCDcRdyuGzPDcWSMvDDisfMUnXeRnNJFdWqBBmK7")
+      .await;
+
+    assert!(matches!(res, Ok(None)));
+  }
+
+  #[tokio::test]
+  async fn test_get_pool_status() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_pool_info()
+      .withf(|r| {
+        is_given_block(r.get_ref().block_hash.as_ref().unwrap())
+          && matches!(&r.get_ref().baker, Some(b) if b.value == 42)
+      })
+      .times(1)
+      .returning(|_| {
+        Ok(Response::new(ccd::PoolInfoResponse {
+          baker: Some(ccd::BakerId {
+            value: 42,
+          }),
+          address: Some(decode_address(ADDRESS).unwrap()),
+          equity_capital: Some(ccd::Amount {
+            value: 1000,
+          }),
+          delegated_capital: Some(ccd::Amount {
+            value: 250,
+          }),
+          delegated_capital_cap: Some(ccd::Amount {
+            value: 3000,
+          }),
+          pool_info: Some(ccd::BakerPoolInfo {
+            open_status: ccd::OpenStatus::ClosedForNew as i32,
+            url: String::new(),
+            commission_rates: Some(ccd::CommissionRates {
+              finalization: Some(ccd::AmountFraction {
+                parts_per_hundred_thousand: 100_000,
+              }),
+              baking: Some(ccd::AmountFraction {
+                parts_per_hundred_thousand: 10_000,
+              }),
+              transaction: Some(ccd::AmountFraction {
+                parts_per_hundred_thousand: 5_000,
+              }),
+            }),
+          }),
+          current_payday_info: Some(ccd::PoolCurrentPaydayInfo {
+            blocks_baked: 12,
+            finalization_live: true,
+            lottery_power: 0.002,
+            ..Default::default()
+          }),
+        }))
+      });
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_pool_status(HASH, 42).await.unwrap().unwrap();
+
+    assert_eq!(ADDRESS, res.baker_address);
+    assert_eq!(Decimal::from(3000), res.delegated_capital_cap);
+    assert_eq!(OpenStatus::ClosedForNew, res.pool_info.open_status);
+    assert_eq!(
+      CommissionRates {
+        transaction_commission: 0.05,
+        finalization_commission: 1.0,
+        baking_commission: 0.1,
+      },
+      res.pool_info.commission_rates
+    );
+    assert!(matches!(res.current_payday_status, Some(s) if s.blocks_baked == 12));
+  }
+
+  #[tokio::test]
+  async fn test_get_pool_status_not_found() {
+    let mut service = MockService::new();
+
+    service
+      .expect_get_pool_info()
+      .times(1)
+      .returning(|_| Err(Status::not_found("unknown baker")));
+
+    let client = init(service).await.unwrap();
+
+    let res = client.get_pool_status(HASH, 42).await;
+
+    assert!(matches!(res, Ok(None)));
+  }
+}
@@ -0,0 +1,382 @@
+use super::bitfinex::{Price, PriceClient};
+use super::node::{
+  AccountInfo, Baker, Block, BlockInfo, BlockStream, BlockSummary, ConsensusStatus, NodeClient, NodeInfo, NodeStats,
+  PoolStatus,
+};
+use super::{ClientMetrics, DynNodeClient, Error, Result};
+use crate::model::Pair;
+use log::warn;
+use rand::Rng;
+use serde::Deserialize;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+use tonic::Code;
+
+/// A policy that defines how many times and how often a call is retried when
+/// it fails with an error that can be retried.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+  /// Maximum number of retries after the first attempt.
+  pub max_retries: u32,
+  /// Backoff before the first retry.
+  pub initial_backoff_ms: u64,
+  /// Upper bound of the backoff between two attempts.
+  pub max_backoff_ms: u64,
+  /// Factor applied to the backoff after each attempt.
+  pub multiplier: f64,
+  /// Ratio of the backoff that is randomized, between 0 and 1.
+  pub jitter: f64,
+  /// Time allowed for a call, including all the attempts.
+  pub deadline_ms: u64,
+}
+
+impl RetryPolicy {
+  /// It returns the backoff before the given retry, starting at zero.
+  pub fn backoff(&self, retry: u32) -> Duration {
+    let backoff = self.initial_backoff_ms as f64 * self.multiplier.powi(retry as i32);
+    let backoff = backoff.min(self.max_backoff_ms as f64);
+
+    let jitter = self.jitter.clamp(0.0, 1.0);
+    let factor = 1.0 - jitter * rand::thread_rng().gen::<f64>();
+
+    Duration::from_millis((backoff * factor) as u64)
+  }
+
+  pub fn deadline(&self) -> Duration {
+    Duration::from_millis(self.deadline_ms)
+  }
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_retries: 3,
+      initial_backoff_ms: 200,
+      max_backoff_ms: 5_000,
+      multiplier: 2.0,
+      jitter: 0.2,
+      deadline_ms: 30_000,
+    }
+  }
+}
+
+/// It returns true when the error is transient and the call can be tried
+/// again.
+fn is_retryable(e: &Error) -> bool {
+  match e {
+    Error::Grpc(status) => matches!(
+      status.code(),
+      Code::Unavailable | Code::DeadlineExceeded | Code::ResourceExhausted | Code::Aborted
+    ),
+    Error::Http(e) => {
+      e.is_timeout()
+        || e.is_connect()
+        || matches!(e.status(), Some(s) if s.is_server_error() || s == reqwest::StatusCode::TOO_MANY_REQUESTS)
+    }
+    _ => false,
+  }
+}
+
+/// A client that wraps another one to retry the calls that fail with a
+/// transient error, following a retry policy.
+#[derive(Clone)]
+pub struct RetryClient<C> {
+  inner: C,
+  policy: RetryPolicy,
+  retries: Arc<AtomicU64>,
+}
+
+impl<C> RetryClient<C> {
+  pub fn new(inner: C, policy: RetryPolicy) -> Self {
+    Self {
+      inner,
+      policy,
+      retries: Arc::new(AtomicU64::new(0)),
+    }
+  }
+
+  /// It returns the number of retries since the client has been created.
+  pub fn retries(&self) -> u64 {
+    self.retries.load(Ordering::Relaxed)
+  }
+
+  /// It executes the call until it succeeds, the error cannot be retried, or
+  /// the policy does not allow another attempt.
+  async fn run<'a, T, F, Fut>(&'a self, f: F) -> Result<T>
+  where
+    F: Fn(&'a C) -> Fut,
+    Fut: Future<Output = Result<T>>,
+  {
+    let deadline = Instant::now() + self.policy.deadline();
+    let mut retry = 0;
+
+    loop {
+      let res = time::timeout_at(deadline, f(&self.inner))
+        .await
+        .map_err(|_| Error::Timeout(self.policy.deadline()))?;
+
+      match res {
+        Err(e) if retry < self.policy.max_retries && is_retryable(&e) => {
+          let backoff = self.policy.backoff(retry);
+
+          if Instant::now() + backoff >= deadline {
+            return Err(e);
+          }
+
+          warn!("client call failed and is retried in {:?}: {}", backoff, e);
+
+          self.retries.fetch_add(1, Ordering::Relaxed);
+          retry += 1;
+
+          time::sleep(backoff).await;
+        }
+        res => return res,
+      }
+    }
+  }
+}
+
+impl RetryClient<DynNodeClient> {
+  pub fn new_node_client(inner: DynNodeClient, policy: RetryPolicy) -> DynNodeClient {
+    Arc::new(Self::new(inner, policy))
+  }
+}
+
+#[async_trait]
+impl NodeClient for RetryClient<DynNodeClient> {
+  fn get_endpoint(&self) -> String {
+    self.inner.get_endpoint()
+  }
+
+  fn get_metrics(&self) -> ClientMetrics {
+    let metrics = ClientMetrics {
+      retries: self.retries(),
+      ..ClientMetrics::default()
+    };
+
+    metrics + self.inner.get_metrics()
+  }
+
+  async fn get_node_info(&self) -> Result<NodeInfo> {
+    self.run(|c| c.get_node_info()).await
+  }
+
+  async fn get_node_uptime(&self) -> Result<u64> {
+    self.run(|c| c.get_node_uptime()).await
+  }
+
+  async fn get_node_stats(&self) -> Result<NodeStats> {
+    self.run(|c| c.get_node_stats()).await
+  }
+
+  async fn get_last_block(&self) -> Result<Block> {
+    self.run(|c| c.get_last_block()).await
+  }
+
+  async fn get_consensus_status(&self) -> Result<ConsensusStatus> {
+    self.run(|c| c.get_consensus_status()).await
+  }
+
+  async fn get_genesis_hash(&self) -> Result<String> {
+    self.run(|c| c.get_genesis_hash()).await
+  }
+
+  async fn get_finalized_blocks(&self) -> Result<BlockStream> {
+    self.run(|c| c.get_finalized_blocks()).await
+  }
+
+  async fn get_block_at_height(&self, genesis_index: Option<u32>, height: i64) -> Result<Option<String>> {
+    self.run(|c| c.get_block_at_height(genesis_index, height)).await
+  }
+
+  async fn get_block_info(&self, block_hash: &str) -> Result<BlockInfo> {
+    self.run(|c| c.get_block_info(block_hash)).await
+  }
+
+  async fn get_block_summary(&self, block_hash: &str) -> Result<BlockSummary> {
+    self.run(|c| c.get_block_summary(block_hash)).await
+  }
+
+  async fn get_account_info(&self, block: &str, address: &str) -> Result<AccountInfo> {
+    self.run(|c| c.get_account_info(block, address)).await
+  }
+
+  async fn get_baker(&self, block: &str, address: &str) -> Result<Option<Baker>> {
+    self.run(|c| c.get_baker(block, address)).await
+  }
+
+  async fn get_pool_status(&self, block: &str, baker_id: u64) -> Result<Option<PoolStatus>> {
+    self.run(|c| c.get_pool_status(block, baker_id)).await
+  }
+}
+
+#[async_trait]
+impl<C: PriceClient + Sync + Send> PriceClient for RetryClient<C> {
+  fn get_metrics(&self) -> ClientMetrics {
+    let metrics = ClientMetrics {
+      retries: self.retries(),
+      ..ClientMetrics::default()
+    };
+
+    metrics + self.inner.get_metrics()
+  }
+
+  async fn get_prices(&self, pairs: Vec<Pair>) -> Result<Vec<Price>> {
+    self.run(|c| c.get_prices(pairs.clone())).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::node::MockNodeClient;
+  use mockall::Sequence;
+  use tonic::Status;
+
+  fn policy() -> RetryPolicy {
+    RetryPolicy {
+      max_retries: 2,
+      initial_backoff_ms: 1,
+      max_backoff_ms: 5,
+      multiplier: 2.0,
+      jitter: 0.5,
+      deadline_ms: 1_000,
+    }
+  }
+
+  #[test]
+  fn test_backoff() {
+    let policy = RetryPolicy {
+      jitter: 0.0,
+      ..RetryPolicy::default()
+    };
+
+    assert_eq!(Duration::from_millis(200), policy.backoff(0));
+    assert_eq!(Duration::from_millis(400), policy.backoff(1));
+    assert_eq!(Duration::from_millis(5_000), policy.backoff(10));
+
+    let policy = RetryPolicy {
+      jitter: 0.5,
+      ..RetryPolicy::default()
+    };
+
+    let backoff = policy.backoff(0);
+    assert!(backoff >= Duration::from_millis(100) && backoff <= Duration::from_millis(200));
+  }
+
+  #[test]
+  fn test_is_retryable() {
+    assert!(is_retryable(&Error::Grpc(Status::unavailable(""))));
+    assert!(is_retryable(&Error::Grpc(Status::deadline_exceeded(""))));
+    assert!(!is_retryable(&Error::Grpc(Status::not_found(""))));
+    assert!(!is_retryable(&Error::Grpc(Status::invalid_argument(""))));
+    assert!(!is_retryable(&Error::Decode("".into())));
+  }
+
+  #[tokio::test]
+  async fn test_retry_until_success() {
+    let mut client = MockNodeClient::new();
+    let mut seq = Sequence::new();
+
+    client
+      .expect_get_node_uptime()
+      .times(2)
+      .in_sequence(&mut seq)
+      .returning(|| Err(Error::Grpc(Status::unavailable("down"))));
+
+    client
+      .expect_get_node_uptime()
+      .times(1)
+      .in_sequence(&mut seq)
+      .returning(|| Ok(42));
+
+    client.expect_get_metrics().returning(ClientMetrics::default);
+
+    let client = RetryClient::new_node_client(Arc::new(client), policy());
+
+    assert!(matches!(client.get_node_uptime().await, Ok(42)));
+    assert_eq!(2, client.get_metrics().retries);
+  }
+
+  #[tokio::test]
+  async fn test_retry_exhausted() {
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_last_block()
+      .times(3)
+      .returning(|| Err(Error::Grpc(Status::unavailable("down"))));
+
+    let client = RetryClient::new(Arc::new(client) as DynNodeClient, policy());
+
+    let res = client.get_last_block().await;
+
+    assert!(matches!(res, Err(Error::Grpc(status)) if status.code() == Code::Unavailable));
+    assert_eq!(2, client.retries());
+  }
+
+  #[tokio::test]
+  async fn test_no_retry() {
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_block_info()
+      .times(1)
+      .returning(|_| Err(Error::Grpc(Status::not_found("unknown block"))));
+
+    let client = RetryClient::new(Arc::new(client) as DynNodeClient, policy());
+
+    let res = client.get_block_info(":hash:").await;
+
+    assert!(matches!(res, Err(Error::Grpc(status)) if status.code() == Code::NotFound));
+    assert_eq!(0, client.retries());
+  }
+
+  /// A price client that always fails after a delay.
+  struct SlowClient {
+    calls: AtomicU64,
+  }
+
+  #[async_trait]
+  impl PriceClient for SlowClient {
+    fn get_metrics(&self) -> ClientMetrics {
+      ClientMetrics::default()
+    }
+
+    async fn get_prices(&self, _: Vec<Pair>) -> Result<Vec<Price>> {
+      self.calls.fetch_add(1, Ordering::Relaxed);
+
+      time::sleep(Duration::from_millis(5)).await;
+
+      Err(Error::Grpc(Status::unavailable("down")))
+    }
+  }
+
+  #[tokio::test]
+  async fn test_deadline() {
+    let client = RetryClient::new(
+      SlowClient {
+        calls: AtomicU64::new(0),
+      },
+      RetryPolicy {
+        max_retries: 10,
+        initial_backoff_ms: 30,
+        max_backoff_ms: 30,
+        multiplier: 1.0,
+        jitter: 0.0,
+        deadline_ms: 50,
+      },
+    );
+
+    let res = client.get_prices(vec![(1, "CCD", "USD").into()]).await;
+
+    assert!(matches!(res, Err(Error::Grpc(_))));
+    assert_eq!(2, client.inner.calls.load(Ordering::Relaxed));
+    assert_eq!(1, client.retries());
+    assert_eq!(1, client.get_metrics().retries);
+  }
+}
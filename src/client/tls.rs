@@ -0,0 +1,90 @@
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity, Uri};
+
+/// A configuration of the TLS layer of the channel to a node. The files are
+/// expected to be PEM-encoded.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct TlsConfig {
+  /// A bundle of the certificate authorities to trust instead of the system
+  /// ones, typically for a node using a self-signed certificate.
+  pub ca_certificate: Option<PathBuf>,
+  /// A client certificate presented to the node for mutual TLS.
+  pub certificate: Option<PathBuf>,
+  /// The private key of the client certificate.
+  pub key: Option<PathBuf>,
+  /// A domain name to verify the node certificate against instead of the host
+  /// of the URI.
+  pub domain_name: Option<String>,
+}
+
+impl TlsConfig {
+  /// It returns the endpoint of the node with the TLS layer configured.
+  pub fn endpoint(&self, uri: &Uri) -> io::Result<Endpoint> {
+    Channel::builder(uri.clone())
+      .tls_config(self.as_client_config()?)
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+  }
+
+  fn as_client_config(&self) -> io::Result<ClientTlsConfig> {
+    let mut cfg = ClientTlsConfig::new();
+
+    if let Some(path) = &self.ca_certificate {
+      cfg = cfg.ca_certificate(Certificate::from_pem(read(path)?));
+    }
+
+    match (&self.certificate, &self.key) {
+      (Some(cert), Some(key)) => {
+        cfg = cfg.identity(Identity::from_pem(read(cert)?, read(key)?));
+      }
+      (None, None) => {}
+      _ => {
+        return Err(io::Error::new(
+          io::ErrorKind::InvalidInput,
+          "both the client certificate and key are required",
+        ))
+      }
+    }
+
+    if let Some(domain_name) = &self.domain_name {
+      cfg = cfg.domain_name(domain_name);
+    }
+
+    Ok(cfg)
+  }
+}
+
+fn read(path: &Path) -> io::Result<Vec<u8>> {
+  fs::read(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_missing_key() {
+    let cfg = TlsConfig {
+      certificate: Some(PathBuf::from("client.pem")),
+      ..TlsConfig::default()
+    };
+
+    let res = cfg.endpoint(&Uri::from_static("https://127.0.0.1:10000"));
+
+    assert!(matches!(res, Err(e) if e.kind() == io::ErrorKind::InvalidInput));
+  }
+
+  #[test]
+  fn test_missing_file() {
+    let cfg = TlsConfig {
+      ca_certificate: Some(PathBuf::from("/non/existing/ca.pem")),
+      ..TlsConfig::default()
+    };
+
+    let res = cfg.endpoint(&Uri::from_static("https://127.0.0.1:10000"));
+
+    assert!(matches!(res, Err(e) if e.kind() == io::ErrorKind::NotFound));
+  }
+}
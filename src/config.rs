@@ -1,14 +1,25 @@
-use crate::client::{node::Client, DynNodeClient};
+use crate::client::bitfinex::BitfinexClient;
+use crate::client::retry::{RetryClient, RetryPolicy};
+use crate::client::tls::TlsConfig;
+use crate::client::{failover::FailoverClient, node, node_v2, DynNodeClient, DynPriceClient};
+use crate::notification::{email::EmailConfig, webhook::WebhookConfig, Notifier};
 use jsonwebtoken::{errors, DecodingKey, EncodingKey};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{self, prelude::*};
 use std::net::SocketAddr;
-use tonic::{metadata::AsciiMetadataValue, transport::Uri};
+use std::sync::Arc;
+use tonic::{
+  metadata::AsciiMetadataValue,
+  transport::{Endpoint, Uri},
+};
 
 const DEFAULT_SECRET: &str = "IUBePnVgKXFPc2QzZTRuSykuQic5IUt8QlY=";
 
+const MAINNET_GENESIS_HASH: &str = "9dd9ca4d19e9393877d2c44b70f89acbfc0883c2243e5eeaecc0d1cd0503f478";
+const TESTNET_GENESIS_HASH: &str = "4221332d34e1694168c2a0c0b3fd0f273809612cb13d000d5c2e00e85f50f796";
+
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Job {
@@ -17,6 +28,7 @@ pub enum Job {
   BlockFetcher,
   StatusChecker,
   RewardRepairer,
+  PoolRefresher,
 }
 
 impl Job {
@@ -27,10 +39,27 @@ impl Job {
       Self::BlockFetcher => "block-fetcher",
       Self::StatusChecker => "status-checker",
       Self::RewardRepairer => "reward-repairer",
+      Self::PoolRefresher => "pool-refresher",
     }
   }
 }
 
+/// An enumeration of the gRPC APIs exposed by a Concordium node.
+#[derive(PartialEq, Eq, Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeApi {
+  /// The legacy P2P service which returns most of the data as JSON.
+  Legacy,
+  /// The typed API of the `concordium.v2.Queries` service.
+  V2,
+}
+
+impl Default for NodeApi {
+  fn default() -> Self {
+    Self::Legacy
+  }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct ClientCfg {
   #[serde(with = "serde_with::rust::display_fromstr")]
@@ -38,11 +67,38 @@ pub struct ClientCfg {
 
   #[serde(with = "serde_with::rust::display_fromstr")]
   token: AsciiMetadataValue,
+
+  #[serde(default)]
+  api: NodeApi,
+
+  retry: Option<RetryPolicy>,
+
+  tls: Option<TlsConfig>,
 }
 
 impl ClientCfg {
-  pub fn as_client(&self) -> DynNodeClient {
-    Client::new(&self.uri, &self.token)
+  pub fn as_client(&self) -> io::Result<DynNodeClient> {
+    Ok(with_retry(self.as_endpoint()?, self.retry.as_ref()))
+  }
+
+  /// It creates the client of the endpoint without the retry policy.
+  fn as_endpoint(&self) -> io::Result<DynNodeClient> {
+    let endpoint = match &self.tls {
+      Some(tls) => tls.endpoint(&self.uri)?,
+      None => Endpoint::from(self.uri.clone()),
+    };
+
+    Ok(match self.api {
+      NodeApi::Legacy => node::Client::new(&endpoint, &self.token),
+      NodeApi::V2 => node_v2::Client::new(&endpoint, &self.token),
+    })
+  }
+}
+
+fn with_retry(client: DynNodeClient, policy: Option<&RetryPolicy>) -> DynNodeClient {
+  match policy {
+    Some(policy) => RetryClient::new_node_client(client, policy.clone()),
+    None => client,
   }
 }
 
@@ -53,14 +109,300 @@ impl Default for ClientCfg {
     Self {
       uri: Uri::from_static("http://127.0.0.1:10000"),
       token: AsciiMetadataValue::from_static("rpcadmin"),
+      api: NodeApi::default(),
+      retry: None,
+      tls: None,
     }
   }
 }
 
+/// A configuration of the node client which is either a single endpoint, or a
+/// list of endpoints ordered by priority to fail over when one is unavailable.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum NodeClientCfg {
+  Single(ClientCfg),
+  Multiple(Vec<ClientCfg>),
+}
+
+impl NodeClientCfg {
+  pub fn as_client(&self) -> io::Result<DynNodeClient> {
+    match self {
+      Self::Single(cfg) => cfg.as_client(),
+      Self::Multiple(cfgs) if cfgs.len() == 1 => cfgs[0].as_client(),
+      Self::Multiple(cfgs) => {
+        let endpoints = cfgs.iter().map(ClientCfg::as_endpoint).collect::<io::Result<_>>()?;
+
+        // The calls are retried over the whole list so that an unavailable
+        // endpoint fails over right away. The policy of the first endpoint that
+        // defines one applies.
+        let policy = cfgs.iter().find_map(|cfg| cfg.retry.as_ref());
+
+        Ok(with_retry(FailoverClient::new(endpoints), policy))
+      }
+    }
+  }
+
+  /// It returns true when one of the endpoints uses the legacy API.
+  fn uses_legacy_api(&self) -> bool {
+    match self {
+      Self::Single(cfg) => cfg.api == NodeApi::Legacy,
+      Self::Multiple(cfgs) => cfgs.iter().any(|cfg| cfg.api == NodeApi::Legacy),
+    }
+  }
+}
+
+/// A configuration of the client used to fetch the prices of the pairs.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PriceClientCfg {
+  retry: Option<RetryPolicy>,
+}
+
+impl PriceClientCfg {
+  pub fn as_client(&self) -> DynPriceClient {
+    let client = BitfinexClient::default();
+
+    match &self.retry {
+      Some(policy) => Arc::new(RetryClient::new(client, policy.clone())),
+      None => Arc::new(client),
+    }
+  }
+}
+
+/// A configuration of the cache of the data of finalized blocks.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BlockCacheCfg {
+  /// Maximum number of blocks kept in memory for each kind of data.
+  pub capacity: usize,
+  /// Persist the data in the database to keep it across restarts.
+  pub persistent: bool,
+  /// Maximum number of entries kept in the database.
+  pub disk_capacity: i64,
+}
+
+impl Default for BlockCacheCfg {
+  fn default() -> Self {
+    Self {
+      capacity: 1_000,
+      persistent: false,
+      disk_capacity: 100_000,
+    }
+  }
+}
+
+/// An enumeration of the known Concordium networks. A custom network is any
+/// other chain like a private one.
+#[derive(PartialEq, Eq, Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkProfile {
+  Mainnet,
+  Testnet,
+  Custom,
+}
+
+impl Default for NetworkProfile {
+  fn default() -> Self {
+    Self::Mainnet
+  }
+}
+
+/// A configuration of the network followed by the daemon.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NetworkCfg {
+  #[serde(default)]
+  profile: NetworkProfile,
+
+  /// Hash of the genesis block expected from the node. It overrides the one of
+  /// the profile, and no check is done for a custom network without it.
+  genesis_hash: Option<String>,
+
+  /// Absolute height of the first block to index when the storage is empty.
+  /// The indexing starts from the last finalized block when it is not given.
+  start_height: Option<i64>,
+}
+
+impl NetworkCfg {
+  /// It returns the hash of the genesis block expected for the network, if
+  /// known.
+  pub fn get_genesis_hash(&self) -> Option<&str> {
+    match (&self.genesis_hash, &self.profile) {
+      (Some(hash), _) => Some(hash),
+      (None, NetworkProfile::Mainnet) => Some(MAINNET_GENESIS_HASH),
+      (None, NetworkProfile::Testnet) => Some(TESTNET_GENESIS_HASH),
+      (None, NetworkProfile::Custom) => None,
+    }
+  }
+
+  pub fn get_start_height(&self) -> Option<i64> {
+    self.start_height
+  }
+}
+
+/// A configuration of the job indexing the blocks.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct BlockFetcherCfg {
+  /// Follow the stream of finalized blocks of the node instead of waiting for
+  /// the next schedule of the job. It requires the API v2.
+  #[serde(default)]
+  pub streaming: bool,
+}
+
+/// A configuration of the retention of the snapshots of the accounts.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct AccountSnapshotCfg {
+  /// Number of days during which every snapshot is kept. The older ones are
+  /// downsampled to one per day.
+  pub raw_days: i64,
+  /// Number of days after which the snapshots are deleted.
+  pub retention_days: i64,
+}
+
+impl Default for AccountSnapshotCfg {
+  fn default() -> Self {
+    Self {
+      raw_days: 7,
+      retention_days: 365,
+    }
+  }
+}
+
+/// A configuration of the checks of the readiness of the daemon.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ReadinessCfg {
+  /// Maximum age of the last answer of the node, as seen by the status
+  /// checker.
+  pub node_window_ms: i64,
+  /// Maximum time between the slot of the last block indexed and now.
+  pub max_block_lag_ms: i64,
+}
+
+impl Default for ReadinessCfg {
+  fn default() -> Self {
+    Self {
+      node_window_ms: 2 * 60 * 1000,
+      max_block_lag_ms: 10 * 60 * 1000,
+    }
+  }
+}
+
+/// A condition of an alerting rule. The alert fires as long as the condition is
+/// met, and it is resolved afterwards.
+#[derive(PartialEq, Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertCondition {
+  /// The last status report could not reach the node.
+  NodeUnreachable,
+  /// The node is not an active member of the committee of the bakers.
+  NotInCommittee,
+  PeerCountBelow {
+    min: usize,
+  },
+  /// No block has been finalized for the given number of minutes.
+  NoFinalizedBlock {
+    minutes: i64,
+  },
+  /// The average load of the CPU is above the threshold, like 0.9 for 90%.
+  CpuAbove {
+    threshold: f32,
+  },
+  /// The share of the memory in use is above the threshold.
+  MemoryAbove {
+    threshold: f64,
+  },
+  /// A staking account did not receive any reward for the given number of
+  /// hours, which should cover at least one payday.
+  NoReward {
+    hours: i64,
+  },
+}
+
+/// A rule of the alerting identified by its name.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlertRule {
+  pub name: String,
+
+  #[serde(flatten)]
+  pub condition: AlertCondition,
+}
+
+/// A configuration of the alerting with the rules evaluated after each run of
+/// a job.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AlertCfg {
+  #[serde(default)]
+  pub rules: Vec<AlertRule>,
+}
+
+/// A configuration of the channels through which the alerts and the rewards
+/// are notified.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NotificationCfg {
+  #[serde(default)]
+  webhooks: Vec<WebhookConfig>,
+
+  #[serde(default)]
+  emails: Vec<EmailConfig>,
+}
+
+impl NotificationCfg {
+  pub fn as_notifier(&self) -> io::Result<Notifier> {
+    let channels = self
+      .webhooks
+      .iter()
+      .map(|webhook| webhook.as_channel())
+      .chain(self.emails.iter().map(|email| email.as_channel()))
+      .collect::<io::Result<_>>()?;
+
+    Ok(Notifier::new(channels))
+  }
+}
+
+fn default_digest_period() -> bool {
+  true
+}
+
+/// A configuration of the digests that summarise the rewards of the accounts
+/// per payday and per day.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DigestCfg {
+  /// Currency in which the rewards are valued, like `USD`, or none to only
+  /// report the amounts in CCD.
+  pub quote: Option<String>,
+
+  #[serde(default = "default_digest_period")]
+  pub payday: bool,
+
+  #[serde(default = "default_digest_period")]
+  pub daily: bool,
+}
+
+/// A configuration of the Prometheus endpoint, which is disabled by default.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct MetricsCfg {
+  #[serde(default)]
+  pub enabled: bool,
+
+  /// Token expected as a bearer by the endpoint, which is public otherwise.
+  pub token: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
   listen_address: SocketAddr,
-  client: Option<ClientCfg>,
+  client: Option<NodeClientCfg>,
+  price_client: Option<PriceClientCfg>,
+  block_cache: Option<BlockCacheCfg>,
+  block_fetcher: Option<BlockFetcherCfg>,
+  network: Option<NetworkCfg>,
+  account_snapshots: Option<AccountSnapshotCfg>,
+  alerts: Option<AlertCfg>,
+  notifications: Option<NotificationCfg>,
+  digests: Option<DigestCfg>,
+  metrics: Option<MetricsCfg>,
+  readiness: Option<ReadinessCfg>,
   jobs: Option<HashMap<Job, String>>,
 }
 
@@ -72,7 +414,29 @@ impl Config {
   }
 
   pub fn from_reader(reader: &mut impl io::Read) -> io::Result<Self> {
-    serde_yaml::from_reader::<_, Config>(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    let cfg =
+      serde_yaml::from_reader::<_, Config>(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    cfg.validate()?;
+
+    Ok(cfg)
+  }
+
+  /// It checks the options that depend on each other.
+  fn validate(&self) -> io::Result<()> {
+    let legacy = match &self.client {
+      Some(client) => client.uses_legacy_api(),
+      None => ClientCfg::default().api == NodeApi::Legacy,
+    };
+
+    if self.get_block_fetcher().streaming && legacy {
+      return Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "the streaming of the block fetcher requires the API v2 of the node",
+      ));
+    }
+
+    Ok(())
   }
 
   /// It opens the secret file if specified in the configuration and read the
@@ -93,13 +457,56 @@ impl Config {
     self.jobs.as_ref()
   }
 
-  pub fn make_client(&self) -> DynNodeClient {
+  pub fn get_block_cache(&self) -> Option<&BlockCacheCfg> {
+    self.block_cache.as_ref()
+  }
+
+  pub fn get_block_fetcher(&self) -> BlockFetcherCfg {
+    self.block_fetcher.clone().unwrap_or_default()
+  }
+
+  pub fn get_network(&self) -> NetworkCfg {
+    self.network.clone().unwrap_or_default()
+  }
+
+  pub fn get_account_snapshots(&self) -> AccountSnapshotCfg {
+    self.account_snapshots.clone().unwrap_or_default()
+  }
+
+  pub fn get_alerts(&self) -> AlertCfg {
+    self.alerts.clone().unwrap_or_default()
+  }
+
+  pub fn make_client(&self) -> io::Result<DynNodeClient> {
     match &self.client {
       None => ClientCfg::default().as_client(),
       Some(cfg) => cfg.as_client(),
     }
   }
 
+  pub fn make_notifier(&self) -> io::Result<Notifier> {
+    self.notifications.clone().unwrap_or_default().as_notifier()
+  }
+
+  pub fn get_digests(&self) -> Option<&DigestCfg> {
+    self.digests.as_ref()
+  }
+
+  pub fn get_metrics(&self) -> MetricsCfg {
+    self.metrics.clone().unwrap_or_default()
+  }
+
+  pub fn get_readiness(&self) -> ReadinessCfg {
+    self.readiness.clone().unwrap_or_default()
+  }
+
+  pub fn make_price_client(&self) -> DynPriceClient {
+    match &self.price_client {
+      None => PriceClientCfg::default().as_client(),
+      Some(cfg) => cfg.as_client(),
+    }
+  }
+
   fn read_secret<T>(&self, path: Option<&str>, from: impl FnOnce(&str) -> errors::Result<T>) -> io::Result<T> {
     let key = match path {
       Some(path) => {
@@ -121,7 +528,17 @@ impl Default for Config {
   fn default() -> Self {
     Self {
       listen_address: SocketAddr::from(([127, 0, 0, 1], 0)),
-      client: Some(ClientCfg::default()),
+      client: Some(NodeClientCfg::Single(ClientCfg::default())),
+      price_client: None,
+      block_cache: None,
+      block_fetcher: None,
+      network: None,
+      account_snapshots: None,
+      alerts: None,
+      notifications: None,
+      digests: None,
+      metrics: None,
+      readiness: None,
       jobs: None,
     }
   }
@@ -0,0 +1,137 @@
+use super::AppError;
+use crate::authentication::Claims;
+use crate::event::{DynEventBus, Event};
+use axum::extract::{Extension, Query};
+use axum::response::sse::{self, KeepAlive, Sse};
+use log::error;
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_stream::{Stream, StreamExt};
+
+/// Interval of the comments sent to keep the connection open while no event
+/// is published.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize, Debug, Default)]
+pub struct EventQuery {
+  /// A comma-separated list of topics, or all of them when missing.
+  topics: Option<String>,
+}
+
+/// A controller to stream the events of the daemon as Server-Sent Events. The
+/// name of each message is the topic of the event, and its data is the event
+/// as JSON. A `lagged` message with the number of events missed is sent when
+/// the client reads too slowly.
+pub async fn get_events(
+  params: Query<EventQuery>,
+  Extension(events): Extension<DynEventBus>,
+  _: Claims,
+) -> Result<Sse<impl Stream<Item = Result<sse::Event, Infallible>>>, AppError> {
+  let topics = parse_topics(params.topics.as_deref())?;
+
+  let stream = BroadcastStream::new(events.subscribe()).filter_map(move |res| match res {
+    Ok(event) if topics.contains(&event.topic()) => as_message(&event).map(Ok),
+    Ok(_) => None,
+    Err(BroadcastStreamRecvError::Lagged(count)) => {
+      Some(Ok(sse::Event::default().event("lagged").data(count.to_string())))
+    }
+  });
+
+  Ok(Sse::new(stream).keep_alive(KeepAlive::new().interval(KEEPALIVE_INTERVAL)))
+}
+
+/// It returns the topics of the list, or every topic when there is none.
+fn parse_topics(list: Option<&str>) -> Result<Vec<&'static str>, AppError> {
+  let list = match list {
+    Some(list) => list,
+    None => return Ok(Event::TOPICS.to_vec()),
+  };
+
+  list
+    .split(',')
+    .map(|topic| {
+      Event::TOPICS
+        .into_iter()
+        .find(|t| *t == topic.trim())
+        .ok_or(AppError::InvalidTopic)
+    })
+    .collect()
+}
+
+fn as_message(event: &Event) -> Option<sse::Event> {
+  match sse::Event::default().event(event.topic()).json_data(event) {
+    Ok(message) => Some(message),
+    Err(e) => {
+      error!("unable to serialize the event: {}", e);
+      None
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::event::EventBus;
+  use axum::body::HttpBody;
+  use axum::response::IntoResponse;
+  use std::sync::Arc;
+
+  #[test]
+  fn test_parse_topics() {
+    assert_eq!(Event::TOPICS.to_vec(), parse_topics(None).unwrap());
+    assert_eq!(
+      vec!["blocks", "rewards"],
+      parse_topics(Some("blocks, rewards")).unwrap()
+    );
+    assert!(matches!(
+      parse_topics(Some("blocks,unknown")),
+      Err(AppError::InvalidTopic)
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_get_events() {
+    let bus = Arc::new(EventBus::new());
+
+    let query = EventQuery {
+      topics: Some("blocks".to_string()),
+    };
+
+    let res = get_events(Query(query), Extension(bus.clone()), Claims::default()).await;
+
+    let mut body = res.unwrap().into_response().into_body();
+
+    bus.publish(Event::AccountUpdated {
+      account_id: 1,
+      address: ":address:".to_string(),
+      balance: 42.into(),
+      stake: 0.into(),
+    });
+
+    bus.publish(Event::BlockIndexed {
+      hash: ":hash:".to_string(),
+      height: 2,
+      slot_time_ms: 2000,
+    });
+
+    let chunk = body.data().await.unwrap().unwrap();
+
+    assert_eq!(
+      "event:blocks\ndata:{\"type\":\"block_indexed\",\"hash\":\":hash:\",\"height\":2,\"slot_time_ms\":2000}\n\n",
+      String::from_utf8_lossy(&chunk)
+    );
+  }
+
+  #[tokio::test]
+  async fn test_get_events_invalid_topic() {
+    let query = EventQuery {
+      topics: Some("unknown".to_string()),
+    };
+
+    let res = get_events(Query(query), Extension(Arc::new(EventBus::new())), Claims::default()).await;
+
+    assert!(matches!(res, Err(AppError::InvalidTopic)));
+  }
+}
@@ -0,0 +1,262 @@
+use crate::config::ReadinessCfg;
+use crate::model::Status;
+use crate::repository::{DynBlockRepository, DynStatusRepository, RepositoryError};
+use axum::{extract::Extension, http::StatusCode, Json};
+use chrono::Utc;
+use log::error;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// A result of a check of the readiness, with the reason of a failure.
+#[derive(Serialize, PartialEq, Debug)]
+pub struct Check {
+  ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  message: Option<String>,
+}
+
+impl Check {
+  fn pass() -> Self {
+    Self {
+      ok: true,
+      message: None,
+    }
+  }
+
+  fn fail(message: &str) -> Self {
+    Self {
+      ok: false,
+      message: Some(message.to_string()),
+    }
+  }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Readiness {
+  ready: bool,
+  checks: BTreeMap<&'static str, Check>,
+}
+
+/// A controller to tell that the process is alive. It does not need to be
+/// authenticated.
+pub async fn get_liveness() -> Json<Value> {
+  Json(json!({ "status": "ok" }))
+}
+
+/// A controller to tell if the daemon is ready: the database must be
+/// reachable, the node must have answered recently, and the last block indexed
+/// must not be too far behind. It does not need to be authenticated, and it
+/// answers with a service unavailable status when a check fails.
+pub async fn get_readiness(
+  Extension(cfg): Extension<Arc<ReadinessCfg>>,
+  Extension(status_repository): Extension<DynStatusRepository>,
+  Extension(block_repository): Extension<DynBlockRepository>,
+) -> (StatusCode, Json<Readiness>) {
+  let now_ms = Utc::now().timestamp_millis();
+  let mut checks = BTreeMap::new();
+
+  let (database, node) = match status_repository.get_last_report().await {
+    Ok(report) => (Check::pass(), check_node(Some(&report), &cfg, now_ms)),
+    Err(RepositoryError::NotFound) => (Check::pass(), check_node(None, &cfg, now_ms)),
+    Err(e) => {
+      error!("readiness check of the database failed: {}", e);
+      (Check::fail("database is unreachable"), check_node(None, &cfg, now_ms))
+    }
+  };
+
+  checks.insert("database", database);
+  checks.insert("node", node);
+
+  let block = match block_repository.get_last_block().await {
+    Ok(block) if now_ms - block.get_slot_time_ms() > cfg.max_block_lag_ms => Check::fail(&format!(
+      "last block indexed is {}s behind",
+      (now_ms - block.get_slot_time_ms()) / 1000
+    )),
+    Ok(_) => Check::pass(),
+    Err(RepositoryError::NotFound) => Check::fail("no block has been indexed"),
+    Err(e) => {
+      error!("readiness check of the block fetcher failed: {}", e);
+      Check::fail("last block indexed is unavailable")
+    }
+  };
+
+  checks.insert("block_fetcher", block);
+
+  let ready = checks.values().all(|check| check.ok);
+
+  let status = if ready {
+    StatusCode::OK
+  } else {
+    StatusCode::SERVICE_UNAVAILABLE
+  };
+
+  (
+    status,
+    Json(Readiness {
+      ready,
+      checks,
+    }),
+  )
+}
+
+/// It checks that the last report of the status checker could reach the node,
+/// and that it has been made within the window.
+fn check_node(report: Option<&Status>, cfg: &ReadinessCfg, now_ms: i64) -> Check {
+  let report = match report {
+    Some(report) => report,
+    None => return Check::fail("no status report"),
+  };
+
+  let age_ms = now_ms - report.get_timestamp_ms();
+
+  if report.get_node().is_none() {
+    Check::fail("node could not be reached")
+  } else if age_ms > cfg.node_window_ms {
+    Check::fail(&format!("node has not answered for {}s", age_ms / 1000))
+  } else {
+    Check::pass()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::Block;
+  use crate::repository::{MockBlockRepository, MockStatusRepository, NodeStatusJson, ResourceStatusJson};
+  use diesel::result::Error;
+
+  fn resources() -> ResourceStatusJson {
+    ResourceStatusJson {
+      avg_cpu_load: None,
+      mem_free: None,
+      mem_total: None,
+      uptime_secs: None,
+    }
+  }
+
+  fn node() -> NodeStatusJson {
+    NodeStatusJson {
+      node_id: None,
+      baker_id: None,
+      is_baker_committee: false,
+      is_finalizer_committee: false,
+      uptime_ms: 0,
+      peer_type: "Node".to_string(),
+      peer_average_latency: 0.0,
+      peer_count: 0,
+      endpoint: None,
+      client_retries: 0,
+      cache_hits: 0,
+      cache_misses: 0,
+      baker_committee: None,
+      consensus_baker_running: None,
+      consensus_running: None,
+      consensus_type: None,
+      consensus: None,
+    }
+  }
+
+  #[tokio::test]
+  async fn test_get_liveness() {
+    let Json(body) = get_liveness().await;
+
+    assert_eq!(json!({ "status": "ok" }), body);
+  }
+
+  #[tokio::test]
+  async fn test_get_readiness() {
+    let now_ms = Utc::now().timestamp_millis();
+
+    let mut status_repository = MockStatusRepository::new();
+
+    status_repository
+      .expect_get_last_report()
+      .times(1)
+      .returning(move || Ok(Status::new(1, resources(), Some(node()), now_ms)));
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .returning(move || Ok(Block::new(1, 101, ":hash:", now_ms - 1000, 42)));
+
+    let (status, Json(body)) = get_readiness(
+      Extension(Arc::new(ReadinessCfg::default())),
+      Extension(Arc::new(status_repository)),
+      Extension(Arc::new(block_repository)),
+    )
+    .await;
+
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(
+      json!({
+        "ready": true,
+        "checks": {
+          "block_fetcher": { "ok": true },
+          "database": { "ok": true },
+          "node": { "ok": true },
+        },
+      }),
+      serde_json::to_value(body).unwrap()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_get_readiness_failures() {
+    let now_ms = Utc::now().timestamp_millis();
+
+    let mut status_repository = MockStatusRepository::new();
+
+    status_repository
+      .expect_get_last_report()
+      .times(1)
+      .returning(|| Err(RepositoryError::Faillable(Box::new(Error::AlreadyInTransaction))));
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .returning(move || Ok(Block::new(1, 101, ":hash:", now_ms - 3_600_000, 42)));
+
+    let (status, Json(body)) = get_readiness(
+      Extension(Arc::new(ReadinessCfg::default())),
+      Extension(Arc::new(status_repository)),
+      Extension(Arc::new(block_repository)),
+    )
+    .await;
+
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, status);
+    assert!(!body.ready);
+    assert_eq!(Check::fail("database is unreachable"), body.checks["database"]);
+    assert_eq!(Check::fail("no status report"), body.checks["node"]);
+    assert_eq!(
+      Check::fail("last block indexed is 3600s behind"),
+      body.checks["block_fetcher"]
+    );
+  }
+
+  #[test]
+  fn test_check_node() {
+    let cfg = ReadinessCfg::default();
+    let now_ms = 1_000_000;
+
+    let report = Status::new(1, resources(), Some(node()), now_ms - 1000);
+    assert_eq!(Check::pass(), check_node(Some(&report), &cfg, now_ms));
+
+    let report = Status::new(1, resources(), None, now_ms - 1000);
+    assert_eq!(
+      Check::fail("node could not be reached"),
+      check_node(Some(&report), &cfg, now_ms)
+    );
+
+    let report = Status::new(1, resources(), Some(node()), now_ms - cfg.node_window_ms - 1000);
+    assert_eq!(
+      Check::fail("node has not answered for 121s"),
+      check_node(Some(&report), &cfg, now_ms)
+    );
+  }
+}
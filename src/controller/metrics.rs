@@ -0,0 +1,541 @@
+use super::{map_internal_error, AppError};
+use crate::client::DynPriceClient;
+use crate::config::MetricsCfg;
+use crate::job::DynJobMetrics;
+use crate::metrics::{as_gauge, Encoder, MetricType, CONTENT_TYPE};
+use crate::model::{Account, Status};
+use crate::repository::{
+  AccountFilter, DynAccountRepository, DynBlockRepository, DynStatusRepository, RepositoryError,
+};
+use axum::extract::{Extension, TypedHeader};
+use axum::headers::{authorization::Bearer, Authorization};
+use axum::http::header::{self, HeaderName};
+use rust_decimal::prelude::ToPrimitive;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+type BearerHeader = TypedHeader<Authorization<Bearer>>;
+
+/// A controller to export the metrics of the daemon in the text format of
+/// Prometheus. The endpoint must be enabled in the configuration, and it
+/// expects the token as a bearer when one is configured.
+pub async fn get_metrics(
+  bearer: Option<BearerHeader>,
+  Extension(cfg): Extension<Arc<MetricsCfg>>,
+  Extension(status_repository): Extension<DynStatusRepository>,
+  Extension(account_repository): Extension<DynAccountRepository>,
+  Extension(block_repository): Extension<DynBlockRepository>,
+  Extension(jobs): Extension<DynJobMetrics>,
+  Extension(price_client): Extension<DynPriceClient>,
+) -> Result<([(HeaderName, &'static str); 1], String), AppError> {
+  if !cfg.enabled {
+    return Err(AppError::MetricsDisabled);
+  }
+
+  if let Some(token) = &cfg.token {
+    match bearer {
+      // The comparison takes the same time whatever the prefix in common.
+      Some(TypedHeader(Authorization(bearer))) if bool::from(bearer.token().as_bytes().ct_eq(token.as_bytes())) => {}
+      _ => return Err(AppError::WrongCredentials),
+    }
+  }
+
+  let mut encoder = Encoder::new();
+
+  match status_repository.get_last_report().await {
+    Ok(status) => encode_status(&mut encoder, &status),
+    // No report has been made yet.
+    Err(RepositoryError::NotFound) => {}
+    Err(e) => return Err(map_internal_error(e)),
+  }
+
+  let accounts = account_repository
+    .get_accounts(AccountFilter::default())
+    .await
+    .map_err(map_internal_error)?;
+
+  encode_accounts(&mut encoder, &accounts);
+
+  match block_repository.get_last_block().await {
+    Ok(block) => {
+      encoder.single(
+        "block_height",
+        MetricType::Gauge,
+        "Height of the last block indexed.",
+        block.get_height() as f64,
+      );
+    }
+    // No block has been indexed yet.
+    Err(RepositoryError::NotFound) => {}
+    Err(e) => return Err(map_internal_error(e)),
+  }
+
+  encode_jobs(&mut encoder, &jobs);
+
+  encoder.single(
+    "price_client_retries_total",
+    MetricType::Counter,
+    "Number of calls to the price provider retried.",
+    price_client.get_metrics().retries as f64,
+  );
+
+  Ok(([(header::CONTENT_TYPE, CONTENT_TYPE)], encoder.finish()))
+}
+
+/// It encodes the gauges of the last report of the server and of the node.
+/// The node gauges are missing when the node could not be reached.
+fn encode_status(encoder: &mut Encoder, status: &Status) {
+  let resources = status.get_resources();
+  let node = status.get_node();
+
+  encoder.single(
+    "report_timestamp_seconds",
+    MetricType::Gauge,
+    "Time of the last status report.",
+    status.get_timestamp_ms() as f64 / 1000.0,
+  );
+
+  if let Some(load) = resources.avg_cpu_load {
+    encoder.single(
+      "resource_cpu_load",
+      MetricType::Gauge,
+      "Average load of the CPU.",
+      load as f64,
+    );
+  }
+
+  if let Some(free) = resources.mem_free {
+    encoder.single(
+      "resource_memory_free_bytes",
+      MetricType::Gauge,
+      "Free memory.",
+      free as f64,
+    );
+  }
+
+  if let Some(total) = resources.mem_total {
+    encoder.single(
+      "resource_memory_total_bytes",
+      MetricType::Gauge,
+      "Total memory.",
+      total as f64,
+    );
+  }
+
+  if let Some(uptime) = resources.uptime_secs {
+    encoder.single(
+      "resource_uptime_seconds",
+      MetricType::Gauge,
+      "Uptime of the server.",
+      uptime as f64,
+    );
+  }
+
+  encoder.single(
+    "node_up",
+    MetricType::Gauge,
+    "Whether the node could be reached for the last report.",
+    as_gauge(node.is_some()),
+  );
+
+  let node = match node {
+    Some(node) => node,
+    None => return,
+  };
+
+  encoder
+    .single(
+      "node_peer_count",
+      MetricType::Gauge,
+      "Number of peers of the node.",
+      node.peer_count as f64,
+    )
+    .single(
+      "node_peer_average_latency_milliseconds",
+      MetricType::Gauge,
+      "Average latency of the peers of the node.",
+      node.peer_average_latency,
+    )
+    .single(
+      "node_uptime_seconds",
+      MetricType::Gauge,
+      "Uptime of the node.",
+      node.uptime_ms as f64 / 1000.0,
+    )
+    .single(
+      "node_baker_committee",
+      MetricType::Gauge,
+      "Whether the node is an active member of the baker committee.",
+      as_gauge(node.is_baker_committee),
+    )
+    .single(
+      "node_finalizer_committee",
+      MetricType::Gauge,
+      "Whether the node is a member of the finalizer committee.",
+      as_gauge(node.is_finalizer_committee),
+    )
+    .single(
+      "node_client_retries_total",
+      MetricType::Counter,
+      "Number of calls to the node retried.",
+      node.client_retries as f64,
+    )
+    .single(
+      "node_cache_hits_total",
+      MetricType::Counter,
+      "Number of calls to the node answered by the block cache.",
+      node.cache_hits as f64,
+    )
+    .single(
+      "node_cache_misses_total",
+      MetricType::Counter,
+      "Number of calls to the node that missed the block cache.",
+      node.cache_misses as f64,
+    );
+
+  if let Some(state) = &node.baker_committee {
+    encoder
+      .family(
+        "node_baker_committee_state",
+        MetricType::Gauge,
+        "State of the node in the baker committee.",
+      )
+      .sample("node_baker_committee_state", &[("state", state)], 1.0);
+  }
+
+  if let Some(consensus) = &node.consensus {
+    encoder
+      .single(
+        "node_best_block_height",
+        MetricType::Gauge,
+        "Height of the best block known by the node.",
+        consensus.best_block_height as f64,
+      )
+      .single(
+        "node_last_finalized_block_height",
+        MetricType::Gauge,
+        "Height of the last block finalized known by the node.",
+        consensus.last_finalized_block_height as f64,
+      )
+      .single(
+        "node_lagging",
+        MetricType::Gauge,
+        "Whether the node is lagging behind the chain.",
+        as_gauge(consensus.is_lagging),
+      );
+
+    if let Some(lag) = consensus.sync_lag_ms {
+      encoder.single(
+        "node_sync_lag_seconds",
+        MetricType::Gauge,
+        "Time elapsed since the last block finalized.",
+        lag as f64 / 1000.0,
+      );
+    }
+  }
+}
+
+/// It encodes the balance, the stake and the lottery power of the accounts
+/// labelled by address.
+fn encode_accounts(encoder: &mut Encoder, accounts: &[Account]) {
+  encoder.family("account_balance_ccd", MetricType::Gauge, "Balance of the account.");
+
+  for account in accounts {
+    let labels = [("address", account.get_address())];
+
+    encoder.sample(
+      "account_balance_ccd",
+      &labels,
+      account.get_balance().to_f64().unwrap_or_default(),
+    );
+  }
+
+  encoder.family("account_stake_ccd", MetricType::Gauge, "Stake of the account.");
+
+  for account in accounts {
+    let labels = [("address", account.get_address())];
+
+    encoder.sample(
+      "account_stake_ccd",
+      &labels,
+      account.get_stake().to_f64().unwrap_or_default(),
+    );
+  }
+
+  encoder.family(
+    "account_lottery_power",
+    MetricType::Gauge,
+    "Share of the account in the lottery to bake a block.",
+  );
+
+  for account in accounts {
+    let labels = [("address", account.get_address())];
+
+    encoder.sample("account_lottery_power", &labels, account.get_lottery_power());
+  }
+}
+
+/// It encodes the executions of the jobs since the daemon started.
+fn encode_jobs(encoder: &mut Encoder, jobs: &DynJobMetrics) {
+  let stats = jobs.get_stats();
+
+  encoder.family(
+    "job_executions_total",
+    MetricType::Counter,
+    "Number of executions of the job by result.",
+  );
+
+  for (name, s) in &stats {
+    encoder
+      .sample(
+        "job_executions_total",
+        &[("job", name), ("result", "success")],
+        s.successes as f64,
+      )
+      .sample(
+        "job_executions_total",
+        &[("job", name), ("result", "failure")],
+        s.failures as f64,
+      );
+  }
+
+  encoder.family(
+    "job_duration_seconds",
+    MetricType::Summary,
+    "Duration of the executions of the job.",
+  );
+
+  for (name, s) in &stats {
+    encoder
+      .sample("job_duration_seconds_sum", &[("job", name)], s.duration_secs)
+      .sample(
+        "job_duration_seconds_count",
+        &[("job", name)],
+        (s.successes + s.failures) as f64,
+      );
+  }
+
+  encoder.family(
+    "job_last_duration_seconds",
+    MetricType::Gauge,
+    "Duration of the last execution of the job.",
+  );
+
+  for (name, s) in &stats {
+    encoder.sample("job_last_duration_seconds", &[("job", name)], s.last_duration_secs);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::{bitfinex::MockPriceClient, ClientMetrics};
+  use crate::job::JobMetrics;
+  use crate::model::Block;
+  use crate::repository::{
+    ConsensusStatusJson, MockAccountRepository, MockBlockRepository, MockStatusRepository, NodeStatusJson,
+    ResourceStatusJson,
+  };
+  use axum::headers::Authorization;
+  use rust_decimal_macros::dec;
+  use std::time::Duration;
+
+  fn node_status() -> NodeStatusJson {
+    NodeStatusJson {
+      node_id: Some(":node:".to_string()),
+      baker_id: Some(42),
+      is_baker_committee: true,
+      is_finalizer_committee: false,
+      uptime_ms: 5000,
+      peer_type: "Node".to_string(),
+      peer_average_latency: 125.5,
+      peer_count: 8,
+      endpoint: None,
+      client_retries: 3,
+      cache_hits: 5,
+      cache_misses: 1,
+      baker_committee: Some("active".to_string()),
+      consensus_baker_running: Some(true),
+      consensus_running: Some(true),
+      consensus_type: Some("Active".to_string()),
+      consensus: Some(ConsensusStatusJson {
+        best_block_height: 125,
+        last_finalized_block_height: 123,
+        last_finalized_ms: Some(1000),
+        epoch: Some(1),
+        slot_duration_ms: 250,
+        protocol_version: 4,
+        finalization_lag: 2,
+        sync_lag_ms: Some(1500),
+        is_lagging: false,
+      }),
+    }
+  }
+
+  fn config(token: Option<&str>) -> Extension<Arc<MetricsCfg>> {
+    Extension(Arc::new(MetricsCfg {
+      enabled: true,
+      token: token.map(String::from),
+    }))
+  }
+
+  fn price_client(retries: u64) -> DynPriceClient {
+    let mut client = MockPriceClient::new();
+
+    client.expect_get_metrics().returning(move || ClientMetrics {
+      retries,
+      ..ClientMetrics::default()
+    });
+
+    Arc::new(client)
+  }
+
+  fn bearer(token: &str) -> Option<BearerHeader> {
+    Some(TypedHeader(Authorization::bearer(token).unwrap()))
+  }
+
+  #[tokio::test]
+  async fn test_get_metrics() {
+    let mut status_repository = MockStatusRepository::new();
+
+    status_repository.expect_get_last_report().times(1).returning(|| {
+      Ok(Status::new(
+        1,
+        ResourceStatusJson {
+          avg_cpu_load: Some(0.5),
+          mem_free: Some(256),
+          mem_total: Some(512),
+          uptime_secs: Some(16),
+        },
+        Some(node_status()),
+        2000,
+      ))
+    });
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository
+      .expect_get_accounts()
+      .times(1)
+      .returning(|_| Ok(vec![Account::new(1, ":address:", dec!(10.5), dec!(2), 0.25)]));
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .returning(|| Ok(Block::new(1, 101, ":hash:", 0, 42)));
+
+    let jobs = Arc::new(JobMetrics::default());
+    jobs.record("block-fetcher", Duration::from_millis(1500), true);
+
+    let res = get_metrics(
+      bearer(":token:"),
+      config(Some(":token:")),
+      Extension(Arc::new(status_repository)),
+      Extension(Arc::new(account_repository)),
+      Extension(Arc::new(block_repository)),
+      Extension(jobs),
+      Extension(price_client(2)),
+    )
+    .await;
+
+    let ([(_, content_type)], body) = res.unwrap();
+
+    assert_eq!(CONTENT_TYPE, content_type);
+
+    for line in [
+      "bakerd_report_timestamp_seconds 2",
+      "bakerd_resource_cpu_load 0.5",
+      "bakerd_resource_memory_total_bytes 512",
+      "bakerd_node_up 1",
+      "bakerd_node_peer_count 8",
+      "bakerd_node_peer_average_latency_milliseconds 125.5",
+      "bakerd_node_uptime_seconds 5",
+      "bakerd_node_baker_committee 1",
+      "bakerd_node_finalizer_committee 0",
+      "bakerd_node_baker_committee_state{state=\"active\"} 1",
+      "bakerd_node_best_block_height 125",
+      "bakerd_node_sync_lag_seconds 1.5",
+      "bakerd_account_balance_ccd{address=\":address:\"} 10.5",
+      "bakerd_account_stake_ccd{address=\":address:\"} 2",
+      "bakerd_account_lottery_power{address=\":address:\"} 0.25",
+      "bakerd_block_height 101",
+      "bakerd_job_executions_total{job=\"block-fetcher\",result=\"success\"} 1",
+      "bakerd_job_executions_total{job=\"block-fetcher\",result=\"failure\"} 0",
+      "bakerd_job_duration_seconds_sum{job=\"block-fetcher\"} 1.5",
+      "bakerd_job_duration_seconds_count{job=\"block-fetcher\"} 1",
+      "bakerd_job_last_duration_seconds{job=\"block-fetcher\"} 1.5",
+      "bakerd_price_client_retries_total 2",
+    ] {
+      assert!(body.lines().any(|l| l == line), "missing `{}`", line);
+    }
+  }
+
+  #[tokio::test]
+  async fn test_get_metrics_empty() {
+    let mut status_repository = MockStatusRepository::new();
+
+    status_repository
+      .expect_get_last_report()
+      .times(1)
+      .returning(|| Err(RepositoryError::NotFound));
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository
+      .expect_get_accounts()
+      .times(1)
+      .returning(|_| Ok(vec![]));
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .returning(|| Err(RepositoryError::NotFound));
+
+    let res = get_metrics(
+      None,
+      config(None),
+      Extension(Arc::new(status_repository)),
+      Extension(Arc::new(account_repository)),
+      Extension(Arc::new(block_repository)),
+      Extension(Arc::new(JobMetrics::default())),
+      Extension(price_client(0)),
+    )
+    .await;
+
+    let (_, body) = res.unwrap();
+
+    assert!(!body.contains("bakerd_node_up"));
+    assert!(!body.contains("bakerd_block_height"));
+  }
+
+  #[tokio::test]
+  async fn test_get_metrics_access() {
+    let call = |bearer, cfg| {
+      get_metrics(
+        bearer,
+        cfg,
+        Extension(Arc::new(MockStatusRepository::new())),
+        Extension(Arc::new(MockAccountRepository::new())),
+        Extension(Arc::new(MockBlockRepository::new())),
+        Extension(Arc::new(JobMetrics::default())),
+        Extension(Arc::new(MockPriceClient::new())),
+      )
+    };
+
+    let res = call(bearer(":token:"), Extension(Arc::new(MetricsCfg::default()))).await;
+    assert!(matches!(res, Err(AppError::MetricsDisabled)));
+
+    let res = call(None, config(Some(":token:"))).await;
+    assert!(matches!(res, Err(AppError::WrongCredentials)));
+
+    let res = call(bearer(":other:"), config(Some(":token:"))).await;
+    assert!(matches!(res, Err(AppError::WrongCredentials)));
+
+    let res = call(bearer(":token"), config(Some(":token:"))).await;
+    assert!(matches!(res, Err(AppError::WrongCredentials)));
+  }
+}
@@ -1,7 +1,14 @@
 pub mod auth;
+pub mod event;
+pub mod health;
+pub mod metrics;
 
+use crate::analysis::{self, Performance, DAY_MS};
 use crate::authentication::{Claims, Role};
-use crate::model::{Account, Block, Pair, Price, Reward, Status};
+use crate::model::{
+  Account, Alert, AlertState, Block, Pair, Peer, Pool, Price, Reward, Silence, Snapshot, StakingKind, Status,
+  Transaction,
+};
 use crate::repository::*;
 use axum::{
   extract::{Extension, Path, Query},
@@ -9,18 +16,39 @@ use axum::{
   response::{IntoResponse, Response},
   Json,
 };
+use chrono::Utc;
 use log::error;
 use serde::Deserialize;
 use serde_json::json;
 
+/// Maximum number of days that the performance of a baker can be analyzed on.
+const MAX_PERFORMANCE_DAYS: i64 = 30;
+
+/// Default and maximum number of transactions returned in a page.
+const DEFAULT_TRANSACTIONS_PER_PAGE: i64 = 20;
+const MAX_TRANSACTIONS_PER_PAGE: i64 = 100;
+
+/// Default time window of the history of an account.
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+
+/// Maximum duration of a silence of the alerts.
+const MAX_SILENCE_MS: i64 = 30 * DAY_MS;
+
 type Result<T> = std::result::Result<T, AppError>;
 
 /// An global definition of errors for the application.
 #[derive(Debug)]
 pub enum AppError {
   AccountNotFound,
+  AccountNotBaker,
   PairNotFound,
   PriceNotFound,
+  PoolNotFound,
+  AlertNotFound,
+  SilenceNotFound,
+  InvalidSilence,
+  InvalidTopic,
+  MetricsDisabled,
   WrongCredentials,
   Forbidden,
   Internal,
@@ -32,8 +60,15 @@ impl IntoResponse for AppError {
   fn into_response(self) -> Response {
     let (status, message) = match self {
       Self::AccountNotFound => (StatusCode::NOT_FOUND, "account does not exist"),
+      Self::AccountNotBaker => (StatusCode::BAD_REQUEST, "account is not a baker"),
       Self::PairNotFound => (StatusCode::NOT_FOUND, "pair does not exist"),
       Self::PriceNotFound => (StatusCode::NOT_FOUND, "price does not exist"),
+      Self::PoolNotFound => (StatusCode::NOT_FOUND, "pool does not exist"),
+      Self::AlertNotFound => (StatusCode::NOT_FOUND, "alert does not exist"),
+      Self::SilenceNotFound => (StatusCode::NOT_FOUND, "silence does not exist"),
+      Self::InvalidSilence => (StatusCode::BAD_REQUEST, "silence must last between 1ms and 30 days"),
+      Self::InvalidTopic => (StatusCode::BAD_REQUEST, "topic does not exist"),
+      Self::MetricsDisabled => (StatusCode::NOT_FOUND, "metrics are disabled"),
       Self::WrongCredentials => (StatusCode::UNAUTHORIZED, "wrong credentials"),
       Self::Forbidden => (StatusCode::FORBIDDEN, "forbidden"),
       Self::Internal => (StatusCode::INTERNAL_SERVER_ERROR, "internal server error"),
@@ -55,6 +90,14 @@ pub async fn get_status(Extension(repository): Extension<DynStatusRepository>, _
   Ok(status.into())
 }
 
+/// A controller to return the peers of the node, including the ones that
+/// recently disconnected.
+pub async fn get_peers(Extension(repository): Extension<DynStatusRepository>, _: Claims) -> Result<Json<Vec<Peer>>> {
+  let peers = repository.get_peers().await.map_err(map_internal_error)?;
+
+  Ok(peers.into())
+}
+
 #[derive(Deserialize, Debug)]
 pub struct CreateAccount {
   address: String,
@@ -121,6 +164,177 @@ pub async fn get_account_rewards(
   Ok(rewards.into())
 }
 
+#[derive(Deserialize, Default, Debug)]
+pub struct PerformanceQuery {
+  days: Option<i64>,
+}
+
+/// A controller to return the analysis of the block production of an account
+/// that is a baker. The production is analyzed per day, starting from the
+/// beginning of the current one by default, with the lottery power recorded in
+/// the history of the pool.
+pub async fn get_account_performance(
+  Path(account_id): Path<i32>,
+  params: Query<PerformanceQuery>,
+  Extension(account_repository): Extension<DynAccountRepository>,
+  Extension(block_repository): Extension<DynBlockRepository>,
+  Extension(pool_repository): Extension<DynPoolRepository>,
+  _: Claims,
+) -> Result<Json<Performance>> {
+  let account = account_repository
+    .get_account(account_id)
+    .await
+    .map_err(map_account_error)?;
+
+  let baker_id = match (account.get_staking(), account.get_pool_id()) {
+    (StakingKind::Baker, Some(baker_id)) => baker_id,
+    _ => return Err(AppError::AccountNotBaker),
+  };
+
+  let days = params.days.unwrap_or(1).clamp(1, MAX_PERFORMANCE_DAYS);
+
+  let now_ms = Utc::now().timestamp_millis();
+  let from_ms = (now_ms / DAY_MS - days + 1) * DAY_MS;
+
+  let filter = BlockFilter {
+    baker: None,
+    since_ms: Some(from_ms),
+  };
+
+  let blocks = block_repository.get_all(filter).await.map_err(map_internal_error)?;
+
+  // The snapshots of the day before give the lottery power at the beginning
+  // of the window.
+  let filter = PoolFilter {
+    baker_id: baker_id as i64,
+    since_ms: Some(from_ms - DAY_MS),
+  };
+
+  let history = pool_repository.get_history(filter).await.map_err(map_internal_error)?;
+
+  let performance = analysis::analyze(
+    baker_id,
+    account.get_lottery_power(),
+    &history,
+    &blocks,
+    from_ms,
+    now_ms,
+    DAY_MS,
+  );
+
+  Ok(performance.into())
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct TransactionQuery {
+  page: Option<i64>,
+  per_page: Option<i64>,
+}
+
+/// A controller to return a page of the transactions of an account, starting
+/// from the most recent one. The pages are numbered from 1.
+pub async fn get_account_transactions(
+  Path(account_id): Path<i32>,
+  params: Query<TransactionQuery>,
+  Extension(repository): Extension<DynAccountRepository>,
+  _: Claims,
+) -> Result<Json<Vec<Transaction>>> {
+  let account = repository.get_account(account_id).await.map_err(map_account_error)?;
+
+  let page = params.page.unwrap_or(1).max(1);
+  let per_page = params
+    .per_page
+    .unwrap_or(DEFAULT_TRANSACTIONS_PER_PAGE)
+    .clamp(1, MAX_TRANSACTIONS_PER_PAGE);
+
+  let filter = TransactionFilter {
+    account_id: account.get_id(),
+    offset: (page - 1) * per_page,
+    limit: per_page,
+  };
+
+  let transactions = repository.get_transactions(filter).await.map_err(map_internal_error)?;
+
+  Ok(transactions.into())
+}
+
+/// An enumeration of the resolutions of the history of an account.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+  Raw,
+  Hour,
+  Day,
+  Week,
+}
+
+impl Resolution {
+  /// It returns the duration of an interval in milliseconds, or none when
+  /// every snapshot is returned.
+  fn as_ms(&self) -> Option<i64> {
+    match self {
+      Self::Raw => None,
+      Self::Hour => Some(DAY_MS / 24),
+      Self::Day => Some(DAY_MS),
+      Self::Week => Some(7 * DAY_MS),
+    }
+  }
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct HistoryQuery {
+  from: Option<i64>,
+  to: Option<i64>,
+  resolution: Option<Resolution>,
+}
+
+/// A controller to return the snapshots of an account over a time window,
+/// which is the last 30 days by default. With a resolution, only the most
+/// recent snapshot of each interval is returned.
+pub async fn get_account_history(
+  Path(account_id): Path<i32>,
+  params: Query<HistoryQuery>,
+  Extension(repository): Extension<DynAccountRepository>,
+  _: Claims,
+) -> Result<Json<Vec<Snapshot>>> {
+  let account = repository.get_account(account_id).await.map_err(map_account_error)?;
+
+  let to_ms = params.to.unwrap_or_else(|| Utc::now().timestamp_millis());
+  let from_ms = params.from.unwrap_or(to_ms - DEFAULT_HISTORY_DAYS * DAY_MS);
+
+  let filter = SnapshotFilter {
+    account_id: account.get_id(),
+    from_ms,
+    to_ms,
+  };
+
+  let snapshots = repository.get_snapshots(filter).await.map_err(map_internal_error)?;
+
+  let snapshots = match params.resolution.and_then(|r| r.as_ms()) {
+    Some(interval_ms) => downsample(snapshots, interval_ms),
+    None => snapshots,
+  };
+
+  Ok(snapshots.into())
+}
+
+/// It keeps the most recent snapshot of each interval. The snapshots are
+/// expected to be sorted from the oldest one.
+fn downsample(snapshots: Vec<Snapshot>, interval_ms: i64) -> Vec<Snapshot> {
+  let mut res: Vec<Snapshot> = Vec::new();
+
+  for snapshot in snapshots {
+    let interval = snapshot.get_epoch_ms().div_euclid(interval_ms);
+
+    match res.last_mut() {
+      Some(last) if last.get_epoch_ms().div_euclid(interval_ms) == interval => *last = snapshot,
+      _ => res.push(snapshot),
+    }
+  }
+
+  res
+}
+
 #[derive(Deserialize, Debug)]
 pub struct CreatePair {
   base: String,
@@ -212,6 +426,173 @@ pub async fn get_blocks(
   Ok(blocks.into())
 }
 
+/// A controller to return the most recent status of the pool of a baker.
+pub async fn get_pool(
+  Path(baker_id): Path<i64>,
+  Extension(repository): Extension<DynPoolRepository>,
+  _: Claims,
+) -> Result<Json<Pool>> {
+  let pool = repository.get_last(baker_id).await.map_err(|e| match e {
+    RepositoryError::NotFound => AppError::PoolNotFound,
+    _ => {
+      error!("unable to find a pool: {}", e);
+
+      AppError::Internal
+    }
+  })?;
+
+  Ok(pool.into())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PoolQuery {
+  since_ms: Option<i64>,
+}
+
+/// A controller to return the history of the status of the pool of a baker,
+/// starting from the most recent one. The list can be filtered by time.
+pub async fn get_pool_history(
+  Path(baker_id): Path<i64>,
+  params: Query<PoolQuery>,
+  repository: Extension<DynPoolRepository>,
+  _: Claims,
+) -> Result<Json<Vec<Pool>>> {
+  let filter = PoolFilter {
+    baker_id,
+    since_ms: params.since_ms,
+  };
+
+  let pools = repository.get_history(filter).await.map_err(map_internal_error)?;
+
+  Ok(pools.into())
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AlertQuery {
+  state: Option<AlertState>,
+  since_ms: Option<i64>,
+  until_ms: Option<i64>,
+}
+
+/// A controller to return the alerts, starting from the most recent one. The
+/// list can be filtered by state and by the time the alerts fired.
+pub async fn get_alerts(
+  params: Query<AlertQuery>,
+  Extension(repository): Extension<DynAlertRepository>,
+  _: Claims,
+) -> Result<Json<Vec<Alert>>> {
+  let filter = AlertFilter {
+    state: params.state,
+    since_ms: params.since_ms,
+    until_ms: params.until_ms,
+  };
+
+  let alerts = repository.get_alerts(filter).await.map_err(map_internal_error)?;
+
+  Ok(alerts.into())
+}
+
+/// A controller to acknowledge an alert on behalf of the user of the token.
+pub async fn ack_alert(
+  Path(alert_id): Path<i32>,
+  Extension(repository): Extension<DynAlertRepository>,
+  claims: Claims,
+) -> Result<Json<Alert>> {
+  if !claims.has_role(Role::Admin) {
+    return Err(AppError::Forbidden);
+  }
+
+  let now_ms = Utc::now().timestamp_millis();
+
+  let alert = repository
+    .ack_alert(alert_id, claims.get_user_id(), now_ms)
+    .await
+    .map_err(|e| match e {
+      RepositoryError::NotFound => AppError::AlertNotFound,
+      _ => map_internal_error(e),
+    })?;
+
+  Ok(alert.into())
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateSilence {
+  rule: Option<String>,
+  subject: Option<String>,
+  #[serde(default)]
+  reason: String,
+  starts_ms: Option<i64>,
+  duration_ms: i64,
+}
+
+/// A controller to silence the notifications of the alerts matching the rule
+/// and the subject for a limited time, starting now by default.
+pub async fn create_silence(
+  request: Json<CreateSilence>,
+  Extension(repository): Extension<DynAlertRepository>,
+  claims: Claims,
+) -> Result<Json<Silence>> {
+  if !claims.has_role(Role::Admin) {
+    return Err(AppError::Forbidden);
+  }
+
+  if request.duration_ms <= 0 || request.duration_ms > MAX_SILENCE_MS {
+    return Err(AppError::InvalidSilence);
+  }
+
+  let starts_ms = request.starts_ms.unwrap_or_else(|| Utc::now().timestamp_millis());
+
+  let ends_ms = starts_ms
+    .checked_add(request.duration_ms)
+    .ok_or(AppError::InvalidSilence)?;
+
+  let new_silence = NewSilence {
+    rule: request.rule.clone(),
+    subject: request.subject.clone(),
+    reason: request.reason.clone(),
+    created_by: claims.get_user_id(),
+    starts_ms,
+    ends_ms,
+  };
+
+  let silence = repository
+    .create_silence(new_silence)
+    .await
+    .map_err(map_internal_error)?;
+
+  Ok(silence.into())
+}
+
+/// A controller to return the silences that are active or starting later.
+pub async fn get_silences(
+  Extension(repository): Extension<DynAlertRepository>,
+  _: Claims,
+) -> Result<Json<Vec<Silence>>> {
+  let now_ms = Utc::now().timestamp_millis();
+
+  let silences = repository.get_silences(now_ms).await.map_err(map_internal_error)?;
+
+  Ok(silences.into())
+}
+
+/// A controller to delete a silence before it ends.
+pub async fn delete_silence(
+  Path(silence_id): Path<i32>,
+  Extension(repository): Extension<DynAlertRepository>,
+  claims: Claims,
+) -> Result<StatusCode> {
+  if !claims.has_role(Role::Admin) {
+    return Err(AppError::Forbidden);
+  }
+
+  repository.delete_silence(silence_id).await.map_err(|e| match e {
+    RepositoryError::NotFound => AppError::SilenceNotFound,
+    _ => map_internal_error(e),
+  })?;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
 fn map_internal_error(e: RepositoryError) -> AppError {
   error!("internal server error: {}", e);
 
@@ -244,7 +625,9 @@ fn map_pair_error(e: RepositoryError) -> AppError {
 mod tests {
   use super::*;
   use crate::model::{Pair, RewardKind, Status as StatusView};
-  use crate::repository::{MockAccountRepository, MockPriceRepository, MockStatusRepository};
+  use crate::repository::{
+    MockAccountRepository, MockAlertRepository, MockPoolRepository, MockPriceRepository, MockStatusRepository,
+  };
   use axum::http::StatusCode;
   use diesel::result::Error;
   use mockall::predicate::*;
@@ -255,8 +638,15 @@ mod tests {
   fn test_app_errors() {
     let tests = vec![
       (StatusCode::NOT_FOUND, AppError::AccountNotFound),
+      (StatusCode::BAD_REQUEST, AppError::AccountNotBaker),
       (StatusCode::NOT_FOUND, AppError::PriceNotFound),
       (StatusCode::NOT_FOUND, AppError::PairNotFound),
+      (StatusCode::NOT_FOUND, AppError::PoolNotFound),
+      (StatusCode::NOT_FOUND, AppError::AlertNotFound),
+      (StatusCode::NOT_FOUND, AppError::SilenceNotFound),
+      (StatusCode::BAD_REQUEST, AppError::InvalidSilence),
+      (StatusCode::BAD_REQUEST, AppError::InvalidTopic),
+      (StatusCode::NOT_FOUND, AppError::MetricsDisabled),
       (StatusCode::UNAUTHORIZED, AppError::WrongCredentials),
       (StatusCode::FORBIDDEN, AppError::Forbidden),
       (StatusCode::INTERNAL_SERVER_ERROR, AppError::Internal),
@@ -305,6 +695,35 @@ mod tests {
     assert!(matches!(res, Err(AppError::Internal)));
   }
 
+  #[tokio::test]
+  async fn test_get_peers() {
+    let mut repository = MockStatusRepository::new();
+
+    repository.expect_get_peers().times(1).returning(|| {
+      Ok(vec![
+        Peer::new(1, "peer-1", 10, 20, 125).with_connection(1000, 2000, None)
+      ])
+    });
+
+    let res = get_peers(Extension(Arc::new(repository)), Claims::default()).await;
+
+    assert!(matches!(res, Ok(Json(peers)) if peers.len() == 1));
+  }
+
+  #[tokio::test]
+  async fn test_get_peers_internal_error() {
+    let mut repository = MockStatusRepository::new();
+
+    repository
+      .expect_get_peers()
+      .times(1)
+      .returning(|| Err(RepositoryError::NotFound));
+
+    let res = get_peers(Extension(Arc::new(repository)), Claims::default()).await;
+
+    assert!(matches!(res, Err(AppError::Internal)));
+  }
+
   #[tokio::test]
   async fn test_get_accounts() {
     let mut repository = MockAccountRepository::new();
@@ -475,6 +894,230 @@ mod tests {
     assert!(matches!(res.await, Ok(rewards) if rewards.len() == 1));
   }
 
+  #[tokio::test]
+  async fn test_get_account_performance() {
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository
+      .expect_get_account()
+      .with(eq(1))
+      .times(1)
+      .returning(|_| {
+        Ok(Account::new(1, ":address:", dec!(125), dec!(50), 0.5).with_staking(StakingKind::Baker, Some(42)))
+      });
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_all()
+      .withf(|filter| filter.baker.is_none() && matches!(filter.since_ms, Some(since) if since % DAY_MS == 0))
+      .times(1)
+      .returning(|filter| {
+        let since_ms = filter.since_ms.unwrap();
+
+        Ok(vec![
+          Block::new(1, 100, ":hash-block-100:", since_ms, 42),
+          Block::new(2, 101, ":hash-block-101:", since_ms + 1, 7),
+        ])
+      });
+
+    let mut pool_repository = MockPoolRepository::new();
+
+    // The snapshot before the window gives the lottery power of the payday.
+    pool_repository
+      .expect_get_history()
+      .withf(|filter| filter.baker_id == 42 && matches!(filter.since_ms, Some(since) if since % DAY_MS == 0))
+      .times(1)
+      .returning(|filter| {
+        let payday = crate::model::PoolPayday {
+          blocks_baked: 10,
+          finalization_live: true,
+          transaction_fees_earned: dec!(0),
+          effective_stake: dec!(1250),
+          lottery_power: 0.25,
+        };
+
+        Ok(vec![Pool::new(
+          1,
+          42,
+          filter.since_ms.unwrap() + DAY_MS - 1,
+          "open_for_all",
+          crate::model::CommissionRates {
+            baking: 0.1,
+            finalization: 1.0,
+            transaction: 0.1,
+          },
+          crate::model::PoolCapital {
+            equity: dec!(1000),
+            delegated: dec!(250),
+            delegated_cap: dec!(3000),
+          },
+          Some(payday),
+        )])
+      });
+
+    let res = get_account_performance(
+      Path(1),
+      Query(PerformanceQuery::default()),
+      Extension(Arc::new(account_repository)),
+      Extension(Arc::new(block_repository)),
+      Extension(Arc::new(pool_repository)),
+      Claims::default(),
+    )
+    .await;
+
+    let value = serde_json::to_value(res.unwrap().0).unwrap();
+
+    assert_eq!(42, value["baker_id"]);
+    assert_eq!(0.5, value["lottery_power"]);
+    assert_eq!(0.5, value["expected_blocks"]);
+    assert_eq!(1, value["actual_blocks"]);
+    assert_eq!(2.0, value["luck"]);
+    assert_eq!(1, value["periods"].as_array().unwrap().len());
+  }
+
+  #[tokio::test]
+  async fn test_get_account_performance_not_baker() {
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository
+      .expect_get_account()
+      .with(eq(1))
+      .times(1)
+      .returning(|_| Ok(Account::new(1, ":address:", dec!(125), dec!(50), 0.0)));
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository.expect_get_all().times(0);
+
+    let mut pool_repository = MockPoolRepository::new();
+
+    pool_repository.expect_get_history().times(0);
+
+    let res = get_account_performance(
+      Path(1),
+      Query(PerformanceQuery::default()),
+      Extension(Arc::new(account_repository)),
+      Extension(Arc::new(block_repository)),
+      Extension(Arc::new(pool_repository)),
+      Claims::default(),
+    )
+    .await;
+
+    assert!(matches!(res, Err(AppError::AccountNotBaker)));
+  }
+
+  #[tokio::test]
+  async fn test_get_account_transactions() {
+    let mut repository = MockAccountRepository::new();
+
+    repository
+      .expect_get_account()
+      .with(eq(1))
+      .times(1)
+      .returning(|_| Ok(Account::new(1, ":address:", dec!(125), dec!(50), 0.0)));
+
+    repository
+      .expect_get_transactions()
+      .with(eq(TransactionFilter {
+        account_id: 1,
+        offset: 200,
+        limit: 100,
+      }))
+      .times(1)
+      .returning(|_| Ok(vec![Transaction::new(1, 1, ":block:", ":tx:", 0, "transfer")]));
+
+    let res = get_account_transactions(
+      Path(1),
+      Query(TransactionQuery {
+        page: Some(3),
+        per_page: Some(500),
+      }),
+      Extension(Arc::new(repository)),
+      Claims::default(),
+    )
+    .await;
+
+    assert!(matches!(res, Ok(transactions) if transactions.len() == 1));
+  }
+
+  #[tokio::test]
+  async fn test_get_account_history() {
+    let mut repository = MockAccountRepository::new();
+
+    repository
+      .expect_get_account()
+      .with(eq(1))
+      .times(1)
+      .returning(|_| Ok(Account::new(1, ":address:", dec!(125), dec!(50), 0.0)));
+
+    repository
+      .expect_get_snapshots()
+      .with(eq(SnapshotFilter {
+        account_id: 1,
+        from_ms: 0,
+        to_ms: 3 * DAY_MS,
+      }))
+      .times(1)
+      .returning(|_| {
+        Ok(
+          [100, 2000, DAY_MS + 1, 2 * DAY_MS + 5, 2 * DAY_MS + 6]
+            .into_iter()
+            .map(|epoch_ms| Snapshot::new(1, 1, epoch_ms, dec!(125), dec!(50), 0.0))
+            .collect(),
+        )
+      });
+
+    let res = get_account_history(
+      Path(1),
+      Query(HistoryQuery {
+        from: Some(0),
+        to: Some(3 * DAY_MS),
+        resolution: Some(Resolution::Day),
+      }),
+      Extension(Arc::new(repository)),
+      Claims::default(),
+    )
+    .await;
+
+    let epochs: Vec<_> = res.unwrap().iter().map(|s| s.get_epoch_ms()).collect();
+
+    assert_eq!(vec![2000, DAY_MS + 1, 2 * DAY_MS + 6], epochs);
+  }
+
+  #[test]
+  fn test_history_query() {
+    let query: HistoryQuery = serde_json::from_str(r#"{"from":1000,"resolution":"hour"}"#).unwrap();
+
+    assert_eq!(Some(1000), query.from);
+    assert_eq!(None, query.to);
+    assert_eq!(Some(Resolution::Hour), query.resolution);
+    assert_eq!(Some(3_600_000), Resolution::Hour.as_ms());
+    assert_eq!(None, Resolution::Raw.as_ms());
+  }
+
+  #[tokio::test]
+  async fn test_get_account_transactions_not_found() {
+    let mut repository = MockAccountRepository::new();
+
+    repository
+      .expect_get_account()
+      .times(1)
+      .returning(|_| Err(RepositoryError::NotFound));
+
+    repository.expect_get_transactions().times(0);
+
+    let res = get_account_transactions(
+      Path(1),
+      Query(TransactionQuery::default()),
+      Extension(Arc::new(repository)),
+      Claims::default(),
+    )
+    .await;
+
+    assert!(matches!(res, Err(AppError::AccountNotFound)));
+  }
+
   #[test]
   fn test_create_pair_request() {
     let value = "{\"base\":\"ETH\",\"quote\":\"CHF\"}";
@@ -667,4 +1310,259 @@ mod tests {
 
     assert!(matches!(&res, Ok(_)), "wrong result: {:?}", res);
   }
+
+  fn pool(epoch_ms: i64) -> Pool {
+    Pool::new(
+      1,
+      42,
+      epoch_ms,
+      "open_for_all",
+      crate::model::CommissionRates {
+        baking: 0.1,
+        finalization: 1.0,
+        transaction: 0.1,
+      },
+      crate::model::PoolCapital {
+        equity: dec!(1000),
+        delegated: dec!(250),
+        delegated_cap: dec!(3000),
+      },
+      None,
+    )
+  }
+
+  #[tokio::test]
+  async fn test_get_pool() {
+    let mut repository = MockPoolRepository::new();
+
+    repository
+      .expect_get_last()
+      .with(eq(42))
+      .times(1)
+      .returning(|_| Ok(pool(1000)));
+
+    let res = get_pool(Path(42), Extension(Arc::new(repository)), Claims::default()).await;
+
+    assert!(matches!(res, Ok(Json(p)) if p == pool(1000)));
+  }
+
+  #[tokio::test]
+  async fn test_get_pool_not_found() {
+    let mut repository = MockPoolRepository::new();
+
+    repository
+      .expect_get_last()
+      .times(1)
+      .returning(|_| Err(RepositoryError::NotFound));
+
+    let res = get_pool(Path(42), Extension(Arc::new(repository)), Claims::default()).await;
+
+    assert!(matches!(res, Err(AppError::PoolNotFound)));
+  }
+
+  #[tokio::test]
+  async fn test_get_pool_history() {
+    let mut repository = MockPoolRepository::new();
+
+    repository
+      .expect_get_history()
+      .with(eq(PoolFilter {
+        baker_id: 42,
+        since_ms: Some(1200),
+      }))
+      .times(1)
+      .returning(|_| Ok(vec![pool(2000), pool(1500)]));
+
+    let query = PoolQuery {
+      since_ms: Some(1200),
+    };
+
+    let res = get_pool_history(
+      Path(42),
+      Query(query),
+      Extension(Arc::new(repository)),
+      Claims::default(),
+    )
+    .await;
+
+    assert!(matches!(res, Ok(pools) if pools.len() == 2));
+  }
+
+  #[tokio::test]
+  async fn test_get_alerts() {
+    let mut repository = MockAlertRepository::new();
+
+    repository
+      .expect_get_alerts()
+      .with(eq(AlertFilter {
+        state: Some(AlertState::Firing),
+        since_ms: Some(1000),
+        until_ms: None,
+      }))
+      .times(1)
+      .returning(|_| Ok(vec![Alert::new(1, "low-peers", "node", "", 2000)]));
+
+    let query = AlertQuery {
+      state: Some(AlertState::Firing),
+      since_ms: Some(1000),
+      until_ms: None,
+    };
+
+    let res = get_alerts(Query(query), Extension(Arc::new(repository)), Claims::default()).await;
+
+    assert!(matches!(res, Ok(alerts) if alerts.len() == 1));
+  }
+
+  #[test]
+  fn test_alert_query() {
+    let res: AlertQuery = serde_json::from_str("{\"state\":\"resolved\",\"until_ms\":1000}").unwrap();
+
+    assert_eq!(Some(AlertState::Resolved), res.state);
+    assert_eq!(Some(1000), res.until_ms);
+  }
+
+  #[tokio::test]
+  async fn test_ack_alert() {
+    let mut repository = MockAlertRepository::new();
+
+    repository
+      .expect_ack_alert()
+      .with(eq(1), eq(Some(42)), always())
+      .times(1)
+      .returning(|id, user_id, at| Ok(Alert::new(id, "low-peers", "node", "", 0).with_ack(at, user_id)));
+
+    repository
+      .expect_ack_alert()
+      .with(eq(2), always(), always())
+      .times(1)
+      .returning(|_, _, _| Err(RepositoryError::NotFound));
+
+    let repository: DynAlertRepository = Arc::new(repository);
+    let claims = || Claims::builder().user_id(42).roles(vec![Role::Admin]).build();
+
+    let res = ack_alert(Path(1), Extension(repository.clone()), claims()).await;
+
+    assert!(matches!(res, Ok(_)));
+
+    let res = ack_alert(Path(2), Extension(repository.clone()), claims()).await;
+
+    assert!(matches!(res, Err(AppError::AlertNotFound)));
+
+    let res = ack_alert(Path(1), Extension(repository), Claims::default()).await;
+
+    assert!(matches!(res, Err(AppError::Forbidden)));
+  }
+
+  #[tokio::test]
+  async fn test_create_silence() {
+    let mut repository = MockAlertRepository::new();
+
+    repository
+      .expect_create_silence()
+      .with(eq(NewSilence {
+        rule: Some("low-peers".to_string()),
+        subject: None,
+        reason: "maintenance".to_string(),
+        created_by: Some(42),
+        starts_ms: 1000,
+        ends_ms: 61000,
+      }))
+      .times(1)
+      .returning(|s| {
+        Ok(Silence::new(1, s.rule.as_deref(), None, &s.reason, s.starts_ms, s.ends_ms).with_creator(s.created_by))
+      });
+
+    let request: CreateSilence = serde_json::from_str(
+      "{\"rule\":\"low-peers\",\"reason\":\"maintenance\",\"starts_ms\":1000,\"duration_ms\":60000}",
+    )
+    .unwrap();
+
+    let res = create_silence(
+      Json(request),
+      Extension(Arc::new(repository)),
+      Claims::builder().user_id(42).roles(vec![Role::Admin]).build(),
+    )
+    .await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_create_silence_invalid() {
+    let mut repository = MockAlertRepository::new();
+
+    repository.expect_create_silence().times(0);
+
+    let repository: DynAlertRepository = Arc::new(repository);
+
+    let claims = || Claims::builder().roles(vec![Role::Admin]).build();
+
+    for (starts_ms, duration_ms) in [(None, 0), (None, MAX_SILENCE_MS + 1), (Some(i64::MAX), 1000)] {
+      let request = CreateSilence {
+        rule: None,
+        subject: None,
+        reason: String::new(),
+        starts_ms,
+        duration_ms,
+      };
+
+      let res = create_silence(Json(request), Extension(repository.clone()), claims()).await;
+
+      assert!(matches!(res, Err(AppError::InvalidSilence)));
+    }
+
+    let request = CreateSilence {
+      rule: None,
+      subject: None,
+      reason: String::new(),
+      starts_ms: None,
+      duration_ms: 1000,
+    };
+
+    let res = create_silence(Json(request), Extension(repository), Claims::default()).await;
+
+    assert!(matches!(res, Err(AppError::Forbidden)));
+  }
+
+  #[tokio::test]
+  async fn test_get_and_delete_silences() {
+    let mut repository = MockAlertRepository::new();
+
+    repository
+      .expect_get_silences()
+      .times(1)
+      .returning(|_| Ok(vec![Silence::new(1, None, None, "", 0, i64::MAX)]));
+
+    repository
+      .expect_delete_silence()
+      .with(eq(1))
+      .times(1)
+      .returning(|_| Ok(()));
+
+    repository
+      .expect_delete_silence()
+      .with(eq(2))
+      .times(1)
+      .returning(|_| Err(RepositoryError::NotFound));
+
+    let repository: DynAlertRepository = Arc::new(repository);
+
+    let res = get_silences(Extension(repository.clone()), Claims::default()).await;
+
+    assert!(matches!(res, Ok(silences) if silences.len() == 1));
+
+    let claims = || Claims::builder().roles(vec![Role::Admin]).build();
+
+    let res = delete_silence(Path(1), Extension(repository.clone()), claims()).await;
+
+    assert!(matches!(res, Ok(StatusCode::NO_CONTENT)));
+
+    let res = delete_silence(Path(2), Extension(repository.clone()), claims()).await;
+
+    assert!(matches!(res, Err(AppError::SilenceNotFound)));
+
+    let res = delete_silence(Path(1), Extension(repository), Claims::default()).await;
+
+    assert!(matches!(res, Err(AppError::Forbidden)));
+  }
 }
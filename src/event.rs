@@ -0,0 +1,146 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Number of events kept for the subscribers that are late. A subscriber that
+/// falls further behind misses the oldest events.
+const CAPACITY: usize = 4096;
+
+/// An enumeration of the events published by the jobs of the daemon.
+#[derive(Serialize, PartialEq, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+  /// A followed account received rewards in a block, which is the payday
+  /// block since the protocol 4.
+  RewardReceived {
+    account_id: i32,
+    address: String,
+    block_hash: String,
+    amount: Decimal,
+    epoch_ms: i64,
+  },
+  /// A block has been processed, after the rewards and the transactions of the
+  /// followed accounts.
+  BlockIndexed {
+    hash: String,
+    height: i64,
+    slot_time_ms: i64,
+  },
+  /// The balance and the stake of a followed account have been refreshed.
+  AccountUpdated {
+    account_id: i32,
+    address: String,
+    balance: Decimal,
+    stake: Decimal,
+  },
+  /// The price of a pair has been refreshed.
+  PriceUpdated {
+    pair_id: i32,
+    base: String,
+    quote: String,
+    bid: f64,
+    ask: f64,
+  },
+  /// A report of the status of the server and of the node has been made. The
+  /// node fields are missing when the node could not be reached.
+  StatusReported {
+    timestamp_ms: i64,
+    peer_count: Option<usize>,
+    best_block_height: Option<i64>,
+    is_lagging: Option<bool>,
+  },
+}
+
+impl Event {
+  /// Topics to which the events can be subscribed.
+  pub const TOPICS: [&'static str; 5] = ["rewards", "blocks", "accounts", "prices", "status"];
+
+  /// It returns the topic of the event.
+  pub fn topic(&self) -> &'static str {
+    match self {
+      Self::RewardReceived {
+        ..
+      } => "rewards",
+      Self::BlockIndexed {
+        ..
+      } => "blocks",
+      Self::AccountUpdated {
+        ..
+      } => "accounts",
+      Self::PriceUpdated {
+        ..
+      } => "prices",
+      Self::StatusReported {
+        ..
+      } => "status",
+    }
+  }
+}
+
+/// A bus that broadcasts the events to every subscriber. Publishing never
+/// blocks, and an event without subscriber is dropped.
+pub struct EventBus {
+  sender: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+  pub fn new() -> Self {
+    let (sender, _) = broadcast::channel(CAPACITY);
+
+    Self {
+      sender,
+    }
+  }
+
+  pub fn publish(&self, event: Event) {
+    // An error only means that nobody is listening.
+    let _ = self.sender.send(event);
+  }
+
+  pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+    self.sender.subscribe()
+  }
+}
+
+impl Default for EventBus {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+pub type DynEventBus = Arc<EventBus>;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_publish() {
+    let bus = EventBus::new();
+
+    // Nobody is listening yet.
+    bus.publish(Event::BlockIndexed {
+      hash: ":hash-1:".to_string(),
+      height: 1,
+      slot_time_ms: 1000,
+    });
+
+    let mut events = bus.subscribe();
+
+    let event = Event::BlockIndexed {
+      hash: ":hash-2:".to_string(),
+      height: 2,
+      slot_time_ms: 2000,
+    };
+
+    bus.publish(event.clone());
+
+    assert_eq!(event, events.recv().await.unwrap());
+    assert_eq!("blocks", event.topic());
+    assert_eq!(
+      "{\"type\":\"block_indexed\",\"hash\":\":hash-2:\",\"height\":2,\"slot_time_ms\":2000}",
+      serde_json::to_string(&event).unwrap()
+    );
+  }
+}
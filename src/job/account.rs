@@ -1,13 +1,22 @@
 use super::{AsyncJob, Status};
-use crate::client::node::Block;
+use crate::analysis::DAY_MS;
+use crate::client::node::{AccountInfo, Block, DelegationTarget};
 use crate::client::DynNodeClient;
+use crate::event::{DynEventBus, Event, EventBus};
 use crate::model::Account;
 use crate::repository::*;
-use rust_decimal::Decimal;
+use chrono::Utc;
+use std::sync::Arc;
+
+const DEFAULT_RAW_DAYS: i64 = 7;
+const DEFAULT_RETENTION_DAYS: i64 = 365;
 
 pub struct RefreshAccountsJob {
   client: DynNodeClient,
   repository: DynAccountRepository,
+  raw_days: i64,
+  retention_days: i64,
+  events: DynEventBus,
 }
 
 impl RefreshAccountsJob {
@@ -15,9 +24,27 @@ impl RefreshAccountsJob {
     Self {
       client,
       repository,
+      raw_days: DEFAULT_RAW_DAYS,
+      retention_days: DEFAULT_RETENTION_DAYS,
+      events: Arc::new(EventBus::new()),
     }
   }
 
+  /// It defines the number of days during which every snapshot of an account
+  /// is kept before being downsampled to one per day, and the number of days
+  /// after which they are deleted.
+  pub fn with_retention(mut self, raw_days: i64, retention_days: i64) -> Self {
+    self.raw_days = raw_days;
+    self.retention_days = retention_days;
+    self
+  }
+
+  /// It publishes the accounts refreshed on the bus.
+  pub fn with_events(mut self, events: DynEventBus) -> Self {
+    self.events = events;
+    self
+  }
+
   async fn do_account(&self, last_block: &Block, account: &Account) -> Status {
     // Get the balance of the account.
     let info = self
@@ -27,7 +54,9 @@ impl RefreshAccountsJob {
 
     // The response contains the total amount of CCD for the account but we
     // store only the available (and the staked) amount.
-    let stake = info.account_baker.map(|b| b.staked_amount).unwrap_or(Decimal::ZERO);
+    let stake = info.get_staked_amount();
+
+    let (staking, pool_id) = staking_of(&info);
 
     // Get the lottery power of the account.
     let baker = self.client.get_baker(&last_block.hash, account.get_address()).await?;
@@ -39,23 +68,82 @@ impl RefreshAccountsJob {
       stake: stake.into(),
       lottery_power: 0.0,
       pending_update: false,
+      staking,
+      pool_id,
     };
 
     if let Some(baker) = baker {
       new_account.lottery_power = baker.baker_lottery_power;
     }
 
+    // The previous state of the account is overwritten, so a snapshot keeps
+    // track of it over time.
+    let snapshot = NewSnapshot {
+      account_id: account.get_id(),
+      epoch_ms: Utc::now().timestamp_millis(),
+      balance: new_account.balance.0.into(),
+      stake: new_account.stake.0.into(),
+      lottery_power: new_account.lottery_power,
+    };
+
+    let event = Event::AccountUpdated {
+      account_id: account.get_id(),
+      address: account.get_address().to_string(),
+      balance: new_account.balance.0,
+      stake: new_account.stake.0,
+    };
+
     self.repository.set_account(new_account).await?;
 
+    self.repository.add_snapshot(snapshot).await?;
+
+    self.events.publish(event);
+
     Ok(())
   }
 }
 
+/// It returns how the account takes part in the staking, and the baker ID of
+/// its pool if any.
+fn staking_of(info: &AccountInfo) -> (StakingKind, Option<i64>) {
+  if let Some(baker) = &info.account_baker {
+    return (StakingKind::Baker, Some(baker.baker_id as i64));
+  }
+
+  match &info.account_delegation {
+    Some(delegation) => match delegation.delegation_target {
+      DelegationTarget::Passive => (StakingKind::Delegator, None),
+      DelegationTarget::Baker {
+        baker_id,
+      } => (StakingKind::Delegator, Some(baker_id as i64)),
+    },
+    None => (StakingKind::None, None),
+  }
+}
+
 #[async_trait]
 impl AsyncJob for RefreshAccountsJob {
   async fn execute(&self) -> Status {
+    let now_ms = Utc::now().timestamp_millis();
+
+    // The retention of the snapshots applies even when no account needs to be
+    // refreshed.
+    self
+      .repository
+      .downsample_snapshots(now_ms - self.raw_days * DAY_MS, DAY_MS)
+      .await?;
+
+    self
+      .repository
+      .delete_snapshots(now_ms - self.retention_days * DAY_MS)
+      .await?;
+
     let accounts = self.repository.get_for_update().await?;
 
+    if accounts.is_empty() {
+      return Ok(());
+    }
+
     // Get the latest block hash of the consensus to get the most up to date information.
     let last_block = self.client.get_last_block().await?;
 
@@ -70,7 +158,7 @@ impl AsyncJob for RefreshAccountsJob {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::client::node::{AccountInfo, Baker, Block, MockNodeClient};
+  use crate::client::node::{AccountDelegation, Baker, MockNodeClient};
   use crate::repository::MockAccountRepository;
   use mockall::predicate::*;
   use rust_decimal::Decimal;
@@ -99,6 +187,7 @@ mod tests {
           account_index: 123,
           account_address: ":address:".into(),
           account_baker: None,
+          account_delegation: None,
         })
       });
 
@@ -128,11 +217,139 @@ mod tests {
         account.lottery_power == 0.5
           && account.balance.0 == Decimal::from(42)
           && account.stake.0 == Decimal::from(0)
+          && matches!(account.staking, StakingKind::None)
           && !account.pending_update
       })
       .times(1)
       .returning(|_| Ok(Account::new(1, ":address:", dec!(42), dec!(0), 0.0)));
 
+    repository
+      .expect_add_snapshot()
+      .withf(|snapshot| snapshot.account_id == 1 && snapshot.balance.0 == dec!(42) && snapshot.lottery_power == 0.5)
+      .times(1)
+      .returning(|_| Ok(()));
+
+    repository
+      .expect_downsample_snapshots()
+      .withf(|_, interval_ms| *interval_ms == DAY_MS)
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    repository
+      .expect_delete_snapshots()
+      .withf(|before_ms| *before_ms < Utc::now().timestamp_millis() - 364 * DAY_MS)
+      .times(1)
+      .returning(|_| Ok(()));
+
+    let bus = Arc::new(EventBus::new());
+    let mut events = bus.subscribe();
+
+    let job = RefreshAccountsJob::new(Arc::new(client), Arc::new(repository)).with_events(bus);
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+    assert_eq!(
+      Event::AccountUpdated {
+        account_id: 1,
+        address: ":address:".to_string(),
+        balance: dec!(42),
+        stake: dec!(0),
+      },
+      events.try_recv().unwrap()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_execute_delegator() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(1).returning(|| {
+      Ok(Block {
+        hash: ":hash:".to_string(),
+        height: 0,
+      })
+    });
+
+    client.expect_get_account_info().times(1).returning(|_, _| {
+      Ok(AccountInfo {
+        account_nonce: 0,
+        account_amount: Decimal::from(42),
+        account_index: 123,
+        account_address: ":address:".into(),
+        account_baker: None,
+        account_delegation: Some(AccountDelegation {
+          staked_amount: Decimal::from(40),
+          restake_earnings: true,
+          delegation_target: DelegationTarget::Baker {
+            baker_id: 7,
+          },
+        }),
+      })
+    });
+
+    client.expect_get_baker().times(1).returning(|_, _| Ok(None));
+
+    let mut repository = MockAccountRepository::new();
+
+    repository
+      .expect_get_for_update()
+      .times(1)
+      .returning(|| Ok(vec![Account::new(1, ":address:", dec!(0), dec!(0), 0.0)]));
+
+    repository
+      .expect_set_account()
+      .withf(|account| {
+        account.balance.0 == Decimal::from(2)
+          && account.stake.0 == Decimal::from(40)
+          && matches!(account.staking, StakingKind::Delegator)
+          && account.pool_id == Some(7)
+      })
+      .times(1)
+      .returning(|_| Ok(Account::new(1, ":address:", dec!(2), dec!(40), 0.0)));
+
+    repository
+      .expect_add_snapshot()
+      .withf(|snapshot| snapshot.stake.0 == dec!(40))
+      .times(1)
+      .returning(|_| Ok(()));
+
+    repository
+      .expect_downsample_snapshots()
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    repository
+      .expect_delete_snapshots()
+      .withf(|before_ms| *before_ms > Utc::now().timestamp_millis() - 31 * DAY_MS)
+      .times(1)
+      .returning(|_| Ok(()));
+
+    let job = RefreshAccountsJob::new(Arc::new(client), Arc::new(repository)).with_retention(1, 30);
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_execute_no_account() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(0);
+
+    let mut repository = MockAccountRepository::new();
+
+    repository.expect_get_for_update().times(1).returning(|| Ok(vec![]));
+
+    // The retention of the snapshots applies anyway.
+    repository
+      .expect_downsample_snapshots()
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    repository.expect_delete_snapshots().times(1).returning(|_| Ok(()));
+
     let job = RefreshAccountsJob::new(Arc::new(client), Arc::new(repository));
 
     let res = job.execute().await;
@@ -0,0 +1,591 @@
+use super::{AsyncJob, Status};
+use crate::config::{AlertCondition, AlertRule};
+use crate::model::{Alert, StakingKind, Status as Report};
+use crate::notification::{DynNotifier, Kind, Notification, Notifier};
+use crate::repository::{
+  AccountFilter, DynAccountRepository, DynAlertRepository, DynStatusRepository, NewAlert, RepositoryError,
+};
+use chrono::Utc;
+use log::{error, info};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Subject of the alerts about the node and the server it is running on.
+const SUBJECT_NODE: &str = "node";
+
+const MINUTE_MS: i64 = 60_000;
+const HOUR_MS: i64 = 60 * MINUTE_MS;
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+/// A subject for which the condition of a rule is met.
+#[derive(PartialEq, Debug)]
+struct Firing {
+  subject: String,
+  message: String,
+}
+
+impl Firing {
+  fn new(subject: &str, message: String) -> Self {
+    Self {
+      subject: subject.to_string(),
+      message,
+    }
+  }
+}
+
+/// An engine that evaluates the alerting rules against the last status report
+/// and the accounts. An alert fires once per rule and subject, and it is
+/// resolved when the condition is not met anymore.
+pub struct AlertEngine {
+  rules: Vec<AlertRule>,
+  alerts: DynAlertRepository,
+  status: DynStatusRepository,
+  account: DynAccountRepository,
+  notifier: DynNotifier,
+  lock: Mutex<()>,
+}
+
+impl AlertEngine {
+  pub fn new(
+    rules: Vec<AlertRule>,
+    alerts: DynAlertRepository,
+    status: DynStatusRepository,
+    account: DynAccountRepository,
+  ) -> Self {
+    Self {
+      rules,
+      alerts,
+      status,
+      account,
+      notifier: Arc::new(Notifier::default()),
+      lock: Mutex::new(()),
+    }
+  }
+
+  /// It sends a notification when an alert fires or is resolved.
+  pub fn with_notifier(mut self, notifier: DynNotifier) -> Self {
+    self.notifier = notifier;
+    self
+  }
+
+  /// It evaluates every rule and updates the alerts accordingly. The alerts of
+  /// a rule that is not configured anymore are resolved.
+  pub async fn evaluate(&self) -> Result<()> {
+    // Jobs finishing at the same time would otherwise fire the same alert.
+    let _guard = self.lock.lock().await;
+
+    let now_ms = Utc::now().timestamp_millis();
+
+    let mut open = self.alerts.get_firing_alerts().await?;
+
+    for rule in &self.rules {
+      let (firing_alerts, others) = open.into_iter().partition(|a| a.get_rule() == rule.name);
+      open = others;
+
+      let firing = match self.check(&rule.condition, now_ms).await {
+        Ok(Some(firing)) => firing,
+        // The rule cannot be evaluated yet, like before the first report.
+        Ok(None) => continue,
+        Err(e) => {
+          error!("unable to evaluate the alerting rule [{}]: {}", rule.name, e);
+          continue;
+        }
+      };
+
+      self.update(&rule.name, firing, firing_alerts, now_ms).await?;
+    }
+
+    for alert in open {
+      self.resolve(alert, now_ms).await?;
+    }
+
+    Ok(())
+  }
+
+  /// It fires the alerts of the new subjects of a rule, and resolves the ones
+  /// of the subjects that do not meet the condition anymore.
+  async fn update(&self, rule: &str, firing: Vec<Firing>, firing_alerts: Vec<Alert>, now_ms: i64) -> Result<()> {
+    for f in &firing {
+      if !firing_alerts.iter().any(|a| a.get_subject() == f.subject) {
+        let alert = self
+          .alerts
+          .fire_alert(NewAlert::firing(rule, &f.subject, &f.message, now_ms))
+          .await?;
+
+        info!(
+          "alert [{}] is firing for {}: {}",
+          alert.get_rule(),
+          alert.get_subject(),
+          alert.get_message()
+        );
+
+        self
+          .notify(Kind::AlertFiring, &alert, alert.get_message(), now_ms)
+          .await;
+      }
+    }
+
+    for alert in firing_alerts {
+      if !firing.iter().any(|f| f.subject == alert.get_subject()) {
+        self.resolve(alert, now_ms).await?;
+      }
+    }
+
+    Ok(())
+  }
+
+  async fn resolve(&self, alert: Alert, now_ms: i64) -> Result<()> {
+    self.alerts.resolve_alert(alert.get_id(), now_ms).await?;
+
+    info!("alert [{}] is resolved for {}", alert.get_rule(), alert.get_subject());
+
+    self
+      .notify(Kind::AlertResolved, &alert, "the condition is not met anymore", now_ms)
+      .await;
+
+    Ok(())
+  }
+
+  /// It notifies about the alert, unless a silence matches it.
+  async fn notify(&self, kind: Kind, alert: &Alert, message: &str, now_ms: i64) {
+    match self.alerts.get_silences(now_ms).await {
+      Ok(silences) if silences.iter().any(|s| s.matches(alert, now_ms)) => {
+        info!("alert [{}] is silenced for {}", alert.get_rule(), alert.get_subject());
+        return;
+      }
+      Ok(_) => {}
+      Err(e) => error!("unable to read the silences: {}", e),
+    }
+
+    let title = format!("[{}] {}", alert.get_rule(), alert.get_subject());
+
+    self
+      .notifier
+      .notify(&Notification::new(kind, &title, message, now_ms))
+      .await;
+  }
+
+  /// It returns the subjects that meet the condition, or none when the
+  /// condition cannot be evaluated.
+  async fn check(&self, condition: &AlertCondition, now_ms: i64) -> Result<Option<Vec<Firing>>> {
+    match condition {
+      AlertCondition::NoReward {
+        hours,
+      } => self.check_rewards(*hours, now_ms).await.map(Some),
+      _ => match self.status.get_last_report().await {
+        Ok(report) => Ok(check_report(condition, &report)),
+        Err(RepositoryError::NotFound) => Ok(None),
+        Err(e) => Err(e),
+      },
+    }
+  }
+
+  /// It looks for the staking accounts without any reward for the given
+  /// number of hours. An account without any reward yet is left out as there
+  /// is nothing to compare with.
+  async fn check_rewards(&self, hours: i64, now_ms: i64) -> Result<Vec<Firing>> {
+    let accounts = self.account.get_accounts(AccountFilter::default()).await?;
+
+    let mut firing = Vec::new();
+
+    for account in accounts {
+      if account.get_staking() == StakingKind::None {
+        continue;
+      }
+
+      if let Some(last_ms) = self.account.get_last_reward_ms(&account).await? {
+        if now_ms - last_ms > hours * HOUR_MS {
+          firing.push(Firing::new(
+            account.get_address(),
+            format!("no reward for more than {} hours", hours),
+          ));
+        }
+      }
+    }
+
+    Ok(firing)
+  }
+}
+
+/// It checks a condition against a status report. The conditions about the
+/// node cannot be evaluated when the node could not be reached.
+fn check_report(condition: &AlertCondition, report: &Report) -> Option<Vec<Firing>> {
+  let resources = report.get_resources();
+  let node = report.get_node();
+
+  let message = match condition {
+    AlertCondition::NodeUnreachable => node.is_none().then(|| "the node could not be reached".to_string()),
+    AlertCondition::NotInCommittee => {
+      let node = node?;
+
+      (!node.is_baker_committee).then(|| {
+        format!(
+          "the node is not active in the committee of the bakers ({})",
+          node.baker_committee.as_deref().unwrap_or("unknown")
+        )
+      })
+    }
+    AlertCondition::PeerCountBelow {
+      min,
+    } => {
+      let node = node?;
+
+      (node.peer_count < *min).then(|| format!("the node has {} peers", node.peer_count))
+    }
+    AlertCondition::NoFinalizedBlock {
+      minutes,
+    } => {
+      let consensus = node?.consensus.as_ref()?;
+
+      match consensus.sync_lag_ms {
+        Some(lag) => (lag > minutes * MINUTE_MS).then(|| format!("no block finalized for {} minutes", lag / MINUTE_MS)),
+        None => Some("no block finalized yet".to_string()),
+      }
+    }
+    AlertCondition::CpuAbove {
+      threshold,
+    } => {
+      let load = resources.avg_cpu_load?;
+
+      (load > *threshold).then(|| format!("the CPU load is at {:.0}%", load * 100.0))
+    }
+    AlertCondition::MemoryAbove {
+      threshold,
+    } => {
+      let (free, total) = (resources.mem_free?, resources.mem_total?);
+      let used = 1.0 - free as f64 / total.max(1) as f64;
+
+      (used > *threshold).then(|| format!("the memory is used at {:.0}%", used * 100.0))
+    }
+    AlertCondition::NoReward {
+      ..
+    } => return None,
+  };
+
+  Some(message.into_iter().map(|m| Firing::new(SUBJECT_NODE, m)).collect())
+}
+
+/// A job that evaluates the alerting rules after each execution of the job it
+/// wraps, whatever its outcome.
+pub struct AlertedJob {
+  job: Box<dyn AsyncJob>,
+  engine: Arc<AlertEngine>,
+}
+
+impl AlertedJob {
+  pub fn new(job: Box<dyn AsyncJob>, engine: Arc<AlertEngine>) -> Self {
+    Self {
+      job,
+      engine,
+    }
+  }
+}
+
+#[async_trait]
+impl AsyncJob for AlertedJob {
+  async fn execute(&self) -> Status {
+    // The error of the job is kept as a message so that it can be returned
+    // after the evaluation.
+    let res = self.job.execute().await.map_err(|e| e.to_string());
+
+    if let Err(e) = self.engine.evaluate().await {
+      error!("unable to evaluate the alerting rules: {}", e);
+    }
+
+    Ok(res?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::{Account, Silence};
+  use crate::notification::MockChannel;
+  use crate::repository::{
+    ConsensusStatusJson, MockAccountRepository, MockAlertRepository, MockStatusRepository, NodeStatusJson,
+    ResourceStatusJson,
+  };
+  use mockall::predicate::*;
+  use rust_decimal::Decimal;
+
+  fn rule(name: &str, condition: AlertCondition) -> AlertRule {
+    AlertRule {
+      name: name.to_string(),
+      condition,
+    }
+  }
+
+  fn node_status(peer_count: usize, sync_lag_ms: Option<i64>) -> NodeStatusJson {
+    NodeStatusJson {
+      node_id: None,
+      baker_id: Some(42),
+      is_baker_committee: false,
+      is_finalizer_committee: false,
+      uptime_ms: 0,
+      peer_type: "Node".to_string(),
+      peer_average_latency: 0.0,
+      peer_count,
+      endpoint: None,
+      client_retries: 0,
+      cache_hits: 0,
+      cache_misses: 0,
+      baker_committee: Some("added_but_wrong_keys".to_string()),
+      consensus_baker_running: None,
+      consensus_running: None,
+      consensus_type: None,
+      consensus: Some(ConsensusStatusJson {
+        best_block_height: 0,
+        last_finalized_block_height: 0,
+        last_finalized_ms: None,
+        epoch: None,
+        slot_duration_ms: 0,
+        protocol_version: 4,
+        finalization_lag: 0,
+        sync_lag_ms,
+        is_lagging: false,
+      }),
+    }
+  }
+
+  fn report(node: Option<NodeStatusJson>) -> Report {
+    let resources = ResourceStatusJson {
+      avg_cpu_load: Some(0.95),
+      mem_free: Some(100),
+      mem_total: Some(1000),
+      uptime_secs: None,
+    };
+
+    Report::new(1, resources, node, 0)
+  }
+
+  #[test]
+  fn test_check_report() {
+    let status = report(Some(node_status(2, Some(20 * MINUTE_MS))));
+
+    let firing = |condition| check_report(&condition, &status).map(|f| f.len());
+
+    assert_eq!(Some(0), firing(AlertCondition::NodeUnreachable));
+    assert_eq!(Some(1), firing(AlertCondition::NotInCommittee));
+    assert_eq!(
+      Some(1),
+      firing(AlertCondition::PeerCountBelow {
+        min: 3
+      })
+    );
+    assert_eq!(
+      Some(0),
+      firing(AlertCondition::PeerCountBelow {
+        min: 2
+      })
+    );
+    assert_eq!(
+      Some(1),
+      firing(AlertCondition::NoFinalizedBlock {
+        minutes: 10
+      })
+    );
+    assert_eq!(
+      Some(1),
+      firing(AlertCondition::CpuAbove {
+        threshold: 0.9
+      })
+    );
+    assert_eq!(
+      Some(0),
+      firing(AlertCondition::MemoryAbove {
+        threshold: 0.95
+      })
+    );
+  }
+
+  #[test]
+  fn test_check_report_node_unreachable() {
+    let status = report(None);
+
+    assert_eq!(
+      Some(vec![Firing::new(
+        SUBJECT_NODE,
+        "the node could not be reached".to_string()
+      )]),
+      check_report(&AlertCondition::NodeUnreachable, &status)
+    );
+    assert_eq!(None, check_report(&AlertCondition::NotInCommittee, &status));
+  }
+
+  #[tokio::test]
+  async fn test_evaluate() {
+    let mut alerts = MockAlertRepository::new();
+
+    // The peer count alert is already firing, and the alert of a rule that was
+    // removed is still open.
+    alerts.expect_get_firing_alerts().times(1).returning(|| {
+      Ok(vec![
+        Alert::new(1, "low-peers", SUBJECT_NODE, "the node has 1 peers", 0),
+        Alert::new(2, "removed", SUBJECT_NODE, "", 0),
+        Alert::new(3, "committee", SUBJECT_NODE, "", 0),
+      ])
+    });
+
+    alerts
+      .expect_fire_alert()
+      .withf(|a| a.rule == "node-down" && a.subject == SUBJECT_NODE)
+      .times(1)
+      .returning(|a| Ok(Alert::new(4, &a.rule, &a.subject, &a.message, a.fired_ms)));
+
+    alerts
+      .expect_resolve_alert()
+      .with(eq(2), always())
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    let mut status = MockStatusRepository::new();
+
+    status.expect_get_last_report().returning(|| Ok(report(None)));
+
+    let mut channel = MockChannel::new();
+
+    channel
+      .expect_send()
+      .withf(|n| n.kind == Kind::AlertFiring && n.title == "[node-down] node")
+      .times(1)
+      .returning(|_| Ok(()));
+
+    // The resolution of the removed rule is silenced.
+    channel.expect_send().withf(|n| n.kind == Kind::AlertResolved).times(0);
+
+    alerts
+      .expect_get_silences()
+      .returning(|at| Ok(vec![Silence::new(1, Some("removed"), None, "", at, at + 1)]));
+
+    let engine = AlertEngine::new(
+      vec![
+        rule("node-down", AlertCondition::NodeUnreachable),
+        rule(
+          "low-peers",
+          AlertCondition::PeerCountBelow {
+            min: 3,
+          },
+        ),
+        // The condition cannot be evaluated so the alert stays open.
+        rule("committee", AlertCondition::NotInCommittee),
+      ],
+      Arc::new(alerts),
+      Arc::new(status),
+      Arc::new(MockAccountRepository::new()),
+    )
+    .with_notifier(Arc::new(Notifier::new(vec![Arc::new(channel)])));
+
+    let res = engine.evaluate().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_evaluate_resolved() {
+    let mut alerts = MockAlertRepository::new();
+
+    alerts
+      .expect_get_firing_alerts()
+      .times(1)
+      .returning(|| Ok(vec![Alert::new(1, "node-down", SUBJECT_NODE, "", 0)]));
+
+    alerts.expect_fire_alert().times(0);
+
+    alerts
+      .expect_resolve_alert()
+      .with(eq(1), always())
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    alerts.expect_get_silences().returning(|_| Ok(vec![]));
+
+    let mut status = MockStatusRepository::new();
+
+    status
+      .expect_get_last_report()
+      .returning(|| Ok(report(Some(node_status(8, Some(0))))));
+
+    let engine = AlertEngine::new(
+      vec![rule("node-down", AlertCondition::NodeUnreachable)],
+      Arc::new(alerts),
+      Arc::new(status),
+      Arc::new(MockAccountRepository::new()),
+    );
+
+    let res = engine.evaluate().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_evaluate_no_reward() {
+    let mut account = MockAccountRepository::new();
+
+    account.expect_get_accounts().times(1).returning(|_| {
+      Ok(vec![
+        Account::new(1, ":address-1:", Decimal::ZERO, Decimal::ZERO, 0.0).with_staking(StakingKind::Baker, Some(42)),
+        Account::new(2, ":address-2:", Decimal::ZERO, Decimal::ZERO, 0.0),
+      ])
+    });
+
+    account
+      .expect_get_last_reward_ms()
+      .withf(|a| a.get_id() == 1)
+      .times(1)
+      .returning(|_| Ok(Some(0)));
+
+    let mut alerts = MockAlertRepository::new();
+
+    alerts.expect_get_firing_alerts().times(1).returning(|| Ok(vec![]));
+
+    alerts
+      .expect_fire_alert()
+      .withf(|a| a.rule == "no-reward" && a.subject == ":address-1:")
+      .times(1)
+      .returning(|a| Ok(Alert::new(1, &a.rule, &a.subject, &a.message, a.fired_ms)));
+
+    alerts.expect_get_silences().returning(|_| Ok(vec![]));
+
+    let engine = AlertEngine::new(
+      vec![rule(
+        "no-reward",
+        AlertCondition::NoReward {
+          hours: 25,
+        },
+      )],
+      Arc::new(alerts),
+      Arc::new(MockStatusRepository::new()),
+      Arc::new(account),
+    );
+
+    let res = engine.evaluate().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_evaluate_no_report() {
+    let mut alerts = MockAlertRepository::new();
+
+    alerts.expect_get_firing_alerts().times(1).returning(|| Ok(vec![]));
+
+    let mut status = MockStatusRepository::new();
+
+    status
+      .expect_get_last_report()
+      .times(1)
+      .returning(|| Err(RepositoryError::NotFound));
+
+    let engine = AlertEngine::new(
+      vec![rule("node-down", AlertCondition::NodeUnreachable)],
+      Arc::new(alerts),
+      Arc::new(status),
+      Arc::new(MockAccountRepository::new()),
+    );
+
+    let res = engine.evaluate().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+}
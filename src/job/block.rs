@@ -1,20 +1,62 @@
 use super::{AsyncJob, Status};
-use crate::client::node::BlockInfo;
+use crate::client::node::{Address, BlockInfo, Event, TransactionEvent, TransactionResult, TransactionSummary};
 use crate::client::DynNodeClient;
-use crate::model::RewardKind as Kind;
+use crate::event::{DynEventBus, Event as DaemonEvent, EventBus};
+use crate::model::{Account, Block, RewardKind as Kind, StakingKind};
+use crate::notification::{self, DynNotifier, Notification, Notifier};
 use crate::repository::*;
+use chrono::Utc;
 use log::{info, warn};
 use rust_decimal::Decimal;
-use std::collections::HashMap;
-
-const EVENT_TAG_REWARD: &str = "PaydayAccountReward";
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+use tokio_stream::StreamExt;
 
 const GC_OFFSET: i64 = 500_000;
 
+/// Number of blocks processed from the stream between two garbage collections.
+const GC_INTERVAL: i64 = 1_000;
+
+/// Time during which an execution follows the stream of finalized blocks
+/// before returning, so that the scheduler can record it and run the jobs
+/// that depend on it.
+const STREAM_WINDOW: Duration = Duration::from_secs(60);
+
+/// Maximum age of a block for its rewards to be notified, so that catching up
+/// does not flood the channels.
+const NOTIFY_MAX_AGE_MS: i64 = 60 * 60 * 1000;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// The amounts distributed to an account by the special events of a block.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct AccountRewards {
+  baker: Decimal,
+  transaction_fees: Decimal,
+  finalization: Decimal,
+}
+
+/// A transaction of a block seen from the point of view of one of the
+/// accounts it touches.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct AccountTransaction {
+  hash: String,
+  kind: String,
+  amount: Decimal,
+  counterparty: Option<String>,
+  cost: Decimal,
+}
+
 pub struct BlockFetcher {
   client: DynNodeClient,
   block_repository: DynBlockRepository,
   account_repository: DynAccountRepository,
+  streaming: bool,
+  start_height: Option<i64>,
+  notifier: DynNotifier,
+  events: DynEventBus,
 }
 
 impl BlockFetcher {
@@ -27,7 +69,153 @@ impl BlockFetcher {
       client,
       block_repository,
       account_repository,
+      streaming: false,
+      start_height: None,
+      notifier: Arc::new(Notifier::default()),
+      events: Arc::new(EventBus::new()),
+    }
+  }
+
+  /// It enables the streaming mode where each execution follows the blocks
+  /// finalized by the node for a while after catching up.
+  pub fn with_streaming(mut self, streaming: bool) -> Self {
+    self.streaming = streaming;
+    self
+  }
+
+  /// It defines the absolute height of the first block to process when none
+  /// has been indexed yet. Otherwise, it starts from the last finalized block.
+  pub fn with_start_height(mut self, start_height: Option<i64>) -> Self {
+    self.start_height = start_height;
+    self
+  }
+
+  /// It sends a notification for the rewards of the followed accounts found in
+  /// recent blocks.
+  pub fn with_notifier(mut self, notifier: DynNotifier) -> Self {
+    self.notifier = notifier;
+    self
+  }
+
+  /// It publishes the blocks processed and the rewards found on the bus.
+  pub fn with_events(mut self, events: DynEventBus) -> Self {
+    self.events = events;
+    self
+  }
+
+  /// It processes the first block when the storage is empty and returns it.
+  async fn start(&self, last_height: i64) -> Result<Block> {
+    let height = self.start_height.unwrap_or(last_height);
+
+    match self.client.get_block_at_height(None, height).await? {
+      Some(block_hash) => self.do_block(&block_hash).await?,
+      None => return Err(format!("unable to find the start block at height `{}`", height).into()),
+    }
+
+    info!("indexing has started from height `{}`", height);
+
+    Ok(self.block_repository.get_last_block().await?)
+  }
+
+  /// It processes the blocks following the last one indexed until the given
+  /// height, and it returns the height of the next block to process.
+  ///
+  /// The blocks are resolved by their height in the era of the last one, and
+  /// the catch-up moves to the next era when a protocol update has ended the
+  /// current one.
+  async fn catch_up(&self, last_height: i64) -> Result<i64> {
+    let current_block = match self.block_repository.get_last_block().await {
+      Ok(block) => Some(block),
+      Err(RepositoryError::NotFound) => None,
+      Err(e) => return Err(e.into()),
+    };
+
+    // The error of the repository cannot be held across the processing of the
+    // first block, hence the two steps.
+    let current_block = match current_block {
+      Some(block) => block,
+      None => self.start(last_height).await?,
+    };
+
+    // The era of the last block is asked to the node as the blocks indexed
+    // before the support of the protocol updates do not know about it.
+    let current_info = self.client.get_block_info(current_block.get_hash()).await?;
+
+    let mut genesis_index = current_info.genesis_index;
+    let mut era_height = current_info.era_block_height + 1;
+    let mut height = current_block.get_height() + 1;
+
+    while height <= last_height {
+      let block_hash = match self.client.get_block_at_height(Some(genesis_index), era_height).await? {
+        Some(block_hash) => block_hash,
+        None => match self.client.get_block_at_height(Some(genesis_index + 1), 0).await? {
+          Some(block_hash) => {
+            info!("a new era has started at height `{}`", height);
+
+            genesis_index += 1;
+            era_height = 0;
+            block_hash
+          }
+          None => {
+            warn!("unable to find a proper hash for height {}", height);
+            break;
+          }
+        },
+      };
+
+      self.do_block(&block_hash).await?;
+
+      height += 1;
+      era_height += 1;
+    }
+
+    Ok(height)
+  }
+
+  /// It subscribes to the finalized blocks of the node and processes them as
+  /// they arrive, until the window has elapsed. It returns early when the
+  /// stream is interrupted so that the next execution falls back to catching
+  /// up.
+  async fn follow(&self, mut height: i64, window: Duration) -> Status {
+    let deadline = Instant::now() + window;
+    let mut stream = self.client.get_finalized_blocks().await?;
+
+    info!("following the finalized blocks from height `{}`", height);
+
+    loop {
+      let block = match time::timeout_at(deadline, stream.next()).await {
+        Ok(Some(block)) => block?,
+        Ok(None) => break,
+        // The window has elapsed and the next execution subscribes again.
+        Err(_) => return Ok(()),
+      };
+
+      if block.height < height {
+        // The block has already been processed.
+        continue;
+      }
+
+      if block.height > height {
+        // Some blocks have been missed and they need to be processed first.
+        height = self.catch_up(block.height - 1).await?;
+
+        if height < block.height {
+          return Ok(());
+        }
+      }
+
+      self.do_block(&block.hash).await?;
+
+      height = block.height + 1;
+
+      if height % GC_INTERVAL == 0 {
+        self.block_repository.garbage_collect(height - GC_OFFSET).await?;
+      }
     }
+
+    warn!("the stream of finalized blocks has been interrupted");
+
+    Ok(())
   }
 
   /// It processes a block by fetching the data about it and analyzes it to
@@ -35,18 +223,34 @@ impl BlockFetcher {
   async fn do_block(&self, block_hash: &str) -> Status {
     let info = self.client.get_block_info(block_hash).await?;
 
-    // Insert the account rewards before processing the block.
-    self.do_rewards(&info).await?;
+    // Insert the account rewards and transactions before processing the block.
+    let rewards = self.do_accounts(&info).await?;
 
     let new_block = NewBlock {
-      hash: info.block_hash,
+      hash: info.block_hash.clone(),
       height: info.block_height,
       slot_time_ms: info.block_slot_time.timestamp_millis(),
       baker: info.block_baker.unwrap_or(0),
+      genesis_index: info.genesis_index as i32,
+      protocol_version: info.protocol_version as i32,
+    };
+
+    let event = DaemonEvent::BlockIndexed {
+      hash: info.block_hash.clone(),
+      height: new_block.height,
+      slot_time_ms: new_block.slot_time_ms,
     };
 
     self.block_repository.store(new_block).await?;
 
+    // The rewards are announced only once the block is stored, so that a block
+    // processed again after a failure does not announce them twice.
+    for (account, amount) in &rewards {
+      self.announce_reward(account, *amount, &info).await;
+    }
+
+    self.events.publish(event);
+
     info!(
       "block at height `{}` has been processed successfullly",
       info.block_height
@@ -55,57 +259,94 @@ impl BlockFetcher {
     Ok(())
   }
 
-  /// It fetches the special events of a block and tries to find rewards for
-  /// the followed accounts.
-  async fn do_rewards(&self, block_info: &BlockInfo) -> Status {
+  /// It fetches the summary of a block and tries to find the rewards and the
+  /// transactions of the followed accounts. It returns the total of the
+  /// rewards found for each account.
+  async fn do_accounts(&self, block_info: &BlockInfo) -> Result<Vec<(Account, Decimal)>> {
     let summary = self.client.get_block_summary(&block_info.block_hash).await?;
 
-    let mut rewards = HashMap::new();
+    let rewards = collect_rewards(&summary.special_events);
+    let transactions = collect_transactions(&summary.transaction_summaries);
 
-    for event in summary.special_events {
-      if event.tag != EVENT_TAG_REWARD {
-        continue;
-      }
-
-      if let Some(addr) = &event.account {
-        rewards.insert(
-          addr.clone(),
-          (
-            event.baker_reward.unwrap_or(Decimal::ZERO),
-            event.transaction_fees.unwrap_or(Decimal::ZERO),
-          ),
-        );
-      }
-    }
+    let addresses: HashSet<&String> = rewards.keys().chain(transactions.keys()).collect();
 
     let accounts = self
       .account_repository
       .get_accounts(AccountFilter {
-        addresses: Some(rewards.keys().map(String::as_str).collect()),
+        addresses: Some(addresses.iter().map(|a| a.as_str()).collect()),
       })
       .await?;
 
+    let epoch_ms = block_info.block_slot_time.timestamp_millis();
+    let mut found = Vec::new();
+
     for account in accounts {
-      if let Some((baker, fees)) = rewards.get(account.get_address()) {
+      for transaction in transactions.get(account.get_address()).into_iter().flatten() {
+        info!("transaction found for account `{}`", account.get_address());
+
+        let new_transaction = NewTransaction {
+          account_id: account.get_id(),
+          block_hash: block_info.block_hash.clone(),
+          transaction_hash: transaction.hash.clone(),
+          epoch_ms,
+          kind: transaction.kind.clone(),
+          amount: transaction.amount.into(),
+          counterparty: transaction.counterparty.clone(),
+          cost: transaction.cost.into(),
+        };
+
+        self.account_repository.set_transaction(new_transaction).await?;
+      }
+
+      if let Some(values) = rewards.get(account.get_address()) {
         info!("rewards found for account `{}`", account.get_address());
 
         insert_rewards(
           &self.account_repository,
-          account.get_id(),
+          &account,
           &block_info.block_hash,
-          block_info.block_slot_time.timestamp_millis(),
-          (*baker, *fees),
+          epoch_ms,
+          *values,
         )
         .await?;
+
+        let total = values.baker + values.transaction_fees + values.finalization;
+
+        found.push((account, total));
       }
     }
 
     self
       .account_repository
-      .set_for_update(rewards.keys().cloned().collect(), true)
+      .set_for_update(addresses.into_iter().cloned().collect(), true)
       .await?;
 
-    Ok(())
+    Ok(found)
+  }
+
+  /// It publishes a reward of an account found in a block that has been
+  /// stored, and it notifies it when the block is recent.
+  async fn announce_reward(&self, account: &Account, amount: Decimal, block_info: &BlockInfo) {
+    let epoch_ms = block_info.block_slot_time.timestamp_millis();
+
+    self.events.publish(DaemonEvent::RewardReceived {
+      account_id: account.get_id(),
+      address: account.get_address().to_string(),
+      block_hash: block_info.block_hash.clone(),
+      amount,
+      epoch_ms,
+    });
+
+    if Utc::now().timestamp_millis() - epoch_ms <= NOTIFY_MAX_AGE_MS {
+      let notification = Notification::new(
+        notification::Kind::Reward,
+        &format!("reward for account `{}`", account.get_address()),
+        &format!("{} CCD received in block {}", amount, block_info.block_hash),
+        epoch_ms,
+      );
+
+      self.notifier.notify(&notification).await;
+    }
   }
 }
 
@@ -116,25 +357,15 @@ impl AsyncJob for BlockFetcher {
     // blocks need to be caught up.
     let last_block = self.client.get_last_block().await?;
 
-    let current_block = self.block_repository.get_last_block().await?;
-
-    let mut height = current_block.get_height() + 1;
-
-    while height <= last_block.height {
-      match self.client.get_block_at_height(height).await? {
-        Some(block_hash) => self.do_block(&block_hash).await?,
-        None => {
-          warn!("unable to find a proper hash for height {}", height);
-          break;
-        }
-      }
-
-      height += 1;
-    }
+    let height = self.catch_up(last_block.height).await?;
 
     // Truncate the block table to avoid filling up the space.
     self.block_repository.garbage_collect(height - GC_OFFSET).await?;
 
+    if self.streaming {
+      self.follow(height, STREAM_WINDOW).await?;
+    }
+
     Ok(())
   }
 }
@@ -162,35 +393,24 @@ impl AsyncJob for RewardRepairer {
       let rewards = self.repository.get_rewards(&account).await?;
 
       for reward in rewards {
-        if *reward.get_kind() == Kind::TransactionFee {
+        if !matches!(reward.get_kind(), Kind::Baker | Kind::Delegation) {
           // Do only one time per block.
           continue;
         }
 
         let summary = self.client.get_block_summary(reward.get_block_hash()).await?;
 
-        for event in summary.special_events {
-          if event.tag != EVENT_TAG_REWARD {
-            continue;
-          }
-
-          if let Some(addr) = &event.account {
-            if addr == account.get_address() {
-              let values = (
-                event.baker_reward.unwrap_or(Decimal::ZERO),
-                event.transaction_fees.unwrap_or(Decimal::ZERO),
-              );
-
-              insert_rewards(
-                &self.repository,
-                account.get_id(),
-                reward.get_block_hash(),
-                reward.get_epoch_ms(),
-                values,
-              )
-              .await?;
-            }
-          }
+        let rewards = collect_rewards(&summary.special_events);
+
+        if let Some(values) = rewards.get(account.get_address()) {
+          insert_rewards(
+            &self.repository,
+            &account,
+            reward.get_block_hash(),
+            reward.get_epoch_ms(),
+            *values,
+          )
+          .await?;
         }
       }
     }
@@ -199,18 +419,153 @@ impl AsyncJob for RewardRepairer {
   }
 }
 
-/// It inserts the baker reward and the transaction fees for the account and the block info.
+/// It sums the amounts distributed to each account by the special events of a
+/// block, either on a payday or directly before the delegation protocol.
+fn collect_rewards(events: &[Event]) -> HashMap<String, AccountRewards> {
+  let mut rewards: HashMap<String, AccountRewards> = HashMap::new();
+
+  for event in events {
+    match event {
+      Event::PaydayAccountReward {
+        account,
+        transaction_fees,
+        baker_reward,
+        finalization_reward,
+      } => {
+        let entry = rewards.entry(account.clone()).or_default();
+        entry.baker += baker_reward;
+        entry.transaction_fees += transaction_fees;
+        entry.finalization += finalization_reward;
+      }
+      // The reward of the baker of a block is its share of the transaction
+      // fees and of the GAS account, the baking rewards being minted per epoch.
+      Event::BlockReward {
+        baker,
+        baker_reward,
+        ..
+      } => {
+        rewards.entry(baker.clone()).or_default().transaction_fees += baker_reward;
+      }
+      Event::BakingRewards {
+        baker_rewards,
+        ..
+      } => {
+        for value in baker_rewards {
+          rewards.entry(value.address.clone()).or_default().baker += value.amount;
+        }
+      }
+      Event::FinalizationRewards {
+        finalization_rewards,
+        ..
+      } => {
+        for value in finalization_rewards {
+          rewards.entry(value.address.clone()).or_default().finalization += value.amount;
+        }
+      }
+      // The events of a later protocol cannot distribute known rewards.
+      Event::Unknown => {}
+      _ => {}
+    }
+  }
+
+  rewards
+}
+
+/// It finds the transactions of a block for each account they touch. The sender
+/// pays the cost of the transaction, and the amounts transferred are summed up
+/// for each account, negatively when they are sent.
+fn collect_transactions(summaries: &[TransactionSummary]) -> HashMap<String, Vec<AccountTransaction>> {
+  let mut transactions: HashMap<String, Vec<AccountTransaction>> = HashMap::new();
+
+  for summary in summaries {
+    let mut touched: HashMap<String, AccountTransaction> = HashMap::new();
+
+    let new_transaction = || AccountTransaction {
+      hash: summary.hash.clone(),
+      kind: summary.kind.contents.as_ref().unwrap_or(&summary.kind.kind).clone(),
+      ..Default::default()
+    };
+
+    if let Some(sender) = &summary.sender {
+      touched.entry(sender.clone()).or_insert_with(new_transaction).cost = summary.cost;
+    }
+
+    let events = match &summary.result {
+      TransactionResult::Success {
+        events,
+      } => events.as_slice(),
+      TransactionResult::Reject => &[],
+    };
+
+    for event in events {
+      if let TransactionEvent::Transferred {
+        amount,
+        from,
+        to,
+      } = event
+      {
+        if let Address::AddressAccount(account) = from {
+          let entry = touched.entry(account.clone()).or_insert_with(new_transaction);
+          entry.amount -= amount;
+          entry.counterparty = Some(address_name(to));
+        }
+
+        if let Address::AddressAccount(account) = to {
+          let entry = touched.entry(account.clone()).or_insert_with(new_transaction);
+          entry.amount += amount;
+          entry.counterparty = Some(address_name(from));
+        }
+      }
+    }
+
+    for (account, transaction) in touched {
+      transactions.entry(account).or_default().push(transaction);
+    }
+  }
+
+  transactions
+}
+
+/// It returns the textual representation of an address, where a contract is
+/// written as `<index,subindex>`.
+fn address_name(address: &Address) -> String {
+  match address {
+    Address::AddressAccount(account) => account.clone(),
+    Address::AddressContract(contract) => format!("<{},{}>", contract.index, contract.subindex),
+  }
+}
+
+/// It inserts the baker reward and the transaction fees for the account and the
+/// block info. The finalization reward is inserted only when it is not zero. A
+/// delegator receives a share of the rewards of its pool which is inserted as a
+/// single delegation reward.
 async fn insert_rewards(
   repository: &DynAccountRepository,
-  account_id: i32,
+  account: &Account,
   hash: &str,
   epoch_ms: i64,
-  (baker, fees): (Decimal, Decimal),
+  values: AccountRewards,
 ) -> Status {
+  let account_id = account.get_id();
+
+  if account.get_staking() == StakingKind::Delegator {
+    let reward = NewReward {
+      account_id,
+      block_hash: hash.to_string(),
+      amount: (values.baker + values.transaction_fees + values.finalization).into(),
+      epoch_ms,
+      kind: RewardKind::Delegation,
+    };
+
+    repository.set_reward(reward).await?;
+
+    return Ok(());
+  }
+
   let baker_reward = NewReward {
     account_id,
     block_hash: hash.to_string(),
-    amount: baker.into(),
+    amount: values.baker.into(),
     epoch_ms,
     kind: RewardKind::Baker,
   };
@@ -220,21 +575,36 @@ async fn insert_rewards(
   let tx_fee = NewReward {
     account_id,
     block_hash: hash.to_string(),
-    amount: fees.into(),
+    amount: values.transaction_fees.into(),
     epoch_ms,
     kind: RewardKind::TransactionFee,
   };
 
   repository.set_reward(tx_fee).await?;
 
+  if !values.finalization.is_zero() {
+    let finalization_reward = NewReward {
+      account_id,
+      block_hash: hash.to_string(),
+      amount: values.finalization.into(),
+      epoch_ms,
+      kind: RewardKind::Finalization,
+    };
+
+    repository.set_reward(finalization_reward).await?;
+  }
+
   Ok(())
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::client::node::{BlockInfo, BlockSummary, Event, MockNodeClient};
-  use crate::model::{Account, Block, Reward};
+  use crate::client::node::{
+    AccountAmount, BlockInfo, BlockSummary, ContractAddress, Event, MockNodeClient, TransactionType,
+  };
+  use crate::model::{Account, Reward};
+  use crate::notification::MockChannel;
   use crate::repository::{MockAccountRepository, MockBlockRepository};
   use chrono::Utc;
   use mockall::predicate::*;
@@ -242,6 +612,38 @@ mod tests {
   use rust_decimal_macros::dec;
   use std::sync::Arc;
 
+  fn block_info(hash: &str, genesis_index: u32, height: i64, era_height: i64) -> BlockInfo {
+    BlockInfo {
+      block_hash: hash.to_string(),
+      block_height: height,
+      finalized: true,
+      block_baker: Some(42),
+      block_slot_time: Utc::now(),
+      genesis_index,
+      era_block_height: era_height,
+      protocol_version: genesis_index + 4,
+    }
+  }
+
+  fn transfer(hash: &str, from: &str, to: &str, amount: i64) -> TransactionSummary {
+    TransactionSummary {
+      hash: hash.to_string(),
+      sender: Some(from.to_string()),
+      cost: Decimal::from(2),
+      kind: TransactionType {
+        kind: "accountTransaction".to_string(),
+        contents: Some("transfer".to_string()),
+      },
+      result: TransactionResult::Success {
+        events: vec![TransactionEvent::Transferred {
+          amount: Decimal::from(amount),
+          from: Address::AddressAccount(from.to_string()),
+          to: Address::AddressAccount(to.to_string()),
+        }],
+      },
+    }
+  }
+
   #[tokio::test]
   async fn test_block_fetcher_execute() {
     let mut client = MockNodeClient::new();
@@ -253,42 +655,38 @@ mod tests {
       })
     });
 
+    // The era has no more block after the height 101 and no new era has
+    // started yet.
     client
       .expect_get_block_at_height()
-      .times(2)
-      .returning(|height| match height {
-        101 => Ok(Some(":hash-101:".to_string())),
+      .times(3)
+      .returning(|genesis_index, height| match (genesis_index, height) {
+        (Some(0), 101) => Ok(Some(":hash-101:".to_string())),
         _ => Ok(None),
       });
 
-    client.expect_get_block_info().times(1).returning(|_| {
-      Ok(BlockInfo {
-        block_hash: ":hash-101:".to_string(),
-        block_height: 101,
-        finalized: true,
-        block_baker: Some(42),
-        block_slot_time: Utc::now(),
-      })
+    client.expect_get_block_info().times(2).returning(|hash| match hash {
+      ":hash-100:" => Ok(block_info(hash, 0, 100, 100)),
+      _ => Ok(block_info(hash, 0, 101, 101)),
     });
 
     client.expect_get_block_summary().times(1).returning(|_| {
       Ok(BlockSummary {
         special_events: vec![
-          Event {
-            tag: EVENT_TAG_REWARD.to_string(),
-            account: Some(":address-1:".to_string()),
-            baker_reward: Some(Decimal::from(25)),
-            transaction_fees: Some(Decimal::from(125)),
-            finalization_reward: None,
+          Event::PaydayAccountReward {
+            account: ":address-1:".to_string(),
+            baker_reward: Decimal::from(25),
+            transaction_fees: Decimal::from(125),
+            finalization_reward: Decimal::from(3),
           },
-          Event {
-            tag: EVENT_TAG_REWARD.to_string(),
-            account: Some(":address-2:".to_string()),
-            baker_reward: Some(Decimal::from(5)),
-            transaction_fees: Some(Decimal::from(12)),
-            finalization_reward: None,
+          Event::PaydayAccountReward {
+            account: ":address-2:".to_string(),
+            baker_reward: Decimal::from(5),
+            transaction_fees: Decimal::from(12),
+            finalization_reward: Decimal::ZERO,
           },
         ],
+        transaction_summaries: vec![transfer(":tx:", ":address-3:", ":address-4:", 100)],
       })
     });
 
@@ -299,7 +697,11 @@ mod tests {
       .times(1)
       .returning(|| Ok(Block::new(1, 100, ":hash-100:", 0, 42)));
 
-    block_repository.expect_store().times(1).returning(|_| Ok(()));
+    block_repository
+      .expect_store()
+      .withf(|block| block.height == 101 && block.genesis_index == 0 && block.protocol_version == 4)
+      .times(1)
+      .returning(|_| Ok(()));
 
     block_repository
       .expect_garbage_collect()
@@ -311,20 +713,335 @@ mod tests {
 
     account_repository
       .expect_get_accounts()
-      .withf(|filter| matches!(&filter.addresses, Some(a) if a.len() == 2))
+      .withf(|filter| matches!(&filter.addresses, Some(a) if a.len() == 4))
       .times(1)
       .returning(|_| {
         Ok(vec![
-          Account::new(1, ":address-1:", dec!(0), dec!(0), 0.0),
-          Account::new(2, ":address-3:", dec!(0), dec!(0), 0.0),
+          Account::new(1, ":address-1:", dec!(0), dec!(0), 0.0).with_staking(StakingKind::Baker, Some(42)),
+          Account::new(2, ":address-2:", dec!(0), dec!(0), 0.0).with_staking(StakingKind::Delegator, Some(42)),
+          Account::new(3, ":address-3:", dec!(0), dec!(0), 0.0),
         ])
       });
 
+    account_repository
+      .expect_set_reward()
+      .withf(|r| match r.kind {
+        RewardKind::Baker => r.account_id == 1 && r.amount.0 == dec!(25),
+        RewardKind::TransactionFee => r.account_id == 1 && r.amount.0 == dec!(125),
+        RewardKind::Finalization => r.account_id == 1 && r.amount.0 == dec!(3),
+        RewardKind::Delegation => r.account_id == 2 && r.amount.0 == dec!(17),
+      })
+      .times(4)
+      .returning(|_| Ok(()));
+
+    account_repository
+      .expect_set_transaction()
+      .withf(|t| {
+        t.account_id == 3
+          && t.transaction_hash == ":tx:"
+          && t.amount.0 == dec!(-100)
+          && t.counterparty.as_deref() == Some(":address-4:")
+          && t.cost.0 == dec!(2)
+      })
+      .times(1)
+      .returning(|_| Ok(()));
+
+    account_repository
+      .expect_set_for_update()
+      .withf(|addrs, pending| *pending && addrs.len() == 4)
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    let bus = Arc::new(EventBus::new());
+    let mut events = bus.subscribe();
+
+    let mut channel = MockChannel::new();
+
+    channel
+      .expect_send()
+      .withf(|n| match n.title.as_str() {
+        "reward for account `:address-1:`" => n.message == "153 CCD received in block :hash-101:",
+        "reward for account `:address-2:`" => n.message == "17 CCD received in block :hash-101:",
+        _ => false,
+      })
+      .times(2)
+      .returning(|_| Ok(()));
+
+    let job = BlockFetcher::new(
+      Arc::new(client),
+      Arc::new(block_repository),
+      Arc::new(account_repository),
+    )
+    .with_notifier(Arc::new(Notifier::new(vec![Arc::new(channel)])))
+    .with_events(bus.clone());
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+
+    let mut topics = vec![];
+    while let Ok(event) = events.try_recv() {
+      topics.push(match event {
+        DaemonEvent::RewardReceived {
+          address,
+          amount,
+          ..
+        } => format!("{}:{}", address, amount),
+        DaemonEvent::BlockIndexed {
+          height,
+          ..
+        } => format!("block:{}", height),
+        e => format!("{:?}", e),
+      });
+    }
+
+    topics.sort();
+
+    assert_eq!(vec![":address-1::153", ":address-2::17", "block:101"], topics);
+  }
+
+  #[tokio::test]
+  async fn test_block_fetcher_store_failure() {
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_block_info()
+      .times(1)
+      .returning(|hash| Ok(block_info(hash, 0, 101, 101)));
+
+    client.expect_get_block_summary().times(1).returning(|_| {
+      Ok(BlockSummary {
+        special_events: vec![Event::PaydayAccountReward {
+          account: ":address-1:".to_string(),
+          baker_reward: Decimal::from(25),
+          transaction_fees: Decimal::ZERO,
+          finalization_reward: Decimal::ZERO,
+        }],
+        transaction_summaries: vec![],
+      })
+    });
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_store()
+      .times(1)
+      .returning(|_| Err(RepositoryError::NotFound));
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository.expect_get_accounts().times(1).returning(|_| {
+      Ok(vec![
+        Account::new(1, ":address-1:", dec!(0), dec!(0), 0.0).with_staking(StakingKind::Baker, Some(42))
+      ])
+    });
+
     account_repository.expect_set_reward().times(2).returning(|_| Ok(()));
 
     account_repository
       .expect_set_for_update()
-      .withf(|addrs, pending| *pending && addrs.len() == 2)
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    let bus = Arc::new(EventBus::new());
+    let mut events = bus.subscribe();
+
+    // The reward is announced when the block is processed again.
+    let mut channel = MockChannel::new();
+
+    channel.expect_send().times(0);
+
+    let job = BlockFetcher::new(
+      Arc::new(client),
+      Arc::new(block_repository),
+      Arc::new(account_repository),
+    )
+    .with_notifier(Arc::new(Notifier::new(vec![Arc::new(channel)])))
+    .with_events(bus.clone());
+
+    let res = job.do_block(":hash-101:").await;
+
+    assert!(res.is_err());
+    assert!(events.try_recv().is_err());
+  }
+
+  #[tokio::test]
+  async fn test_block_fetcher_streaming() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(1).returning(|| {
+      Ok(crate::client::node::Block {
+        hash: ":hash-100:".to_string(),
+        height: 100,
+      })
+    });
+
+    client.expect_get_finalized_blocks().times(1).returning(|| {
+      let blocks = vec![
+        Ok(crate::client::node::Block {
+          hash: ":hash-100:".to_string(),
+          height: 100,
+        }),
+        Ok(crate::client::node::Block {
+          hash: ":hash-102:".to_string(),
+          height: 102,
+        }),
+      ];
+
+      Ok(Box::pin(tokio_stream::iter(blocks)))
+    });
+
+    // The missing block is caught up before processing the streamed one.
+    client
+      .expect_get_block_at_height()
+      .with(eq(Some(0)), eq(101))
+      .times(1)
+      .returning(|_, _| Ok(Some(":hash-101:".to_string())));
+
+    client
+      .expect_get_block_info()
+      .with(eq(":hash-100:"))
+      .times(2)
+      .returning(|hash| Ok(block_info(hash, 0, 100, 100)));
+
+    client
+      .expect_get_block_info()
+      .withf(|hash| hash == ":hash-101:" || hash == ":hash-102:")
+      .times(2)
+      .returning(|hash| {
+        let height = if hash == ":hash-101:" { 101 } else { 102 };
+
+        Ok(block_info(hash, 0, height, height))
+      });
+
+    client.expect_get_block_summary().times(2).returning(|_| {
+      Ok(BlockSummary {
+        special_events: vec![],
+        transaction_summaries: vec![],
+      })
+    });
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(2)
+      .returning(|| Ok(Block::new(1, 100, ":hash-100:", 0, 42)));
+
+    block_repository
+      .expect_store()
+      .withf(|block| block.height == 101 || block.height == 102)
+      .times(2)
+      .returning(|_| Ok(()));
+
+    block_repository
+      .expect_garbage_collect()
+      .with(eq(101i64 - GC_OFFSET))
+      .times(1)
+      .returning(|_| Ok(()));
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository
+      .expect_get_accounts()
+      .times(2)
+      .returning(|_| Ok(vec![]));
+
+    account_repository
+      .expect_set_for_update()
+      .times(2)
+      .returning(|_, _| Ok(()));
+
+    let job = BlockFetcher::new(
+      Arc::new(client),
+      Arc::new(block_repository),
+      Arc::new(account_repository),
+    )
+    .with_streaming(true);
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_block_fetcher_follow_window() {
+    let mut client = MockNodeClient::new();
+
+    // The node does not finalize any block during the window.
+    client
+      .expect_get_finalized_blocks()
+      .times(1)
+      .returning(|| Ok(Box::pin(tokio_stream::pending())));
+
+    let job = BlockFetcher::new(
+      Arc::new(client),
+      Arc::new(MockBlockRepository::new()),
+      Arc::new(MockAccountRepository::new()),
+    );
+
+    let res = job.follow(100, Duration::from_millis(10)).await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_block_fetcher_new_era() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(1).returning(|| {
+      Ok(crate::client::node::Block {
+        hash: ":hash-101:".to_string(),
+        height: 101,
+      })
+    });
+
+    // A protocol update ended the era after the block 100, and the next block
+    // is the genesis block of the new era.
+    client
+      .expect_get_block_at_height()
+      .times(2)
+      .returning(|genesis_index, height| match (genesis_index, height) {
+        (Some(1), 0) => Ok(Some(":hash-101:".to_string())),
+        _ => Ok(None),
+      });
+
+    client.expect_get_block_info().times(2).returning(|hash| match hash {
+      ":hash-100:" => Ok(block_info(hash, 0, 100, 60)),
+      _ => Ok(block_info(hash, 1, 101, 0)),
+    });
+
+    client.expect_get_block_summary().times(1).returning(|_| {
+      Ok(BlockSummary {
+        special_events: vec![],
+        transaction_summaries: vec![],
+      })
+    });
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .returning(|| Ok(Block::new(1, 100, ":hash-100:", 0, 42)));
+
+    block_repository
+      .expect_store()
+      .withf(|block| block.height == 101 && block.genesis_index == 1 && block.protocol_version == 5)
+      .times(1)
+      .returning(|_| Ok(()));
+
+    block_repository.expect_garbage_collect().times(1).returning(|_| Ok(()));
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository
+      .expect_get_accounts()
+      .times(1)
+      .returning(|_| Ok(vec![]));
+
+    account_repository
+      .expect_set_for_update()
       .times(1)
       .returning(|_, _| Ok(()));
 
@@ -339,6 +1056,136 @@ mod tests {
     assert!(matches!(res, Ok(_)));
   }
 
+  #[tokio::test]
+  async fn test_block_fetcher_start_height() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(1).returning(|| {
+      Ok(crate::client::node::Block {
+        hash: ":hash-200:".to_string(),
+        height: 200,
+      })
+    });
+
+    client
+      .expect_get_block_at_height()
+      .with(eq(None), eq(150))
+      .times(1)
+      .returning(|_, _| Ok(Some(":hash-150:".to_string())));
+
+    client
+      .expect_get_block_at_height()
+      .with(eq(Some(0)), eq(151))
+      .times(1)
+      .returning(|_, _| Ok(None));
+
+    client
+      .expect_get_block_at_height()
+      .with(eq(Some(1)), eq(0))
+      .times(1)
+      .returning(|_, _| Ok(None));
+
+    client
+      .expect_get_block_info()
+      .with(eq(":hash-150:"))
+      .times(2)
+      .returning(|hash| Ok(block_info(hash, 0, 150, 150)));
+
+    client.expect_get_block_summary().times(1).returning(|_| {
+      Ok(BlockSummary {
+        special_events: vec![],
+        transaction_summaries: vec![],
+      })
+    });
+
+    let mut block_repository = MockBlockRepository::new();
+    let mut seq = mockall::Sequence::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .in_sequence(&mut seq)
+      .returning(|| Err(RepositoryError::NotFound));
+
+    block_repository
+      .expect_store()
+      .withf(|block| block.height == 150)
+      .times(1)
+      .in_sequence(&mut seq)
+      .returning(|_| Ok(()));
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .in_sequence(&mut seq)
+      .returning(|| Ok(Block::new(1, 150, ":hash-150:", 0, 42)));
+
+    block_repository
+      .expect_garbage_collect()
+      .with(eq(151 - GC_OFFSET))
+      .times(1)
+      .returning(|_| Ok(()));
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository
+      .expect_get_accounts()
+      .times(1)
+      .returning(|_| Ok(vec![]));
+
+    account_repository
+      .expect_set_for_update()
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    let job = BlockFetcher::new(
+      Arc::new(client),
+      Arc::new(block_repository),
+      Arc::new(account_repository),
+    )
+    .with_start_height(Some(150));
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_block_fetcher_start_not_found() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(1).returning(|| {
+      Ok(crate::client::node::Block {
+        hash: ":hash-200:".to_string(),
+        height: 200,
+      })
+    });
+
+    // The indexing starts from the last finalized block by default.
+    client
+      .expect_get_block_at_height()
+      .with(eq(None), eq(200))
+      .times(1)
+      .returning(|_, _| Ok(None));
+
+    let mut block_repository = MockBlockRepository::new();
+
+    block_repository
+      .expect_get_last_block()
+      .times(1)
+      .returning(|| Err(RepositoryError::NotFound));
+
+    let job = BlockFetcher::new(
+      Arc::new(client),
+      Arc::new(block_repository),
+      Arc::new(MockAccountRepository::new()),
+    );
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Err(e) if e.to_string().contains("height `200`")));
+  }
+
   #[tokio::test]
   async fn test_reward_repairer_execute() {
     let mut client = MockNodeClient::new();
@@ -349,21 +1196,20 @@ mod tests {
       .times(1)
       .returning(|_| Ok(BlockSummary{
         special_events: vec![
-          Event {
-            tag: EVENT_TAG_REWARD.to_string(),
-            account: Some(":address:".to_string()),
-            baker_reward: Some(Decimal::from(2)),
-            transaction_fees: Some(Decimal::from(3)),
-            finalization_reward: None,
+          Event::PaydayAccountReward {
+            account: ":address:".to_string(),
+            baker_reward: Decimal::from(2),
+            transaction_fees: Decimal::from(3),
+            finalization_reward: Decimal::ZERO,
           },
-          Event {
-            tag: "another event".to_string(),
-            account: Some(":address:".to_string()),
-            baker_reward: None,
-            transaction_fees: None,
-            finalization_reward: None,
+          Event::PaydayPoolReward {
+            pool_owner: Some(1),
+            baker_reward: Decimal::from(20),
+            transaction_fees: Decimal::from(30),
+            finalization_reward: Decimal::ZERO,
           },
         ],
+        transaction_summaries: vec![],
       }));
 
     let mut repository = MockAccountRepository::new();
@@ -385,6 +1231,7 @@ mod tests {
       .withf(|r| match r.kind {
         RewardKind::Baker => r.amount.0 == dec!(2),
         RewardKind::TransactionFee => r.amount.0 == dec!(3),
+        RewardKind::Finalization | RewardKind::Delegation => false,
       })
       .times(2)
       .returning(|_| Ok(()));
@@ -395,4 +1242,134 @@ mod tests {
 
     assert!(matches!(res, Ok(_)));
   }
+
+  #[test]
+  fn test_collect_rewards() {
+    let events = vec![
+      Event::BakingRewards {
+        baker_rewards: vec![AccountAmount {
+          address: ":address-1:".to_string(),
+          amount: dec!(10),
+        }],
+        remainder: dec!(0),
+      },
+      Event::FinalizationRewards {
+        finalization_rewards: vec![
+          AccountAmount {
+            address: ":address-1:".to_string(),
+            amount: dec!(4),
+          },
+          AccountAmount {
+            address: ":address-2:".to_string(),
+            amount: dec!(6),
+          },
+        ],
+        remainder: dec!(1),
+      },
+      Event::PaydayAccountReward {
+        account: ":address-1:".to_string(),
+        transaction_fees: dec!(3),
+        baker_reward: dec!(2),
+        finalization_reward: dec!(1),
+      },
+      Event::BlockReward {
+        transaction_fees: dec!(8),
+        old_gas_account: dec!(2),
+        new_gas_account: dec!(2),
+        baker_reward: dec!(7),
+        foundation_charge: dec!(1),
+        baker: ":address-2:".to_string(),
+        foundation_account: ":foundation:".to_string(),
+      },
+      Event::Unknown,
+    ];
+
+    let rewards = collect_rewards(&events);
+
+    assert_eq!(2, rewards.len());
+    assert_eq!(
+      Some(&AccountRewards {
+        baker: dec!(12),
+        transaction_fees: dec!(3),
+        finalization: dec!(5),
+      }),
+      rewards.get(":address-1:")
+    );
+    assert_eq!(
+      Some(&AccountRewards {
+        baker: dec!(0),
+        transaction_fees: dec!(7),
+        finalization: dec!(6),
+      }),
+      rewards.get(":address-2:")
+    );
+  }
+
+  #[test]
+  fn test_collect_transactions() {
+    let mut rejected = transfer(":tx-2:", ":address-1:", ":address-2:", 0);
+    rejected.result = TransactionResult::Reject;
+    rejected.kind.contents = Some("configureBaker".to_string());
+
+    let mut from_contract = transfer(":tx-3:", ":address-1:", ":address-2:", 7);
+    from_contract.sender = None;
+    from_contract.result = TransactionResult::Success {
+      events: vec![TransactionEvent::Transferred {
+        amount: dec!(7),
+        from: Address::AddressContract(ContractAddress {
+          index: 1,
+          subindex: 0,
+        }),
+        to: Address::AddressAccount(":address-2:".to_string()),
+      }],
+    };
+
+    let summaries = vec![
+      transfer(":tx-1:", ":address-1:", ":address-2:", 100),
+      rejected,
+      from_contract,
+    ];
+
+    let transactions = collect_transactions(&summaries);
+
+    assert_eq!(2, transactions.len());
+    assert_eq!(
+      Some(&vec![
+        AccountTransaction {
+          hash: ":tx-1:".to_string(),
+          kind: "transfer".to_string(),
+          amount: dec!(-100),
+          counterparty: Some(":address-2:".to_string()),
+          cost: dec!(2),
+        },
+        AccountTransaction {
+          hash: ":tx-2:".to_string(),
+          kind: "configureBaker".to_string(),
+          amount: dec!(0),
+          counterparty: None,
+          cost: dec!(2),
+        },
+      ]),
+      transactions.get(":address-1:")
+    );
+    assert_eq!(
+      Some(&vec![
+        AccountTransaction {
+          hash: ":tx-1:".to_string(),
+          kind: "transfer".to_string(),
+          amount: dec!(100),
+          counterparty: Some(":address-1:".to_string()),
+          cost: dec!(0),
+        },
+        AccountTransaction {
+          hash: ":tx-3:".to_string(),
+          kind: "transfer".to_string(),
+          amount: dec!(7),
+          counterparty: Some("<1,0>".to_string()),
+          cost: dec!(0),
+        },
+      ]),
+      transactions.get(":address-2:")
+    );
+  }
 }
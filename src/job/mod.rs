@@ -1,13 +1,15 @@
 pub mod account;
+pub mod alert;
 pub mod block;
+pub mod pool;
 pub mod price;
 pub mod status;
 
 use cron::Schedule;
 use log::{error, info, warn};
-use std::collections::HashMap;
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::{watch, Barrier};
 use tokio::task::JoinHandle;
 use tokio::time;
@@ -22,15 +24,59 @@ pub trait AsyncJob: Sync + Send {
   async fn execute(&self) -> Status;
 }
 
+/// The statistics of the executions of a job since the daemon started. An
+/// execution interrupted by the shutdown is not counted.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct JobStats {
+  pub successes: u64,
+  pub failures: u64,
+  pub duration_secs: f64,
+  pub last_duration_secs: f64,
+}
+
+/// A registry of the statistics of the jobs executed by the scheduler.
+#[derive(Default)]
+pub struct JobMetrics {
+  stats: Mutex<BTreeMap<String, JobStats>>,
+}
+
+impl JobMetrics {
+  /// It records an execution of the job with its duration and its outcome.
+  pub fn record(&self, name: &str, duration: Duration, success: bool) {
+    let mut stats = self.stats.lock().unwrap();
+    let stats = stats.entry(name.to_string()).or_default();
+
+    if success {
+      stats.successes += 1;
+    } else {
+      stats.failures += 1;
+    }
+
+    stats.duration_secs += duration.as_secs_f64();
+    stats.last_duration_secs = duration.as_secs_f64();
+  }
+
+  /// It returns the statistics of the jobs ordered by name.
+  pub fn get_stats(&self) -> Vec<(String, JobStats)> {
+    let stats = self.stats.lock().unwrap();
+
+    stats.iter().map(|(name, s)| (name.clone(), s.clone())).collect()
+  }
+}
+
+pub type DynJobMetrics = Arc<JobMetrics>;
+
 struct Context {
   name: String,
   closed: watch::Receiver<bool>,
   barrier: Arc<Barrier>,
   schedule: Schedule,
+  metrics: DynJobMetrics,
 }
 
 pub struct Scheduler {
   jobs: HashMap<String, (Schedule, Box<dyn AsyncJob>)>,
+  metrics: DynJobMetrics,
 }
 
 impl Scheduler {
@@ -38,9 +84,16 @@ impl Scheduler {
   pub fn new() -> Self {
     Self {
       jobs: HashMap::new(),
+      metrics: Arc::new(JobMetrics::default()),
     }
   }
 
+  /// It defines the registry where the executions of the jobs are recorded.
+  pub fn with_metrics(mut self, metrics: DynJobMetrics) -> Self {
+    self.metrics = metrics;
+    self
+  }
+
   /// It registers a job given a name and a schedule.
   pub fn register(&mut self, name: &str, schedule: Schedule, job: Box<dyn AsyncJob>) {
     self.jobs.insert(name.to_string(), (schedule, job));
@@ -59,6 +112,7 @@ impl Scheduler {
         closed: closing.clone(),
         barrier: barrier.clone(),
         schedule: schedule,
+        metrics: self.metrics.clone(),
       };
 
       Self::schedule_job(ctx, job);
@@ -107,9 +161,28 @@ impl Scheduler {
 
         info!("job [{}] has started", ctx.name);
 
-        match job.execute().await {
-          Ok(_) => info!("job [{}] has finished", ctx.name),
-          Err(e) => error!("job [{}] has finished with an error: {}", ctx.name, e),
+        let started = Instant::now();
+
+        // A job can run for a long time, like when following a stream, so it is
+        // interrupted when the scheduler is closing.
+        let interrupted = tokio::select! {
+            res = job.execute() => {
+                match &res {
+                    Ok(_) => info!("job [{}] has finished", ctx.name),
+                    Err(e) => error!("job [{}] has finished with an error: {}", ctx.name, e),
+                }
+                ctx.metrics.record(&ctx.name, started.elapsed(), res.is_ok());
+                false
+            },
+            _ = closed.changed() => true,
+        };
+
+        if interrupted {
+          info!("job [{}] has been interrupted", ctx.name);
+
+          // Wait for all the jobs to finish.
+          ctx.barrier.wait().await;
+          return;
         }
       }
     });
@@ -185,4 +258,37 @@ mod tests {
 
     jobber.shutdown().await;
   }
+
+  #[test]
+  fn test_job_metrics() {
+    let metrics = JobMetrics::default();
+
+    metrics.record("job-b", Duration::from_millis(500), true);
+    metrics.record("job-a", Duration::from_millis(1500), true);
+    metrics.record("job-a", Duration::from_millis(250), false);
+
+    assert_eq!(
+      vec![
+        (
+          "job-a".to_string(),
+          JobStats {
+            successes: 1,
+            failures: 1,
+            duration_secs: 1.75,
+            last_duration_secs: 0.25,
+          }
+        ),
+        (
+          "job-b".to_string(),
+          JobStats {
+            successes: 1,
+            failures: 0,
+            duration_secs: 0.5,
+            last_duration_secs: 0.5,
+          }
+        ),
+      ],
+      metrics.get_stats()
+    );
+  }
 }
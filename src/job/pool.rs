@@ -0,0 +1,229 @@
+use super::{AsyncJob, Status};
+use crate::client::node::PoolStatus;
+use crate::client::DynNodeClient;
+use crate::model::StakingKind;
+use crate::repository::*;
+use chrono::Utc;
+use log::info;
+
+const MAX_NUM_SNAPSHOT: i64 = 100_000;
+
+/// A job to take a snapshot of the status of the pools of the followed
+/// accounts that are bakers.
+pub struct PoolRefresher {
+  client: DynNodeClient,
+  account_repository: DynAccountRepository,
+  pool_repository: DynPoolRepository,
+}
+
+impl PoolRefresher {
+  pub fn new(
+    client: DynNodeClient,
+    account_repository: DynAccountRepository,
+    pool_repository: DynPoolRepository,
+  ) -> Self {
+    Self {
+      client,
+      account_repository,
+      pool_repository,
+    }
+  }
+}
+
+#[async_trait]
+impl AsyncJob for PoolRefresher {
+  async fn execute(&self) -> Status {
+    let accounts = self.account_repository.get_accounts(AccountFilter::default()).await?;
+
+    // Get the latest block hash of the consensus to get the most up to date information.
+    let last_block = self.client.get_last_block().await?;
+
+    let epoch_ms = Utc::now().timestamp_millis();
+
+    for account in accounts {
+      // The baker ID is known when the account has been refreshed, otherwise
+      // it is looked up in the consensus.
+      let baker_id = match (account.get_staking(), account.get_pool_id()) {
+        (StakingKind::Baker, Some(baker_id)) => baker_id,
+        _ => match self.client.get_baker(&last_block.hash, account.get_address()).await? {
+          Some(baker) => baker.baker_id,
+          None => continue,
+        },
+      };
+
+      match self.client.get_pool_status(&last_block.hash, baker_id).await? {
+        Some(status) => self.pool_repository.store(new_pool(status, epoch_ms)).await?,
+        None => info!("no pool found for baker `{}`", baker_id),
+      }
+    }
+
+    self.pool_repository.garbage_collect(MAX_NUM_SNAPSHOT).await?;
+
+    Ok(())
+  }
+}
+
+/// It converts the status of a pool into a snapshot taken at the given time.
+fn new_pool(status: PoolStatus, epoch_ms: i64) -> NewPool {
+  let rates = status.pool_info.commission_rates;
+  let payday = status.current_payday_status;
+
+  NewPool {
+    baker_id: status.baker_id as i64,
+    epoch_ms,
+    open_status: status.pool_info.open_status.as_str().to_string(),
+    baking_commission: rates.baking_commission,
+    finalization_commission: rates.finalization_commission,
+    transaction_commission: rates.transaction_commission,
+    equity_capital: status.baker_equity_capital.into(),
+    delegated_capital: status.delegated_capital.into(),
+    delegated_capital_cap: status.delegated_capital_cap.into(),
+    blocks_baked: payday.as_ref().map(|p| p.blocks_baked as i64),
+    finalization_live: payday.as_ref().map(|p| p.finalization_live),
+    transaction_fees_earned: payday.as_ref().map(|p| p.transaction_fees_earned.into()),
+    effective_stake: payday.as_ref().map(|p| p.effective_stake.into()),
+    lottery_power: payday.as_ref().map(|p| p.lottery_power),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::client::node::{Baker, Block, CommissionRates, MockNodeClient, OpenStatus, PoolInfo};
+  use crate::model::Account;
+  use crate::repository::{MockAccountRepository, MockPoolRepository};
+  use mockall::predicate::*;
+  use rust_decimal::Decimal;
+  use rust_decimal_macros::dec;
+  use std::sync::Arc;
+
+  #[tokio::test]
+  async fn test_execute() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(1).returning(|| {
+      Ok(Block {
+        hash: ":hash:".to_string(),
+        height: 0,
+      })
+    });
+
+    client
+      .expect_get_baker()
+      .with(eq(":hash:"), always())
+      .times(2)
+      .returning(|_, address| match address {
+        ":address-1:" => Ok(Some(Baker {
+          baker_account: address.to_string(),
+          baker_id: 42,
+          baker_lottery_power: 0.1,
+        })),
+        _ => Ok(None),
+      });
+
+    client
+      .expect_get_pool_status()
+      .with(eq(":hash:"), eq(42))
+      .times(1)
+      .returning(|_, baker_id| {
+        Ok(Some(PoolStatus {
+          baker_id,
+          baker_address: ":address-1:".to_string(),
+          baker_equity_capital: Decimal::from(1000),
+          delegated_capital: Decimal::from(250),
+          delegated_capital_cap: Decimal::from(3000),
+          pool_info: PoolInfo {
+            open_status: OpenStatus::ClosedForNew,
+            commission_rates: CommissionRates {
+              transaction_commission: 0.1,
+              finalization_commission: 1.0,
+              baking_commission: 0.1,
+            },
+          },
+          current_payday_status: None,
+        }))
+      });
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository.expect_get_accounts().times(1).returning(|_| {
+      Ok(vec![
+        Account::new(1, ":address-1:", dec!(0), dec!(0), 0.0),
+        Account::new(2, ":address-2:", dec!(0), dec!(0), 0.0),
+      ])
+    });
+
+    let mut pool_repository = MockPoolRepository::new();
+
+    pool_repository
+      .expect_store()
+      .withf(|pool| {
+        pool.baker_id == 42
+          && pool.open_status == "closed_for_new"
+          && pool.delegated_capital.0 == dec!(250)
+          && pool.blocks_baked.is_none()
+      })
+      .times(1)
+      .returning(|_| Ok(()));
+
+    pool_repository
+      .expect_garbage_collect()
+      .with(eq(MAX_NUM_SNAPSHOT))
+      .times(1)
+      .returning(|_| Ok(()));
+
+    let job = PoolRefresher::new(
+      Arc::new(client),
+      Arc::new(account_repository),
+      Arc::new(pool_repository),
+    );
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[tokio::test]
+  async fn test_execute_known_baker() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_last_block().times(1).returning(|| {
+      Ok(Block {
+        hash: ":hash:".to_string(),
+        height: 0,
+      })
+    });
+
+    client.expect_get_baker().times(0);
+
+    client
+      .expect_get_pool_status()
+      .with(eq(":hash:"), eq(42))
+      .times(1)
+      .returning(|_, _| Ok(None));
+
+    let mut account_repository = MockAccountRepository::new();
+
+    account_repository.expect_get_accounts().times(1).returning(|_| {
+      Ok(vec![
+        Account::new(1, ":address-1:", dec!(0), dec!(0), 0.0).with_staking(StakingKind::Baker, Some(42))
+      ])
+    });
+
+    let mut pool_repository = MockPoolRepository::new();
+
+    pool_repository.expect_store().times(0);
+
+    pool_repository.expect_garbage_collect().times(1).returning(|_| Ok(()));
+
+    let job = PoolRefresher::new(
+      Arc::new(client),
+      Arc::new(account_repository),
+      Arc::new(pool_repository),
+    );
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+}
@@ -1,19 +1,29 @@
 use super::{AsyncJob, Status};
-use crate::client::BoxedPriceClient;
+use crate::client::DynPriceClient;
+use crate::event::{DynEventBus, Event, EventBus};
 use crate::repository::{DynPriceRepository, NewPrice, PairFilter};
+use std::sync::Arc;
 
 pub struct PriceRefresher {
-  client: BoxedPriceClient,
+  client: DynPriceClient,
   repository: DynPriceRepository,
+  events: DynEventBus,
 }
 
 impl PriceRefresher {
-  pub fn new(client: BoxedPriceClient, repository: DynPriceRepository) -> Self {
+  pub fn new(client: DynPriceClient, repository: DynPriceRepository) -> Self {
     Self {
       client,
       repository,
+      events: Arc::new(EventBus::new()),
     }
   }
+
+  /// It publishes the prices refreshed on the bus.
+  pub fn with_events(mut self, events: DynEventBus) -> Self {
+    self.events = events;
+    self
+  }
 }
 
 #[async_trait]
@@ -34,6 +44,14 @@ impl AsyncJob for PriceRefresher {
       };
 
       self.repository.set_price(new_price).await?;
+
+      self.events.publish(Event::PriceUpdated {
+        pair_id: price.pair.get_id(),
+        base: price.pair.get_base().to_string(),
+        quote: price.pair.get_quote().to_string(),
+        bid: price.bid,
+        ask: price.ask,
+      });
     }
 
     Ok(())
@@ -46,7 +64,6 @@ mod tests {
   use crate::client::bitfinex::{MockPriceClient, Price as ClientPrice};
   use crate::repository::MockPriceRepository;
   use mockall::predicate::*;
-  use std::sync::Arc;
 
   #[tokio::test]
   async fn test_execute() {
@@ -88,10 +105,23 @@ mod tests {
       .times(1)
       .returning(|_| Ok(()));
 
-    let job = PriceRefresher::new(Box::new(mock_client), Arc::new(mock_repository));
+    let bus = Arc::new(EventBus::new());
+    let mut events = bus.subscribe();
+
+    let job = PriceRefresher::new(Arc::new(mock_client), Arc::new(mock_repository)).with_events(bus);
 
     let res = job.execute().await;
 
     assert!(matches!(res, Ok(())));
+    assert_eq!(
+      Event::PriceUpdated {
+        pair_id: 1,
+        base: "CCD".to_string(),
+        quote: "USD".to_string(),
+        bid: 2.0,
+        ask: 0.5,
+      },
+      events.try_recv().unwrap()
+    );
   }
 }
@@ -1,21 +1,35 @@
 use super::{AsyncJob, Status};
+use crate::analysis::DAY_MS;
+use crate::client::node::{BakerCommittee, ConsensusStatus};
 use crate::client::{DynNodeClient, Error as ClientError};
-use crate::repository::{DynStatusRepository, NewStatus, NodeStatusJson, ResourceStatusJson};
-use chrono::Utc;
-use log::error;
+use crate::event::{DynEventBus, Event, EventBus};
+use crate::repository::{
+  ConsensusStatusJson, DynStatusRepository, NewPeer, NewStatus, NodeStatusJson, ResourceStatusJson,
+};
+use chrono::{DateTime, Utc};
+use log::{error, warn};
 use std::io;
+use std::sync::Arc;
 use std::time::Duration;
 use systemstat::{Platform, System};
 use tokio::time;
 
 const MAX_NUM_REPORT: i64 = 10_000;
 
+/// The duration for which a disconnected peer is kept.
+const PEER_RETENTION_MS: i64 = 7 * DAY_MS;
+
+/// The time after the last finalized block beyond which the node is considered
+/// lagging behind the chain.
+const MAX_SYNC_LAG_MS: i64 = 5 * 60 * 1000;
+
 /// A job to create a report of the status of the server and the blockchain
 /// node.
 pub struct StatusChecker {
   repository: DynStatusRepository,
   client: DynNodeClient,
   sleep_duration: Duration,
+  events: DynEventBus,
 }
 
 impl StatusChecker {
@@ -24,9 +38,16 @@ impl StatusChecker {
       repository,
       client,
       sleep_duration: Duration::from_millis(10_000),
+      events: Arc::new(EventBus::new()),
     }
   }
 
+  /// It publishes the status reports on the bus.
+  pub fn with_events(mut self, events: DynEventBus) -> Self {
+    self.events = events;
+    self
+  }
+
   /// It gathers resource usage for the system and return the result. The call
   /// will sleep for some time to gather the CPU load.
   async fn get_system_stats(&self) -> ResourceStatusJson {
@@ -74,41 +95,118 @@ impl StatusChecker {
   }
 
   /// It fetches the node for multiple statistics and build the JSON that will be
-  /// store for the node status.
-  async fn get_node_status(&self) -> Result<NodeStatusJson, ClientError> {
+  /// store for the node status, alongside the statistics of its peers. The
+  /// consensus is left out when its status cannot be fetched, as the node is
+  /// reachable anyway.
+  async fn get_node_status(&self) -> Result<(NodeStatusJson, Vec<NewPeer>), ClientError> {
     let node_info = self.client.get_node_info().await?;
 
     let uptime = self.client.get_node_uptime().await?;
 
     let node_stats = self.client.get_node_stats().await?;
 
-    Ok(NodeStatusJson {
+    let consensus = match self.client.get_consensus_status().await {
+      Ok(consensus) => Some(consensus_json(&consensus, Utc::now())),
+      Err(e) => {
+        warn!("unable to gather the consensus status: {}", e);
+        None
+      }
+    };
+
+    let metrics = self.client.get_metrics();
+
+    let peers = node_stats
+      .peers
+      .iter()
+      .map(|peer| NewPeer {
+        node_id: peer.node_id.clone(),
+        packets_sent: peer.packets_sent as i64,
+        packets_received: peer.packets_received as i64,
+        latency: peer.latency as i64,
+      })
+      .collect();
+
+    let status = NodeStatusJson {
       node_id: node_info.node_id,
       baker_id: node_info.baker_id,
-      is_baker_committee: node_info.is_baker_committee,
+      is_baker_committee: node_info.baker_committee == BakerCommittee::Active,
       is_finalizer_committee: node_info.is_finalizer_committee,
       uptime_ms: uptime,
       peer_type: node_info.peer_type,
       peer_average_latency: node_stats.avg_latency,
       peer_count: node_stats.peer_count,
-    })
+      endpoint: Some(self.client.get_endpoint()),
+      client_retries: metrics.retries,
+      cache_hits: metrics.cache_hits,
+      cache_misses: metrics.cache_misses,
+      baker_committee: Some(node_info.baker_committee.as_str().to_string()),
+      consensus_baker_running: Some(node_info.consensus_baker_running),
+      consensus_running: Some(node_info.consensus_running),
+      consensus_type: Some(node_info.consensus_type),
+      consensus,
+    };
+
+    Ok((status, peers))
+  }
+}
+
+/// It builds the JSON of the consensus status at the given time. The node is
+/// considered lagging when it does not know any finalized block.
+fn consensus_json(consensus: &ConsensusStatus, now: DateTime<Utc>) -> ConsensusStatusJson {
+  let last_finalized_ms = consensus.last_finalized_time.map(|t| t.timestamp_millis());
+  let sync_lag_ms = last_finalized_ms.map(|t| (now.timestamp_millis() - t).max(0));
+
+  ConsensusStatusJson {
+    best_block_height: consensus.best_block_height,
+    last_finalized_block_height: consensus.last_finalized_block_height,
+    last_finalized_ms,
+    epoch: consensus.get_epoch(now),
+    slot_duration_ms: consensus.slot_duration_ms,
+    protocol_version: consensus.protocol_version,
+    finalization_lag: consensus.best_block_height - consensus.last_finalized_block_height,
+    sync_lag_ms,
+    is_lagging: sync_lag_ms.map(|lag| lag > MAX_SYNC_LAG_MS).unwrap_or(true),
   }
 }
 
 #[async_trait]
 impl AsyncJob for StatusChecker {
   async fn execute(&self) -> Status {
+    let resources = self.get_system_stats().await;
+    let now_ms = Utc::now().timestamp_millis();
+
+    let node = match self.get_node_status().await {
+      Ok((node, peers)) => {
+        self.repository.set_peers(peers, now_ms).await?;
+        Some(node)
+      }
+      Err(e) => {
+        error!("unable to gather the status of the node: {}", e);
+        None
+      }
+    };
+
+    let consensus = node.as_ref().and_then(|node| node.consensus.as_ref());
+
+    let event = Event::StatusReported {
+      timestamp_ms: now_ms,
+      peer_count: node.as_ref().map(|node| node.peer_count),
+      best_block_height: consensus.map(|c| c.best_block_height),
+      is_lagging: consensus.map(|c| c.is_lagging),
+    };
+
     let new_status = NewStatus {
-      resources: self.get_system_stats().await,
-      node: match self.get_node_status().await {
-        Ok(n) => Some(n),
-        Err(_) => None,
-      },
-      timestamp_ms: Utc::now().timestamp_millis(),
+      resources,
+      node,
+      timestamp_ms: now_ms,
     };
 
     self.repository.report(new_status).await?;
 
+    self.events.publish(event);
+
+    self.repository.delete_peers(now_ms - PEER_RETENTION_MS).await?;
+
     // Keep only the most recent reports to avoid filling up the storage
     // indefinitely.
     self.repository.garbage_collect(MAX_NUM_REPORT).await?;
@@ -120,10 +218,23 @@ impl AsyncJob for StatusChecker {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::client::node::{MockNodeClient, NodeInfo, NodeStats};
+  use crate::client::node::{MockNodeClient, NodeInfo, NodeStats, PeerStats};
+  use crate::client::ClientMetrics;
   use crate::repository::MockStatusRepository;
+  use chrono::TimeZone;
   use mockall::predicate::*;
-  use std::sync::Arc;
+
+  fn consensus_status(last_finalized_time: Option<DateTime<Utc>>) -> ConsensusStatus {
+    ConsensusStatus {
+      best_block_height: 125,
+      last_finalized_block_height: 123,
+      last_finalized_time,
+      era_genesis_time: Some(Utc.timestamp_millis_opt(0).unwrap()),
+      slot_duration_ms: 250,
+      epoch_duration_ms: 1000,
+      protocol_version: 4,
+    }
+  }
 
   #[tokio::test]
   async fn test_execute() {
@@ -133,38 +244,86 @@ mod tests {
       Ok(NodeInfo {
         node_id: None,
         baker_id: None,
-        is_baker_committee: true,
+        baker_committee: BakerCommittee::AddedButWrongKeys,
         is_finalizer_committee: false,
         peer_type: "Node".to_string(),
+        consensus_baker_running: true,
+        consensus_running: true,
+        consensus_type: "Active".to_string(),
       })
     });
 
     client.expect_get_node_uptime().times(1).returning(|| Ok(250));
 
+    client
+      .expect_get_consensus_status()
+      .times(1)
+      .returning(|| Ok(consensus_status(Some(Utc::now()))));
+
+    client
+      .expect_get_endpoint()
+      .times(1)
+      .returning(|| "http://127.0.0.1:10000/".to_string());
+
+    client.expect_get_metrics().times(1).returning(|| ClientMetrics {
+      retries: 3,
+      cache_hits: 5,
+      cache_misses: 1,
+    });
+
     client.expect_get_node_stats().times(1).returning(|| {
-      Ok(NodeStats {
-        avg_latency: 125.75,
-        avg_bps_in: 0,
-        avg_bps_out: 0,
-        peer_count: 6,
-      })
+      let peers = vec![PeerStats {
+        node_id: "peer-1".to_string(),
+        packets_sent: 10,
+        packets_received: 20,
+        latency: 125,
+      }];
+
+      Ok(NodeStats::new(peers, 0, 0))
     });
 
     let mut repository = MockStatusRepository::new();
 
     repository
       .expect_report()
-      .withf(|status| !status.node.is_none())
+      .withf(|status| {
+        matches!(&status.node, Some(node) if node.endpoint.is_some()
+          && node.client_retries == 3
+          && node.cache_hits == 5
+          && !node.is_baker_committee
+          && node.baker_committee.as_deref() == Some("added_but_wrong_keys")
+          && node.consensus_type.as_deref() == Some("Active")
+          && matches!(&node.consensus, Some(c) if c.finalization_lag == 2 && !c.is_lagging))
+      })
       .times(1)
       .returning(|_| Ok(()));
 
+    repository
+      .expect_set_peers()
+      .withf(|peers, _| {
+        peers
+          == &vec![NewPeer {
+            node_id: "peer-1".to_string(),
+            packets_sent: 10,
+            packets_received: 20,
+            latency: 125,
+          }]
+      })
+      .times(1)
+      .returning(|_, _| Ok(()));
+
+    repository.expect_delete_peers().times(1).returning(|_| Ok(()));
+
     repository
       .expect_garbage_collect()
       .with(eq(MAX_NUM_REPORT))
       .times(1)
       .returning(|_| Ok(()));
 
-    let mut job = StatusChecker::new(Arc::new(repository), Arc::new(client));
+    let bus = Arc::new(EventBus::new());
+    let mut events = bus.subscribe();
+
+    let mut job = StatusChecker::new(Arc::new(repository), Arc::new(client)).with_events(bus);
 
     // Sleep only for a short amount of time for the test.
     job.sleep_duration = Duration::from_millis(1);
@@ -172,5 +331,91 @@ mod tests {
     let res = job.execute().await;
 
     assert!(matches!(res, Ok(_)));
+    assert!(matches!(
+      events.try_recv(),
+      Ok(Event::StatusReported {
+        peer_count: Some(1),
+        best_block_height: Some(125),
+        is_lagging: Some(false),
+        ..
+      })
+    ));
+  }
+
+  #[tokio::test]
+  async fn test_execute_without_consensus() {
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_node_info().times(1).returning(|| {
+      Ok(NodeInfo {
+        node_id: None,
+        baker_id: None,
+        baker_committee: BakerCommittee::NotInCommittee,
+        is_finalizer_committee: false,
+        peer_type: "Node".to_string(),
+        consensus_baker_running: false,
+        consensus_running: true,
+        consensus_type: "Passive".to_string(),
+      })
+    });
+
+    client.expect_get_node_uptime().times(1).returning(|| Ok(250));
+
+    client
+      .expect_get_node_stats()
+      .times(1)
+      .returning(|| Ok(NodeStats::new(vec![], 0, 0)));
+
+    client
+      .expect_get_consensus_status()
+      .times(1)
+      .returning(|| Err(ClientError::Decode("consensus".to_string())));
+
+    client
+      .expect_get_endpoint()
+      .returning(|| "http://127.0.0.1:10000/".to_string());
+
+    client.expect_get_metrics().returning(ClientMetrics::default);
+
+    let mut repository = MockStatusRepository::new();
+
+    repository
+      .expect_report()
+      .withf(|status| matches!(&status.node, Some(node) if node.consensus.is_none()))
+      .times(1)
+      .returning(|_| Ok(()));
+
+    repository.expect_set_peers().times(1).returning(|_, _| Ok(()));
+    repository.expect_delete_peers().times(1).returning(|_| Ok(()));
+    repository.expect_garbage_collect().times(1).returning(|_| Ok(()));
+
+    let mut job = StatusChecker::new(Arc::new(repository), Arc::new(client));
+
+    job.sleep_duration = Duration::from_millis(1);
+
+    let res = job.execute().await;
+
+    assert!(matches!(res, Ok(_)));
+  }
+
+  #[test]
+  fn test_consensus_json() {
+    let now = Utc.timestamp_millis_opt(MAX_SYNC_LAG_MS + 5000).unwrap();
+
+    let res = consensus_json(&consensus_status(Some(Utc.timestamp_millis_opt(4000).unwrap())), now);
+
+    assert_eq!(Some(MAX_SYNC_LAG_MS + 1000), res.sync_lag_ms);
+    assert_eq!(Some(MAX_SYNC_LAG_MS as u64 / 1000 + 5), res.epoch);
+    assert!(res.is_lagging);
+
+    let res = consensus_json(&consensus_status(Some(now)), now);
+
+    assert_eq!(Some(0), res.sync_lag_ms);
+    assert!(!res.is_lagging);
+
+    let res = consensus_json(&consensus_status(None), now);
+
+    assert_eq!(None, res.sync_lag_ms);
+    assert!(res.is_lagging);
   }
 }
@@ -1,23 +1,30 @@
+mod analysis;
 mod authentication;
 mod client;
 mod config;
 mod controller;
+mod event;
 mod job;
+mod metrics;
 mod model;
+mod notification;
 mod repository;
 mod schema;
 
-use crate::client::bitfinex;
-use crate::config::Config;
-use crate::job::Jobber;
+use crate::client::cache::CachedClient;
+use crate::client::{DynNodeClient, DynPriceClient};
+use crate::config::{Config, NetworkCfg};
+use crate::event::{DynEventBus, EventBus};
+use crate::job::{DynJobMetrics, JobMetrics, Jobber};
 use crate::repository::*;
 use axum::{
   extract::Extension,
-  routing::{get, post},
+  routing::{delete, get, post},
   Router,
 };
 use clap::Parser;
 use env_logger::Env;
+use log::warn;
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -62,10 +69,16 @@ struct Dependencies {
   args: Args,
   cfg: Config,
   account: DynAccountRepository,
+  alerts: DynAlertRepository,
   price: DynPriceRepository,
   block: DynBlockRepository,
+  block_cache: DynBlockCacheRepository,
+  pools: DynPoolRepository,
   status: DynStatusRepository,
   user: DynUserRepository,
+  events: DynEventBus,
+  job_metrics: DynJobMetrics,
+  price_client: DynPriceClient,
 }
 
 impl Dependencies {
@@ -76,14 +89,24 @@ impl Dependencies {
     // storage.
     pool.run_migrations().await?;
 
+    // The price client is shared by the job and the metrics to report its
+    // retries.
+    let price_client = cfg.make_price_client();
+
     Ok(Dependencies {
       args,
       cfg,
       account: Arc::new(SqliteAccountRepository::new(pool.clone())),
+      alerts: Arc::new(SqliteAlertRepository::new(pool.clone())),
       price: Arc::new(SqlitePriceRepository::new(pool.clone())),
       block: Arc::new(SqliteBlockRepository::new(pool.clone())),
+      block_cache: Arc::new(SqliteBlockCacheRepository::new(pool.clone())),
+      pools: Arc::new(SqlitePoolRepository::new(pool.clone())),
       status: Arc::new(SqliteStatusRepository::new(pool.clone())),
       user: Arc::new(SqliteUserRepository::new(pool.clone())),
+      events: Arc::new(EventBus::new()),
+      job_metrics: Arc::new(JobMetrics::default()),
+      price_client,
     })
   }
 }
@@ -116,46 +139,109 @@ async fn main() -> Result<()> {
   Ok(())
 }
 
-async fn prepare_jobs(deps: &Dependencies) -> Jobber {
-  let mut scheduler = job::Scheduler::new();
+async fn prepare_jobs(deps: &Dependencies) -> Result<Jobber> {
+  let mut scheduler = job::Scheduler::new().with_metrics(deps.job_metrics.clone());
+
+  let mut node_client = deps.cfg.make_client()?;
+
+  if let Some(cfg) = deps.cfg.get_block_cache() {
+    let repository = cfg.persistent.then(|| deps.block_cache.clone());
+
+    node_client = CachedClient::new(node_client, cfg.capacity, repository, cfg.disk_capacity);
+  }
+
+  let network = deps.cfg.get_network();
+
+  check_network(&network, &node_client).await?;
+
+  let snapshots = deps.cfg.get_account_snapshots();
+
+  let notifier = Arc::new(deps.cfg.make_notifier()?);
 
-  let price_client = Box::new(bitfinex::BitfinexClient::default());
+  // The alerting rules are evaluated after each run of a job.
+  let engine = Arc::new(
+    job::alert::AlertEngine::new(
+      deps.cfg.get_alerts().rules,
+      deps.alerts.clone(),
+      deps.status.clone(),
+      deps.account.clone(),
+    )
+    .with_notifier(notifier.clone()),
+  );
 
-  let node_client = deps.cfg.make_client();
+  if let Some(cfg) = deps.cfg.get_digests() {
+    notification::digest::DigestScheduler::new(deps.price.clone(), notifier.clone())
+      .with_quote(cfg.quote.clone())
+      .with_periods(cfg.payday, cfg.daily)
+      .spawn(deps.events.subscribe());
+  }
 
   for (name, schedule_str) in deps.cfg.get_jobs().unwrap_or(&HashMap::new()) {
     let schedule = cron::Schedule::from_str(&schedule_str).unwrap();
 
+    let job: Box<dyn job::AsyncJob> = match name {
+      config::Job::AccountsRefresher => Box::new(
+        job::account::RefreshAccountsJob::new(node_client.clone(), deps.account.clone())
+          .with_retention(snapshots.raw_days, snapshots.retention_days)
+          .with_events(deps.events.clone()),
+      ),
+      config::Job::PriceRefresher => Box::new(
+        job::price::PriceRefresher::new(deps.price_client.clone(), deps.price.clone()).with_events(deps.events.clone()),
+      ),
+      config::Job::BlockFetcher => Box::new(
+        job::block::BlockFetcher::new(node_client.clone(), deps.block.clone(), deps.account.clone())
+          .with_streaming(deps.cfg.get_block_fetcher().streaming)
+          .with_start_height(network.get_start_height())
+          .with_notifier(notifier.clone())
+          .with_events(deps.events.clone()),
+      ),
+      config::Job::StatusChecker => Box::new(
+        job::status::StatusChecker::new(deps.status.clone(), node_client.clone()).with_events(deps.events.clone()),
+      ),
+      config::Job::RewardRepairer => Box::new(job::block::RewardRepairer::new(
+        node_client.clone(),
+        deps.account.clone(),
+      )),
+      config::Job::PoolRefresher => Box::new(job::pool::PoolRefresher::new(
+        node_client.clone(),
+        deps.account.clone(),
+        deps.pools.clone(),
+      )),
+    };
+
     scheduler.register(
       name.as_str(),
       schedule,
-      match name {
-        config::Job::AccountsRefresher => Box::new(job::account::RefreshAccountsJob::new(
-          node_client.clone(),
-          deps.account.clone(),
-        )),
-        config::Job::PriceRefresher => Box::new(job::price::PriceRefresher::new(
-          price_client.clone(),
-          deps.price.clone(),
-        )),
-        config::Job::BlockFetcher => Box::new(job::block::BlockFetcher::new(
-          node_client.clone(),
-          deps.block.clone(),
-          deps.account.clone(),
-        )),
-        config::Job::StatusChecker => Box::new(job::status::StatusChecker::new(
-          deps.status.clone(),
-          node_client.clone(),
-        )),
-        config::Job::RewardRepairer => Box::new(job::block::RewardRepairer::new(
-          node_client.clone(),
-          deps.account.clone(),
-        )),
-      },
+      Box::new(job::alert::AlertedJob::new(job, engine.clone())),
     );
   }
 
-  scheduler.start()
+  Ok(scheduler.start())
+}
+
+/// It makes sure that the node follows the network of the configuration. The
+/// check is skipped when the node cannot be reached so that the daemon can
+/// start before it.
+async fn check_network(network: &NetworkCfg, client: &DynNodeClient) -> Result<()> {
+  let expected = match network.get_genesis_hash() {
+    Some(hash) => hash,
+    None => return Ok(()),
+  };
+
+  match client.get_genesis_hash().await {
+    Ok(hash) if hash != expected => Err(
+      format!(
+        "the node follows the network with genesis `{}` instead of `{}`",
+        hash, expected
+      )
+      .into(),
+    ),
+    Ok(_) => Ok(()),
+    Err(e) => {
+      warn!("unable to check the network of the node: {}", e);
+      Ok(())
+    }
+  }
 }
 
 /// It creates an application and registers the different routes.
@@ -168,6 +254,7 @@ async fn create_app(deps: &Dependencies) -> Result<Router> {
   Ok(
     Router::new()
       .route("/", get(controller::get_status))
+      .route("/node/peers", get(controller::get_peers))
       .route("/auth/authorize", post(controller::auth::authorize))
       .route("/auth/token", post(controller::auth::refresh_token))
       .route("/users", post(controller::auth::create_user))
@@ -175,15 +262,42 @@ async fn create_app(deps: &Dependencies) -> Result<Router> {
       .route("/accounts", get(controller::get_accounts))
       .route("/accounts/:account_id", get(controller::get_account))
       .route("/accounts/:account_id/rewards", get(controller::get_account_rewards))
+      .route(
+        "/accounts/:account_id/transactions",
+        get(controller::get_account_transactions),
+      )
+      .route("/accounts/:account_id/history", get(controller::get_account_history))
+      .route(
+        "/accounts/:account_id/performance",
+        get(controller::get_account_performance),
+      )
       .route("/pairs", post(controller::create_pair))
       .route("/pairs", get(controller::get_pairs))
       .route("/pairs/:pair_id/price", get(controller::get_price))
       .route("/blocks", get(controller::get_blocks))
+      .route("/pools/:baker_id", get(controller::get_pool))
+      .route("/pools/:baker_id/history", get(controller::get_pool_history))
+      .route("/alerts", get(controller::get_alerts))
+      .route("/alerts/:alert_id/ack", post(controller::ack_alert))
+      .route("/silences", get(controller::get_silences))
+      .route("/silences", post(controller::create_silence))
+      .route("/silences/:silence_id", delete(controller::delete_silence))
+      .route("/events", get(controller::event::get_events))
+      .route("/metrics", get(controller::metrics::get_metrics))
+      .route("/healthz", get(controller::health::get_liveness))
+      .route("/readyz", get(controller::health::get_readiness))
       .layer(Extension(deps.account.clone()))
+      .layer(Extension(deps.alerts.clone()))
       .layer(Extension(deps.price.clone()))
       .layer(Extension(deps.block.clone()))
+      .layer(Extension(deps.pools.clone()))
       .layer(Extension(deps.status.clone()))
       .layer(Extension(deps.user.clone()))
+      .layer(Extension(deps.events.clone()))
+      .layer(Extension(deps.job_metrics.clone()))
+      .layer(Extension(deps.price_client.clone()))
+      .layer(Extension(Arc::new(deps.cfg.get_metrics())))
+      .layer(Extension(Arc::new(deps.cfg.get_readiness())))
       .layer(Extension(Arc::new(encoding_key)))
       .layer(Extension(Arc::new(decoding_key))),
   )
@@ -192,7 +306,7 @@ async fn create_app(deps: &Dependencies) -> Result<Router> {
 /// It schedules the different jobs from the configuration and start the server.
 /// The binding address is defined by the configuration.
 async fn run_server(deps: &Dependencies, termination: impl std::future::Future<Output = ()>) -> Result<()> {
-  let jobber = prepare_jobs(deps).await;
+  let jobber = prepare_jobs(deps).await?;
 
   axum::Server::bind(deps.cfg.get_listen_addr())
     .serve(create_app(deps).await?.into_make_service())
@@ -210,6 +324,7 @@ async fn run_server(deps: &Dependencies, termination: impl std::future::Future<O
 mod tests {
   use super::*;
   use crate::authentication::Claims;
+  use crate::client::node::MockNodeClient;
   use axum::{
     body::Body,
     http::{Request, StatusCode},
@@ -236,6 +351,16 @@ mod tests {
       "jobs:\n",
       "  accounts_refresher: \"* * * * * * 1970\"\n",
       "  price_refresher: \"* * * * * * 1970\"\n",
+      "  pool_refresher: \"* * * * * * 1970\"\n",
+      "alerts:\n",
+      "  rules:\n",
+      "    - name: node-unreachable\n",
+      "      kind: node_unreachable\n",
+      "    - name: low-peer-count\n",
+      "      kind: peer_count_below\n",
+      "      min: 3\n",
+      "digests:\n",
+      "  quote: USD\n",
       "pairs:\n",
       "  - [\"BTC\", \"USD\"]\n"
     );
@@ -249,11 +374,64 @@ mod tests {
       .await
       .unwrap();
 
-    let jobber = prepare_jobs(&deps).await;
+    let jobber = prepare_jobs(&deps).await.unwrap();
 
     jobber.shutdown().await;
   }
 
+  /// It makes sure that the network can be configured and checked against the
+  /// node.
+  #[tokio::test]
+  async fn test_check_network() {
+    let values = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "network:\n",
+      "  profile: testnet\n",
+      "  start_height: 42\n",
+    );
+
+    let mut values = values.as_bytes();
+
+    let network = Config::from_reader(&mut values).unwrap().get_network();
+
+    assert_eq!(Some(42), network.get_start_height());
+
+    let mut client = MockNodeClient::new();
+
+    client
+      .expect_get_genesis_hash()
+      .times(2)
+      .returning(|| Ok("4221332d34e1694168c2a0c0b3fd0f273809612cb13d000d5c2e00e85f50f796".to_string()));
+
+    let client: DynNodeClient = Arc::new(client);
+
+    assert!(matches!(check_network(&network, &client).await, Ok(_)));
+
+    // The node of the testnet does not match the default profile.
+    let network = Config::default().get_network();
+
+    assert!(matches!(check_network(&network, &client).await, Err(_)));
+  }
+
+  /// It makes sure that the network is not checked when the genesis of a
+  /// custom network is unknown.
+  #[tokio::test]
+  async fn test_check_network_custom() {
+    let values = concat!("listen_address: 127.0.0.1:0\n", "network:\n", "  profile: custom\n",);
+
+    let mut values = values.as_bytes();
+
+    let network = Config::from_reader(&mut values).unwrap().get_network();
+
+    let mut client = MockNodeClient::new();
+
+    client.expect_get_genesis_hash().times(0);
+
+    let client: DynNodeClient = Arc::new(client);
+
+    assert!(matches!(check_network(&network, &client).await, Ok(_)));
+  }
+
   /// It makes sure the server can bind the address.
   #[tokio::test(flavor = "multi_thread")]
   async fn test_run_server() {
@@ -279,6 +457,117 @@ mod tests {
     run_server(&deps, async {}).await.unwrap();
   }
 
+  /// It makes sure that a list of node endpoints can be configured.
+  #[tokio::test]
+  async fn test_make_client_with_endpoints() {
+    let values = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "client:\n",
+      "  - uri: \"http://127.0.0.1:10000\"\n",
+      "    token: \"rpcadmin\"\n",
+      "  - uri: \"http://127.0.0.1:20000\"\n",
+      "    token: \"rpcadmin\"\n",
+      "    api: v2\n",
+    );
+
+    let mut values = values.as_bytes();
+
+    let cfg = Config::from_reader(&mut values).unwrap();
+
+    let client = cfg.make_client().unwrap();
+
+    assert_eq!("http://127.0.0.1:10000/", client.get_endpoint());
+  }
+
+  /// It makes sure that the streaming of the blocks is refused with the legacy
+  /// API.
+  #[test]
+  fn test_block_fetcher_streaming() {
+    let legacy = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "block_fetcher:\n",
+      "  streaming: true\n",
+    );
+
+    assert!(Config::from_reader(&mut legacy.as_bytes()).is_err());
+
+    let v2 = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "client:\n",
+      "  uri: \"http://127.0.0.1:20000\"\n",
+      "  token: \"rpcadmin\"\n",
+      "  api: v2\n",
+      "block_fetcher:\n",
+      "  streaming: true\n",
+    );
+
+    let cfg = Config::from_reader(&mut v2.as_bytes()).unwrap();
+
+    assert!(cfg.get_block_fetcher().streaming);
+  }
+
+  /// It makes sure that the retry policy of the clients can be configured.
+  #[tokio::test]
+  async fn test_make_client_with_retry() {
+    let values = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "client:\n",
+      "  uri: \"http://127.0.0.1:10000\"\n",
+      "  token: \"rpcadmin\"\n",
+      "  retry:\n",
+      "    max_retries: 5\n",
+      "    deadline_ms: 10000\n",
+      "price_client:\n",
+      "  retry: {}\n",
+    );
+
+    let mut values = values.as_bytes();
+
+    let cfg = Config::from_reader(&mut values).unwrap();
+
+    let client = cfg.make_client().unwrap();
+
+    assert_eq!("http://127.0.0.1:10000/", client.get_endpoint());
+    assert_eq!(0, client.get_metrics().retries);
+
+    assert_eq!(0, cfg.make_price_client().get_metrics().retries);
+  }
+
+  /// It makes sure that the notification channels can be configured.
+  #[tokio::test]
+  async fn test_make_notifier() {
+    let values = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "notifications:\n",
+      "  webhooks:\n",
+      "    - url: \"http://127.0.0.1:9000/hook\"\n",
+      "  emails:\n",
+      "    - host: \"smtp.example.com\"\n",
+      "      from: \"bakerd@example.com\"\n",
+      "      recipients: [\"ops@example.com\"]\n",
+      "      kinds: [alert_firing]\n",
+    );
+
+    let mut values = values.as_bytes();
+
+    let cfg = Config::from_reader(&mut values).unwrap();
+
+    assert!(matches!(cfg.make_notifier(), Ok(_)));
+
+    let values = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "notifications:\n",
+      "  emails:\n",
+      "    - host: \"smtp.example.com\"\n",
+      "      from: \"bakerd@example.com\"\n",
+      "      recipients: [\"not an address\"]\n",
+    );
+
+    let mut values = values.as_bytes();
+
+    assert!(matches!(Config::from_reader(&mut values), Err(_)));
+  }
+
   #[tokio::test(flavor = "multi_thread")]
   async fn test_get_account() {
     let mut secret_file = env::temp_dir();
@@ -313,4 +602,80 @@ mod tests {
 
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
   }
+
+  /// It makes sure that the metrics are exported only to the scrapers with
+  /// the token of the configuration.
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_get_metrics() {
+    let values = concat!(
+      "listen_address: 127.0.0.1:0\n",
+      "metrics:\n",
+      "  enabled: true\n",
+      "  token: \":token:\"\n",
+    );
+
+    let mut args = Args::default();
+    args.data_dir = ":memory:".into();
+
+    let deps = Dependencies::make(args, Config::from_reader(&mut values.as_bytes()).unwrap())
+      .await
+      .unwrap();
+
+    let request = |token: &str| {
+      Request::builder()
+        .uri("/metrics")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+    };
+
+    let response = create_app(&deps)
+      .await
+      .unwrap()
+      .oneshot(request(":token:"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = create_app(&deps)
+      .await
+      .unwrap()
+      .oneshot(request(":other:"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+  }
+
+  /// It makes sure that the health of the daemon can be probed without a
+  /// token.
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_health() {
+    let mut args = Args::default();
+    args.data_dir = ":memory:".into();
+
+    let deps = Dependencies::make(args, Config::default()).await.unwrap();
+
+    let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = create_app(&deps)
+      .await
+      .unwrap()
+      .oneshot(request("/healthz"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Nothing has been reported nor indexed yet.
+    let response = create_app(&deps)
+      .await
+      .unwrap()
+      .oneshot(request("/readyz"))
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+  }
 }
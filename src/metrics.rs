@@ -0,0 +1,121 @@
+use std::fmt::Write;
+
+/// Content type of the text format of Prometheus.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Prefix of the names of the metrics of the daemon.
+const PREFIX: &str = "bakerd";
+
+/// A enumeration of the types of metrics supported by the encoder.
+#[derive(Clone, Copy, Debug)]
+pub enum MetricType {
+  Counter,
+  Gauge,
+  Summary,
+}
+
+impl MetricType {
+  fn as_str(&self) -> &'static str {
+    match self {
+      Self::Counter => "counter",
+      Self::Gauge => "gauge",
+      Self::Summary => "summary",
+    }
+  }
+}
+
+/// An encoder of metrics in the text format of Prometheus. A family is
+/// declared with its help and its type before its samples are written.
+#[derive(Default)]
+pub struct Encoder {
+  buffer: String,
+}
+
+impl Encoder {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// It declares a family of metrics. The name is prefixed by `bakerd_`.
+  pub fn family(&mut self, name: &str, kind: MetricType, help: &str) -> &mut Self {
+    let _ = writeln!(self.buffer, "# HELP {}_{} {}", PREFIX, name, help);
+    let _ = writeln!(self.buffer, "# TYPE {}_{} {}", PREFIX, name, kind.as_str());
+    self
+  }
+
+  /// It writes a sample of a metric with the labels in the given order.
+  pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Self {
+    let _ = write!(self.buffer, "{}_{}", PREFIX, name);
+
+    if !labels.is_empty() {
+      let labels: Vec<String> = labels
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+        .collect();
+
+      let _ = write!(self.buffer, "{{{}}}", labels.join(","));
+    }
+
+    let _ = writeln!(self.buffer, " {}", value);
+    self
+  }
+
+  /// It declares a family with a single sample without labels.
+  pub fn single(&mut self, name: &str, kind: MetricType, help: &str, value: f64) -> &mut Self {
+    self.family(name, kind, help).sample(name, &[], value)
+  }
+
+  pub fn finish(self) -> String {
+    self.buffer
+  }
+}
+
+/// It escapes a label value as required by the text format.
+fn escape(value: &str) -> String {
+  value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// It converts a boolean into the value of a gauge.
+pub fn as_gauge(value: bool) -> f64 {
+  if value {
+    1.0
+  } else {
+    0.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_encoder() {
+    let mut encoder = Encoder::new();
+
+    encoder
+      .family("account_balance", MetricType::Gauge, "Balance of the account.")
+      .sample("account_balance", &[("address", ":address-1:")], 12.5)
+      .sample("account_balance", &[("address", "a\"b\\c\nd")], 0.0);
+
+    encoder.single("block_height", MetricType::Gauge, "Height of the block.", 42.0);
+
+    assert_eq!(
+      concat!(
+        "# HELP bakerd_account_balance Balance of the account.\n",
+        "# TYPE bakerd_account_balance gauge\n",
+        "bakerd_account_balance{address=\":address-1:\"} 12.5\n",
+        "bakerd_account_balance{address=\"a\\\"b\\\\c\\nd\"} 0\n",
+        "# HELP bakerd_block_height Height of the block.\n",
+        "# TYPE bakerd_block_height gauge\n",
+        "bakerd_block_height 42\n",
+      ),
+      encoder.finish()
+    );
+  }
+
+  #[test]
+  fn test_as_gauge() {
+    assert_eq!(1.0, as_gauge(true));
+    assert_eq!(0.0, as_gauge(false));
+  }
+}
@@ -1,6 +1,20 @@
 use crate::repository::{NodeStatusJson, ResourceStatusJson};
 use rust_decimal::Decimal;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+/// A enumeration of the ways an account takes part in the staking. It
+/// supports serialization into a human readable string.
+#[derive(Serialize, PartialEq, Clone, Copy, Debug)]
+pub enum StakingKind {
+  #[serde(rename = "none")]
+  None,
+
+  #[serde(rename = "baker")]
+  Baker,
+
+  #[serde(rename = "delegator")]
+  Delegator,
+}
 
 /// An account on the Concordium blockchain. It is uniquely identified through
 /// the address.
@@ -11,6 +25,8 @@ pub struct Account {
   balance: Decimal,
   stake: Decimal,
   lottery_power: f64,
+  staking: StakingKind,
+  pool_id: Option<u64>,
 }
 
 impl Account {
@@ -22,6 +38,32 @@ impl Account {
   pub fn get_address(&self) -> &str {
     return &self.address;
   }
+
+  pub fn get_balance(&self) -> Decimal {
+    self.balance
+  }
+
+  pub fn get_stake(&self) -> Decimal {
+    self.stake
+  }
+
+  /// It returns the share of the stake of the baker in the lottery to produce
+  /// a block.
+  pub fn get_lottery_power(&self) -> f64 {
+    self.lottery_power
+  }
+
+  /// It returns how the account takes part in the staking.
+  pub fn get_staking(&self) -> StakingKind {
+    self.staking
+  }
+
+  /// It returns the baker ID of the pool of the account, which is either its
+  /// own pool for a baker, or the pool it delegates to. The passive delegation
+  /// has no pool.
+  pub fn get_pool_id(&self) -> Option<u64> {
+    self.pool_id
+  }
 }
 
 impl Account {
@@ -32,8 +74,18 @@ impl Account {
       balance,
       stake,
       lottery_power,
+      staking: StakingKind::None,
+      pool_id: None,
     }
   }
+
+  /// It sets how the account takes part in the staking and the pool it is
+  /// attached to.
+  pub fn with_staking(mut self, staking: StakingKind, pool_id: Option<u64>) -> Self {
+    self.staking = staking;
+    self.pool_id = pool_id;
+    self
+  }
 }
 
 /// A enumeration of the reward kinds. It supports serialization into a human
@@ -45,10 +97,16 @@ pub enum RewardKind {
 
   #[serde(rename = "kind_transaction_fee")]
   TransactionFee,
+
+  #[serde(rename = "kind_finalization")]
+  Finalization,
+
+  #[serde(rename = "kind_delegation")]
+  Delegation,
 }
 
-/// A reward of a baker which can be either a baker reward or the transaction
-/// fees.
+/// A reward of an account which can be either a baker reward, the transaction
+/// fees, a finalization reward, or the reward of a delegator.
 #[derive(Serialize, Debug)]
 pub struct Reward {
   id: i32,
@@ -84,8 +142,82 @@ impl Reward {
   }
 }
 
-/// A unique combination of a base and a quote currency.
+/// A transaction of a block that touches an account. The amount is positive
+/// when the account receives CCD and negative when it sends some, and the cost
+/// is only paid by the sender.
 #[derive(Serialize, Debug)]
+pub struct Transaction {
+  id: i32,
+  account_id: i32,
+  block_hash: String,
+  transaction_hash: String,
+  epoch_ms: i64,
+  kind: String,
+  amount: Decimal,
+  counterparty: Option<String>,
+  cost: Decimal,
+}
+
+impl Transaction {
+  pub fn new(id: i32, account_id: i32, block_hash: &str, transaction_hash: &str, epoch_ms: i64, kind: &str) -> Self {
+    Self {
+      id,
+      account_id,
+      block_hash: block_hash.to_string(),
+      transaction_hash: transaction_hash.to_string(),
+      epoch_ms,
+      kind: kind.to_string(),
+      amount: Decimal::ZERO,
+      counterparty: None,
+      cost: Decimal::ZERO,
+    }
+  }
+
+  /// It defines the amount transferred and the address of the other party of
+  /// the transaction.
+  pub fn with_transfer(mut self, amount: Decimal, counterparty: Option<String>) -> Self {
+    self.amount = amount;
+    self.counterparty = counterparty;
+    self
+  }
+
+  pub fn with_cost(mut self, cost: Decimal) -> Self {
+    self.cost = cost;
+    self
+  }
+}
+
+/// A snapshot of the balance, the stake and the lottery power of an account
+/// taken when it is refreshed.
+#[derive(Serialize, Debug)]
+pub struct Snapshot {
+  id: i32,
+  account_id: i32,
+  epoch_ms: i64,
+  balance: Decimal,
+  stake: Decimal,
+  lottery_power: f64,
+}
+
+impl Snapshot {
+  pub fn new(id: i32, account_id: i32, epoch_ms: i64, balance: Decimal, stake: Decimal, lottery_power: f64) -> Self {
+    Self {
+      id,
+      account_id,
+      epoch_ms,
+      balance,
+      stake,
+      lottery_power,
+    }
+  }
+
+  pub fn get_epoch_ms(&self) -> i64 {
+    self.epoch_ms
+  }
+}
+
+/// A unique combination of a base and a quote currency.
+#[derive(Serialize, Debug, Clone)]
 pub struct Pair {
   id: i32,
   base: String,
@@ -149,6 +281,10 @@ impl Price {
       low,
     }
   }
+
+  pub fn get_bid(&self) -> f64 {
+    self.bid
+  }
 }
 
 impl From<(i32, f64, f64)> for Price {
@@ -172,6 +308,8 @@ pub struct Block {
   hash: String,
   slot_time_ms: i64,
   baker: i64,
+  genesis_index: i32,
+  protocol_version: i32,
 }
 
 impl Block {
@@ -182,12 +320,105 @@ impl Block {
       hash: hash.to_string(),
       slot_time_ms,
       baker,
+      genesis_index: 0,
+      protocol_version: 0,
     }
   }
 
+  /// It sets the era of the block given by the index of its genesis, and the
+  /// version of the protocol in use during this era.
+  pub fn with_era(mut self, genesis_index: i32, protocol_version: i32) -> Self {
+    self.genesis_index = genesis_index;
+    self.protocol_version = protocol_version;
+    self
+  }
+
   pub fn get_height(&self) -> i64 {
     self.height
   }
+
+  pub fn get_hash(&self) -> &str {
+    &self.hash
+  }
+
+  pub fn get_slot_time_ms(&self) -> i64 {
+    self.slot_time_ms
+  }
+
+  /// It returns the ID of the baker that produced the block.
+  pub fn get_baker(&self) -> i64 {
+    self.baker
+  }
+}
+
+/// The rates of the commissions that a baker takes on the rewards of its pool.
+#[derive(PartialEq, Clone, Serialize, Debug)]
+pub struct CommissionRates {
+  pub baking: f64,
+  pub finalization: f64,
+  pub transaction: f64,
+}
+
+/// The capital of a pool expressed in micro CCD.
+#[derive(PartialEq, Clone, Serialize, Debug)]
+pub struct PoolCapital {
+  pub equity: Decimal,
+  pub delegated: Decimal,
+  pub delegated_cap: Decimal,
+}
+
+/// The statistics of a pool for the reward period in progress.
+#[derive(PartialEq, Clone, Serialize, Debug)]
+pub struct PoolPayday {
+  pub blocks_baked: i64,
+  pub finalization_live: bool,
+  pub transaction_fees_earned: Decimal,
+  pub effective_stake: Decimal,
+  pub lottery_power: f64,
+}
+
+/// A snapshot of the status of the pool of a baker at a given time.
+#[derive(PartialEq, Clone, Serialize, Debug)]
+pub struct Pool {
+  id: i32,
+  baker_id: i64,
+  epoch_ms: i64,
+  open_status: String,
+  commission_rates: CommissionRates,
+  capital: PoolCapital,
+  payday: Option<PoolPayday>,
+}
+
+impl Pool {
+  pub fn new(
+    id: i32,
+    baker_id: i64,
+    epoch_ms: i64,
+    open_status: &str,
+    commission_rates: CommissionRates,
+    capital: PoolCapital,
+    payday: Option<PoolPayday>,
+  ) -> Self {
+    Self {
+      id,
+      baker_id,
+      epoch_ms,
+      open_status: open_status.to_string(),
+      commission_rates,
+      capital,
+      payday,
+    }
+  }
+
+  pub fn get_epoch_ms(&self) -> i64 {
+    self.epoch_ms
+  }
+
+  /// It returns the statistics of the payday at the time of the snapshot, if
+  /// the node gave them.
+  pub fn get_payday(&self) -> Option<&PoolPayday> {
+    self.payday.as_ref()
+  }
 }
 
 #[derive(Serialize, Debug)]
@@ -207,6 +438,170 @@ impl Status {
       timestamp_ms,
     }
   }
+
+  pub fn get_resources(&self) -> &ResourceStatusJson {
+    &self.resources
+  }
+
+  /// It returns the status of the node, or none when the node could not be
+  /// reached for the report.
+  pub fn get_node(&self) -> Option<&NodeStatusJson> {
+    self.node.as_ref()
+  }
+
+  pub fn get_timestamp_ms(&self) -> i64 {
+    self.timestamp_ms
+  }
+}
+
+/// A peer of the node with the statistics of the last report where it was
+/// seen. A peer that is not connected anymore has the time of the first report
+/// that missed it.
+#[derive(Serialize, Debug)]
+pub struct Peer {
+  id: i32,
+  node_id: String,
+  packets_sent: i64,
+  packets_received: i64,
+  latency: i64,
+  connected_ms: i64,
+  last_seen_ms: i64,
+  disconnected_ms: Option<i64>,
+}
+
+impl Peer {
+  pub fn new(id: i32, node_id: &str, packets_sent: i64, packets_received: i64, latency: i64) -> Self {
+    Self {
+      id,
+      node_id: node_id.to_string(),
+      packets_sent,
+      packets_received,
+      latency,
+      connected_ms: 0,
+      last_seen_ms: 0,
+      disconnected_ms: None,
+    }
+  }
+
+  pub fn with_connection(mut self, connected_ms: i64, last_seen_ms: i64, disconnected_ms: Option<i64>) -> Self {
+    self.connected_ms = connected_ms;
+    self.last_seen_ms = last_seen_ms;
+    self.disconnected_ms = disconnected_ms;
+    self
+  }
+}
+
+/// A enumeration of the states of an alert. It supports serialization into a
+/// human readable string.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum AlertState {
+  #[serde(rename = "firing")]
+  Firing,
+
+  #[serde(rename = "resolved")]
+  Resolved,
+}
+
+/// An alert fired by a rule about a subject, like the node or an account. It
+/// stays firing until the condition of the rule is not met anymore.
+#[derive(Serialize, Debug)]
+pub struct Alert {
+  id: i32,
+  rule: String,
+  subject: String,
+  message: String,
+  state: AlertState,
+  fired_ms: i64,
+  resolved_ms: Option<i64>,
+  acked_ms: Option<i64>,
+  acked_by: Option<i32>,
+}
+
+impl Alert {
+  pub fn new(id: i32, rule: &str, subject: &str, message: &str, fired_ms: i64) -> Self {
+    Self {
+      id,
+      rule: rule.to_string(),
+      subject: subject.to_string(),
+      message: message.to_string(),
+      state: AlertState::Firing,
+      fired_ms,
+      resolved_ms: None,
+      acked_ms: None,
+      acked_by: None,
+    }
+  }
+
+  /// It marks the alert as resolved at the given time.
+  pub fn with_resolution(mut self, resolved_ms: i64) -> Self {
+    self.state = AlertState::Resolved;
+    self.resolved_ms = Some(resolved_ms);
+    self
+  }
+
+  /// It records that the alert has been acknowledged by a user.
+  pub fn with_ack(mut self, acked_ms: i64, acked_by: Option<i32>) -> Self {
+    self.acked_ms = Some(acked_ms);
+    self.acked_by = acked_by;
+    self
+  }
+
+  pub fn get_id(&self) -> i32 {
+    self.id
+  }
+
+  pub fn get_rule(&self) -> &str {
+    &self.rule
+  }
+
+  pub fn get_subject(&self) -> &str {
+    &self.subject
+  }
+
+  pub fn get_message(&self) -> &str {
+    &self.message
+  }
+}
+
+/// A silence that mutes the notifications of the alerts matching the rule and
+/// the subject during a time window. A missing rule or subject matches any.
+#[derive(Serialize, Debug)]
+pub struct Silence {
+  id: i32,
+  rule: Option<String>,
+  subject: Option<String>,
+  reason: String,
+  created_by: Option<i32>,
+  starts_ms: i64,
+  ends_ms: i64,
+}
+
+impl Silence {
+  pub fn new(id: i32, rule: Option<&str>, subject: Option<&str>, reason: &str, starts_ms: i64, ends_ms: i64) -> Self {
+    Self {
+      id,
+      rule: rule.map(String::from),
+      subject: subject.map(String::from),
+      reason: reason.to_string(),
+      created_by: None,
+      starts_ms,
+      ends_ms,
+    }
+  }
+
+  pub fn with_creator(mut self, created_by: Option<i32>) -> Self {
+    self.created_by = created_by;
+    self
+  }
+
+  /// It returns true when the silence is active at the given time and matches
+  /// the rule and the subject of the alert.
+  pub fn matches(&self, alert: &Alert, at_ms: i64) -> bool {
+    let rule = self.rule.as_deref().map_or(true, |r| r == alert.rule);
+    let subject = self.subject.as_deref().map_or(true, |s| s == alert.subject);
+
+    self.starts_ms <= at_ms && at_ms < self.ends_ms && rule && subject
+  }
 }
 
 #[derive(Serialize, Debug)]
@@ -280,6 +675,8 @@ mod tests {
       balance: Decimal::from(123),
       lottery_power: 0.0,
       stake: Decimal::from(456),
+      staking: StakingKind::None,
+      pool_id: None,
     };
 
     // Serialize
@@ -293,6 +690,23 @@ mod tests {
     assert!(account == account.clone());
   }
 
+  #[test]
+  fn test_account_staking() {
+    let account = Account::new(1, ":address:", Decimal::from(1), Decimal::from(2), 0.0);
+
+    assert_eq!(StakingKind::None, account.get_staking());
+    assert_eq!(None, account.get_pool_id());
+
+    let account = account.with_staking(StakingKind::Delegator, Some(42));
+
+    assert_eq!(StakingKind::Delegator, account.get_staking());
+    assert_eq!(Some(42), account.get_pool_id());
+
+    let res = serde_json::to_value(&account).unwrap();
+    assert_eq!("delegator", res["staking"]);
+    assert_eq!(42, res["pool_id"]);
+  }
+
   #[test]
   fn test_reward() {
     let reward = Reward {
@@ -328,6 +742,37 @@ mod tests {
     format!("{:?}", reward);
   }
 
+  #[test]
+  fn test_reward_kind_serialize() {
+    assert_eq!("\"kind_baker\"", serde_json::to_string(&RewardKind::Baker).unwrap());
+    assert_eq!(
+      "\"kind_transaction_fee\"",
+      serde_json::to_string(&RewardKind::TransactionFee).unwrap()
+    );
+    assert_eq!(
+      "\"kind_finalization\"",
+      serde_json::to_string(&RewardKind::Finalization).unwrap()
+    );
+    assert_eq!(
+      "\"kind_delegation\"",
+      serde_json::to_string(&RewardKind::Delegation).unwrap()
+    );
+  }
+
+  #[test]
+  fn test_transaction_serialize() {
+    let transaction = Transaction::new(1, 2, ":block:", ":tx:", 1000, "transfer")
+      .with_transfer(Decimal::from(-250), Some(":address:".to_string()))
+      .with_cost(Decimal::from(3));
+
+    let value = serde_json::to_value(&transaction).unwrap();
+
+    assert_eq!("-250", value["amount"]);
+    assert_eq!(":address:", value["counterparty"]);
+    assert_eq!("3", value["cost"]);
+    assert_eq!("transfer", value["kind"]);
+  }
+
   #[test]
   fn test_pair_attributes() {
     let pair = Pair {
@@ -371,6 +816,8 @@ mod tests {
       hash: ":hash:".into(),
       height: 123,
       slot_time_ms: 1000,
+      genesis_index: 3,
+      protocol_version: 4,
     };
 
     // Serialize
@@ -406,6 +853,18 @@ mod tests {
     format!("{:?}", res);
   }
 
+  #[test]
+  fn test_silence_matches() {
+    let alert = Alert::new(1, "low-peers", "node", "", 0);
+
+    assert!(Silence::new(1, None, None, "", 1000, 2000).matches(&alert, 1000));
+    assert!(Silence::new(1, Some("low-peers"), Some("node"), "", 1000, 2000).matches(&alert, 1500));
+    assert!(!Silence::new(1, Some("low-peers"), None, "", 1000, 2000).matches(&alert, 2000));
+    assert!(!Silence::new(1, None, Some("node"), "", 1000, 2000).matches(&alert, 999));
+    assert!(!Silence::new(1, Some("node-down"), None, "", 1000, 2000).matches(&alert, 1500));
+    assert!(!Silence::new(1, None, Some(":address:"), "", 1000, 2000).matches(&alert, 1500));
+  }
+
   #[test]
   fn test_user_attributes() {
     let user = User {
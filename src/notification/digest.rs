@@ -0,0 +1,320 @@
+use super::{DynNotifier, Kind, Notification};
+use crate::analysis::DAY_MS;
+use crate::event::Event;
+use crate::repository::{DynPriceRepository, PairFilter};
+use chrono::{TimeZone, Utc};
+use log::{error, warn};
+use rust_decimal::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+
+/// Base currency of the pair used to value the rewards.
+const BASE_CURRENCY: &str = "CCD";
+
+/// Maximum age of the end of a period for its digest to be sent, so that
+/// catching up does not flood the channels.
+const MAX_AGE_MS: i64 = DAY_MS;
+
+/// The rewards of the accounts over a period.
+struct Rewards {
+  epoch_ms: i64,
+  amounts: BTreeMap<String, Decimal>,
+}
+
+impl Rewards {
+  fn new(epoch_ms: i64) -> Self {
+    Self {
+      epoch_ms,
+      amounts: BTreeMap::new(),
+    }
+  }
+
+  fn add(&mut self, address: &str, amount: Decimal) {
+    *self.amounts.entry(address.to_string()).or_default() += amount;
+  }
+}
+
+/// A scheduler that follows the events of the daemon to summarise the rewards
+/// of the accounts per payday and per day. The indexed blocks act as the clock
+/// so that a period is over once a block after it has been processed.
+pub struct DigestScheduler {
+  prices: DynPriceRepository,
+  notifier: DynNotifier,
+  quote: Option<String>,
+  payday: bool,
+  daily: bool,
+  paydays: HashMap<String, Rewards>,
+  days: BTreeMap<i64, Rewards>,
+}
+
+impl DigestScheduler {
+  pub fn new(prices: DynPriceRepository, notifier: DynNotifier) -> Self {
+    Self {
+      prices,
+      notifier,
+      quote: None,
+      payday: true,
+      daily: true,
+      paydays: HashMap::new(),
+      days: BTreeMap::new(),
+    }
+  }
+
+  /// It defines the currency in which the rewards are valued, like `USD`.
+  pub fn with_quote(mut self, quote: Option<String>) -> Self {
+    self.quote = quote;
+    self
+  }
+
+  /// It defines which of the payday and the daily digests are sent.
+  pub fn with_periods(mut self, payday: bool, daily: bool) -> Self {
+    self.payday = payday;
+    self.daily = daily;
+    self
+  }
+
+  /// It spawns a task that handles the events until the bus is closed.
+  pub fn spawn(mut self, mut events: broadcast::Receiver<Event>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+      loop {
+        match events.recv().await {
+          Ok(event) => self.handle(event, Utc::now().timestamp_millis()).await,
+          Err(RecvError::Lagged(count)) => warn!("digest scheduler has missed {} events", count),
+          Err(RecvError::Closed) => return,
+        }
+      }
+    })
+  }
+
+  async fn handle(&mut self, event: Event, now_ms: i64) {
+    match event {
+      Event::RewardReceived {
+        address,
+        block_hash,
+        amount,
+        epoch_ms,
+        ..
+      } => {
+        let day = epoch_ms.div_euclid(DAY_MS);
+
+        self
+          .paydays
+          .entry(block_hash)
+          .or_insert_with(|| Rewards::new(epoch_ms))
+          .add(&address, amount);
+
+        self
+          .days
+          .entry(day)
+          .or_insert_with(|| Rewards::new(day * DAY_MS))
+          .add(&address, amount);
+      }
+      Event::BlockIndexed {
+        hash,
+        slot_time_ms,
+        ..
+      } => {
+        if let Some(rewards) = self.paydays.remove(&hash) {
+          if self.payday && now_ms - rewards.epoch_ms <= MAX_AGE_MS {
+            self.send("payday rewards", &rewards, rewards.epoch_ms).await;
+          }
+        }
+
+        // The rewards of a block that was missed are dropped.
+        self.paydays.retain(|_, rewards| rewards.epoch_ms >= slot_time_ms);
+
+        let current = self.days.split_off(&slot_time_ms.div_euclid(DAY_MS));
+        let ended = std::mem::replace(&mut self.days, current);
+
+        for rewards in ended.into_values() {
+          let end_ms = rewards.epoch_ms + DAY_MS;
+
+          if self.daily && now_ms - end_ms <= MAX_AGE_MS {
+            self.send("daily rewards", &rewards, end_ms).await;
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  async fn send(&self, title: &str, rewards: &Rewards, timestamp_ms: i64) {
+    let date = match Utc.timestamp_millis_opt(rewards.epoch_ms).single() {
+      Some(at) => at.format("%Y-%m-%d").to_string(),
+      None => return,
+    };
+
+    let rate = self.get_rate().await;
+
+    let value = |amount: Decimal| match (&self.quote, rate) {
+      (Some(quote), Some(rate)) => format!(
+        "{} {} ({} {})",
+        amount,
+        BASE_CURRENCY,
+        (amount * rate).round_dp(2),
+        quote
+      ),
+      _ => format!("{} {}", amount, BASE_CURRENCY),
+    };
+
+    let mut lines: Vec<String> = rewards
+      .amounts
+      .iter()
+      .map(|(address, amount)| format!("{}: {}", address, value(*amount)))
+      .collect();
+
+    lines.push(format!("total: {}", value(rewards.amounts.values().sum())));
+
+    let notification = Notification::new(
+      Kind::Digest,
+      &format!("{} of {}", title, date),
+      &lines.join("\n"),
+      timestamp_ms,
+    );
+
+    self.notifier.notify(&notification).await;
+  }
+
+  /// It returns the last bid of the pair of the quote, if any.
+  async fn get_rate(&self) -> Option<Decimal> {
+    let quote = self.quote.as_deref()?;
+
+    let filter = PairFilter {
+      base: Some(BASE_CURRENCY),
+      quote: Some(quote),
+    };
+
+    let pairs = match self.prices.get_pairs(filter).await {
+      Ok(pairs) => pairs,
+      Err(e) => {
+        error!("unable to find the pair of {}: {}", quote, e);
+        return None;
+      }
+    };
+
+    match self.prices.get_price(pairs.first()?).await {
+      Ok(price) => Decimal::from_f64(price.get_bid()),
+      Err(e) => {
+        error!("unable to value the rewards in {}: {}", quote, e);
+        None
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::Price;
+  use crate::notification::{MockChannel, Notifier};
+  use crate::repository::MockPriceRepository;
+  use rust_decimal_macros::dec;
+  use std::sync::{Arc, Mutex};
+
+  const DAY_0: i64 = 19_000 * DAY_MS;
+  const HOUR_MS: i64 = DAY_MS / 24;
+
+  fn reward(address: &str, block_hash: &str, amount: Decimal, epoch_ms: i64) -> Event {
+    Event::RewardReceived {
+      account_id: 1,
+      address: address.to_string(),
+      block_hash: block_hash.to_string(),
+      amount,
+      epoch_ms,
+    }
+  }
+
+  fn block(hash: &str, slot_time_ms: i64) -> Event {
+    Event::BlockIndexed {
+      hash: hash.to_string(),
+      height: 1,
+      slot_time_ms,
+    }
+  }
+
+  fn scheduler(sent: Arc<Mutex<Vec<Notification>>>) -> DigestScheduler {
+    let mut prices = MockPriceRepository::new();
+
+    prices
+      .expect_get_pairs()
+      .withf(|f| f.base == Some("CCD") && f.quote == Some("USD"))
+      .returning(|_| Ok(vec![(1, "CCD", "USD").into()]));
+
+    prices.expect_get_price().returning(|_| Ok(Price::from((1, 1.5, 1.6))));
+
+    let mut channel = MockChannel::new();
+
+    channel.expect_send().returning(move |n| {
+      sent.lock().unwrap().push(n.clone());
+      Ok(())
+    });
+
+    DigestScheduler::new(Arc::new(prices), Arc::new(Notifier::new(vec![Arc::new(channel)])))
+      .with_quote(Some("USD".to_string()))
+  }
+
+  #[tokio::test]
+  async fn test_handle() {
+    let sent = Arc::new(Mutex::new(vec![]));
+
+    let mut scheduler = scheduler(sent.clone());
+
+    let now_ms = DAY_0 + 2 * HOUR_MS;
+
+    scheduler
+      .handle(reward(":address-1:", ":payday:", dec!(10), DAY_0 + HOUR_MS), now_ms)
+      .await;
+    scheduler
+      .handle(reward(":address-2:", ":payday:", dec!(5.5), DAY_0 + HOUR_MS), now_ms)
+      .await;
+    scheduler.handle(block(":payday:", DAY_0 + HOUR_MS), now_ms).await;
+
+    scheduler
+      .handle(reward(":address-1:", ":other:", dec!(1), DAY_0 + 2 * HOUR_MS), now_ms)
+      .await;
+
+    // The next day starts and the block with the other rewards was missed.
+    let now_ms = DAY_0 + DAY_MS + HOUR_MS;
+
+    scheduler.handle(block(":next:", DAY_0 + DAY_MS), now_ms).await;
+
+    let sent = sent.lock().unwrap();
+
+    assert_eq!(2, sent.len());
+
+    assert_eq!(Kind::Digest, sent[0].kind);
+    assert_eq!("payday rewards of 2022-01-08", sent[0].title);
+    assert_eq!(
+      ":address-1:: 10 CCD (15.0 USD)\n:address-2:: 5.5 CCD (8.25 USD)\ntotal: 15.5 CCD (23.25 USD)",
+      sent[0].message
+    );
+
+    assert_eq!("daily rewards of 2022-01-08", sent[1].title);
+    assert_eq!(DAY_0 + DAY_MS, sent[1].timestamp_ms);
+    assert_eq!(
+      ":address-1:: 11 CCD (16.5 USD)\n:address-2:: 5.5 CCD (8.25 USD)\ntotal: 16.5 CCD (24.75 USD)",
+      sent[1].message
+    );
+
+    assert!(scheduler.paydays.is_empty());
+    assert!(scheduler.days.is_empty());
+  }
+
+  #[tokio::test]
+  async fn test_handle_catching_up() {
+    let sent = Arc::new(Mutex::new(vec![]));
+
+    let mut scheduler = scheduler(sent.clone());
+
+    let now_ms = DAY_0 + 10 * DAY_MS;
+
+    scheduler
+      .handle(reward(":address-1:", ":payday:", dec!(10), DAY_0), now_ms)
+      .await;
+    scheduler.handle(block(":payday:", DAY_0), now_ms).await;
+    scheduler.handle(block(":next:", DAY_0 + DAY_MS), now_ms).await;
+
+    assert!(sent.lock().unwrap().is_empty());
+  }
+}
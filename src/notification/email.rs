@@ -0,0 +1,249 @@
+use super::{Channel, DynChannel, Kind, Notification, Result};
+use chrono::{TimeZone, Utc};
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Deserialize;
+use std::io;
+use std::sync::Arc;
+
+const DEFAULT_PORT: u16 = 587;
+
+fn default_port() -> u16 {
+  DEFAULT_PORT
+}
+
+fn default_starttls() -> bool {
+  true
+}
+
+/// A configuration of an SMTP server and of the recipients of the
+/// notifications sent by email.
+#[serde_with::serde_as]
+#[derive(Clone, Debug, Deserialize)]
+pub struct EmailConfig {
+  host: String,
+
+  #[serde(default = "default_port")]
+  port: u16,
+
+  /// The connection is upgraded with STARTTLS when enabled, and it is refused
+  /// if the server does not support it. Otherwise, it stays unencrypted.
+  #[serde(default = "default_starttls")]
+  starttls: bool,
+
+  username: Option<String>,
+  password: Option<String>,
+
+  #[serde(with = "serde_with::rust::display_fromstr")]
+  from: Mailbox,
+
+  #[serde_as(as = "Vec<serde_with::DisplayFromStr>")]
+  recipients: Vec<Mailbox>,
+
+  /// Kinds of the notifications sent by email, or all of them when empty.
+  #[serde(default)]
+  kinds: Vec<Kind>,
+}
+
+impl EmailConfig {
+  pub fn as_channel(&self) -> io::Result<DynChannel> {
+    let mut builder = if self.starttls {
+      AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+    } else {
+      AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host)
+    };
+
+    builder = builder.port(self.port);
+
+    if let Some(username) = &self.username {
+      let password = self.password.clone().unwrap_or_default();
+
+      builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+
+    Ok(Arc::new(EmailChannel {
+      transport: builder.build(),
+      from: self.from.clone(),
+      recipients: self.recipients.clone(),
+      kinds: self.kinds.clone(),
+    }))
+  }
+}
+
+/// A channel that sends the notifications by email to a list of recipients.
+pub struct EmailChannel {
+  transport: AsyncSmtpTransport<Tokio1Executor>,
+  from: Mailbox,
+  recipients: Vec<Mailbox>,
+  kinds: Vec<Kind>,
+}
+
+impl EmailChannel {
+  fn build(&self, notification: &Notification) -> Result<Message> {
+    let mut builder = Message::builder()
+      .from(self.from.clone())
+      .subject(&notification.title)
+      .header(ContentType::TEXT_PLAIN);
+
+    for recipient in &self.recipients {
+      builder = builder.to(recipient.clone());
+    }
+
+    let mut body = notification.message.clone();
+
+    if let Some(at) = Utc.timestamp_millis_opt(notification.timestamp_ms).single() {
+      body.push_str(&format!("\n\n{}\n", at.to_rfc3339()));
+    }
+
+    Ok(builder.body(body)?)
+  }
+}
+
+#[async_trait]
+impl Channel for EmailChannel {
+  async fn send(&self, notification: &Notification) -> Result<()> {
+    if !self.kinds.is_empty() && !self.kinds.contains(&notification.kind) {
+      return Ok(());
+    }
+
+    let message = self.build(notification)?;
+
+    self.transport.send(message).await?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::notification::Error;
+  use std::sync::Mutex;
+  use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+  use tokio::net::TcpListener;
+
+  type Lines = Arc<Mutex<Vec<String>>>;
+
+  /// It starts a fake SMTP server that accepts a single connection and
+  /// records the lines received. The recipients are refused when asked.
+  async fn serve(refuse_recipients: bool) -> (u16, Lines) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let lines: Lines = Arc::new(Mutex::new(vec![]));
+    let received = lines.clone();
+
+    tokio::spawn(async move {
+      let (stream, _) = listener.accept().await.unwrap();
+      let (reader, mut writer) = stream.into_split();
+      let mut reader = BufReader::new(reader).lines();
+
+      writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+      let mut in_data = false;
+
+      while let Ok(Some(line)) = reader.next_line().await {
+        received.lock().unwrap().push(line.clone());
+
+        let reply: &[u8] = if in_data {
+          if line != "." {
+            continue;
+          }
+          in_data = false;
+          b"250 queued\r\n"
+        } else if line.starts_with("EHLO") {
+          b"250-localhost\r\n250 AUTH PLAIN LOGIN\r\n"
+        } else if line.starts_with("AUTH") {
+          b"235 authenticated\r\n"
+        } else if line.starts_with("RCPT") && refuse_recipients {
+          b"550 unknown recipient\r\n"
+        } else if line == "DATA" {
+          in_data = true;
+          b"354 go ahead\r\n"
+        } else if line == "QUIT" {
+          writer.write_all(b"221 bye\r\n").await.unwrap();
+          break;
+        } else {
+          b"250 ok\r\n"
+        };
+
+        writer.write_all(reply).await.unwrap();
+      }
+    });
+
+    (port, lines)
+  }
+
+  fn config(port: u16) -> EmailConfig {
+    EmailConfig {
+      host: "127.0.0.1".to_string(),
+      port,
+      starttls: false,
+      username: Some("bakerd".to_string()),
+      password: Some("password".to_string()),
+      from: "bakerd <bakerd@example.com>".parse().unwrap(),
+      recipients: vec![
+        "ops@example.com".parse().unwrap(),
+        "Alice <alice@example.com>".parse().unwrap(),
+      ],
+      kinds: vec![],
+    }
+  }
+
+  fn notification() -> Notification {
+    Notification::new(Kind::AlertFiring, "[low-peers] node", "the node has 1 peers", 1000)
+  }
+
+  #[tokio::test]
+  async fn test_send() {
+    let (port, lines) = serve(false).await;
+
+    let res = config(port).as_channel().unwrap().send(&notification()).await;
+
+    assert!(matches!(res, Ok(_)));
+
+    let lines = lines.lock().unwrap();
+
+    assert!(lines.iter().any(|l| l.starts_with("AUTH PLAIN ")));
+    assert!(lines.contains(&"MAIL FROM:<bakerd@example.com>".to_string()));
+    assert!(lines.contains(&"RCPT TO:<ops@example.com>".to_string()));
+    assert!(lines.contains(&"RCPT TO:<alice@example.com>".to_string()));
+    assert!(lines.contains(&"Subject: [low-peers] node".to_string()));
+    assert!(lines.contains(&"the node has 1 peers".to_string()));
+    assert!(lines.contains(&"1970-01-01T00:00:01+00:00".to_string()));
+  }
+
+  #[tokio::test]
+  async fn test_send_refused() {
+    let (port, _) = serve(true).await;
+
+    let res = config(port).as_channel().unwrap().send(&notification()).await;
+
+    assert!(matches!(res, Err(Error::Smtp(e)) if e.is_permanent()));
+  }
+
+  #[tokio::test]
+  async fn test_send_filtered() {
+    // Nothing listens on the port, so the notification must not be sent.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+      .unwrap()
+      .local_addr()
+      .unwrap()
+      .port();
+
+    let mut cfg = config(port);
+    cfg.kinds = vec![Kind::AlertFiring, Kind::AlertResolved];
+
+    let channel = cfg.as_channel().unwrap();
+
+    let res = channel.send(&Notification::new(Kind::Reward, "reward", "", 1000)).await;
+
+    assert!(matches!(res, Ok(_)));
+
+    let res = channel.send(&notification()).await;
+
+    assert!(matches!(res, Err(Error::Smtp(_))));
+  }
+}
@@ -0,0 +1,158 @@
+pub mod digest;
+pub mod email;
+pub mod webhook;
+
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum Error {
+  Http(reqwest::Error),
+  Smtp(lettre::transport::smtp::Error),
+  Email(lettre::error::Error),
+  Template(String),
+  Timeout(Duration),
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Http(e) => write!(f, "notification error: http: {}", e),
+      Self::Smtp(e) => write!(f, "notification error: smtp: {}", e),
+      Self::Email(e) => write!(f, "notification error: email: {}", e),
+      Self::Template(e) => write!(f, "notification error: template: {}", e),
+      Self::Timeout(d) => write!(f, "notification error: deadline of {:?} exceeded", d),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+  fn from(e: reqwest::Error) -> Self {
+    Self::Http(e)
+  }
+}
+
+impl From<lettre::transport::smtp::Error> for Error {
+  fn from(e: lettre::transport::smtp::Error) -> Self {
+    Self::Smtp(e)
+  }
+}
+
+impl From<lettre::error::Error> for Error {
+  fn from(e: lettre::error::Error) -> Self {
+    Self::Email(e)
+  }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A enumeration of the events that are notified.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+  AlertFiring,
+  AlertResolved,
+  Reward,
+  Digest,
+}
+
+impl Kind {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::AlertFiring => "alert_firing",
+      Self::AlertResolved => "alert_resolved",
+      Self::Reward => "reward",
+      Self::Digest => "digest",
+    }
+  }
+}
+
+/// A message sent through the notification channels.
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct Notification {
+  pub kind: Kind,
+  pub title: String,
+  pub message: String,
+  pub timestamp_ms: i64,
+}
+
+impl Notification {
+  pub fn new(kind: Kind, title: &str, message: &str, timestamp_ms: i64) -> Self {
+    Self {
+      kind,
+      title: title.to_string(),
+      message: message.to_string(),
+      timestamp_ms,
+    }
+  }
+}
+
+/// A channel through which the notifications are sent.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait Channel {
+  async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+pub type DynChannel = Arc<dyn Channel + Send + Sync>;
+
+/// A notifier that sends the notifications to every configured channel. A
+/// failure of a channel is logged and does not prevent the others from
+/// receiving the notification.
+#[derive(Default)]
+pub struct Notifier {
+  channels: Vec<DynChannel>,
+}
+
+impl Notifier {
+  pub fn new(channels: Vec<DynChannel>) -> Self {
+    Self {
+      channels,
+    }
+  }
+
+  pub async fn notify(&self, notification: &Notification) {
+    for channel in &self.channels {
+      if let Err(e) = channel.send(notification).await {
+        error!("unable to send the notification `{}`: {}", notification.title, e);
+      }
+    }
+  }
+}
+
+pub type DynNotifier = Arc<Notifier>;
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_notify() {
+    let notification = Notification::new(Kind::Reward, "title", "message", 1000);
+
+    let mut failing = MockChannel::new();
+
+    failing
+      .expect_send()
+      .times(1)
+      .returning(|_| Err(Error::Template("unknown".to_string())));
+
+    let mut channel = MockChannel::new();
+
+    let expected = notification.clone();
+    channel
+      .expect_send()
+      .withf(move |n| *n == expected)
+      .times(1)
+      .returning(|_| Ok(()));
+
+    let notifier = Notifier::new(vec![Arc::new(failing), Arc::new(channel)]);
+
+    notifier.notify(&notification).await;
+  }
+}
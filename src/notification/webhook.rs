@@ -0,0 +1,357 @@
+use super::{Channel, DynChannel, Error, Notification, Result};
+use crate::client::retry::RetryPolicy;
+use hmac::{Hmac, Mac};
+use log::warn;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use tokio::time::{self, Instant};
+
+const DEFAULT_SIGNATURE_HEADER: &str = "x-bakerd-signature";
+
+fn default_signature_header() -> String {
+  DEFAULT_SIGNATURE_HEADER.to_string()
+}
+
+/// A configuration of an HTTP endpoint that receives the notifications as a
+/// JSON body.
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebhookConfig {
+  url: String,
+
+  #[serde(default)]
+  headers: HashMap<String, String>,
+
+  /// Template of the JSON body where the `{{kind}}`, `{{title}}`,
+  /// `{{message}}` and `{{timestamp_ms}}` placeholders are replaced by the
+  /// values of the notification. The notification itself is sent otherwise.
+  template: Option<String>,
+
+  /// Secret used to sign the body with HMAC-SHA256. The signature is sent in
+  /// hexadecimal in the signature header.
+  secret: Option<String>,
+
+  #[serde(default = "default_signature_header")]
+  signature_header: String,
+
+  retry: Option<RetryPolicy>,
+}
+
+impl WebhookConfig {
+  pub fn as_channel(&self) -> io::Result<DynChannel> {
+    let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    for (name, value) in &self.headers {
+      headers.insert(
+        HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(e.to_string()))?,
+        HeaderValue::from_str(value).map_err(|e| invalid(e.to_string()))?,
+      );
+    }
+
+    let signature_header =
+      HeaderName::from_bytes(self.signature_header.as_bytes()).map_err(|e| invalid(e.to_string()))?;
+
+    Ok(Arc::new(WebhookChannel {
+      client: reqwest::Client::new(),
+      url: self.url.clone(),
+      headers,
+      template: self.template.clone(),
+      secret: self.secret.clone(),
+      signature_header,
+      retry: self.retry.clone(),
+    }))
+  }
+}
+
+/// It returns true when the error is transient and the call can be tried
+/// again.
+fn is_retryable(e: &reqwest::Error) -> bool {
+  e.is_timeout()
+    || e.is_connect()
+    || matches!(e.status(), Some(s) if s.is_server_error() || s == reqwest::StatusCode::TOO_MANY_REQUESTS)
+}
+
+/// A channel that posts the notifications to a webhook.
+pub struct WebhookChannel {
+  client: reqwest::Client,
+  url: String,
+  headers: HeaderMap,
+  template: Option<String>,
+  secret: Option<String>,
+  signature_header: HeaderName,
+  retry: Option<RetryPolicy>,
+}
+
+impl WebhookChannel {
+  /// It builds the body of the request, either from the template or from the
+  /// notification, and makes sure it is valid JSON.
+  fn render(&self, notification: &Notification) -> Result<String> {
+    let template = match &self.template {
+      Some(template) => template,
+      None => return serde_json::to_string(notification).map_err(|e| Error::Template(e.to_string())),
+    };
+
+    let body = template
+      .replace("{{kind}}", notification.kind.as_str())
+      .replace("{{title}}", &escape(&notification.title))
+      .replace("{{message}}", &escape(&notification.message))
+      .replace("{{timestamp_ms}}", &notification.timestamp_ms.to_string());
+
+    serde_json::from_str::<serde_json::Value>(&body).map_err(|e| Error::Template(e.to_string()))?;
+
+    Ok(body)
+  }
+
+  /// It returns the signature of the body in hexadecimal, if a secret is
+  /// configured.
+  fn sign(&self, body: &str) -> Option<String> {
+    let secret = self.secret.as_ref()?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).ok()?;
+    mac.update(body.as_bytes());
+
+    Some(hex::encode(mac.finalize().into_bytes()))
+  }
+
+  async fn post(&self, body: &str) -> Result<()> {
+    let mut headers = self.headers.clone();
+
+    if let Some(signature) = self.sign(body) {
+      headers.insert(
+        self.signature_header.clone(),
+        HeaderValue::from_str(&signature).expect("hexadecimal is a valid header value"),
+      );
+    }
+
+    self
+      .client
+      .post(&self.url)
+      .headers(headers)
+      .body(body.to_string())
+      .send()
+      .await?
+      .error_for_status()?;
+
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl Channel for WebhookChannel {
+  /// It posts the notification until it succeeds, the error cannot be
+  /// retried, or the policy does not allow another attempt.
+  async fn send(&self, notification: &Notification) -> Result<()> {
+    let body = self.render(notification)?;
+
+    let policy = match &self.retry {
+      Some(policy) => policy,
+      None => return self.post(&body).await,
+    };
+
+    let deadline = Instant::now() + policy.deadline();
+    let mut retry = 0;
+
+    loop {
+      let res = time::timeout_at(deadline, self.post(&body))
+        .await
+        .map_err(|_| Error::Timeout(policy.deadline()))?;
+
+      match res {
+        Err(Error::Http(e)) if retry < policy.max_retries && is_retryable(&e) => {
+          let backoff = policy.backoff(retry);
+
+          if Instant::now() + backoff >= deadline {
+            return Err(Error::Http(e));
+          }
+
+          warn!("webhook call failed and is retried in {:?}: {}", backoff, e);
+
+          retry += 1;
+
+          time::sleep(backoff).await;
+        }
+        res => return res,
+      }
+    }
+  }
+}
+
+/// It escapes a value to be inserted in a JSON string of the template.
+fn escape(value: &str) -> String {
+  let quoted = serde_json::Value::String(value.to_string()).to_string();
+
+  quoted[1..quoted.len() - 1].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::notification::Kind;
+  use axum::extract::Extension;
+  use axum::http::{HeaderMap as Headers, StatusCode};
+  use axum::routing::post;
+  use axum::Router;
+  use std::net::TcpListener;
+  use std::sync::Mutex;
+
+  type Requests = Arc<Mutex<Vec<(Headers, String)>>>;
+
+  /// It starts a stand-in of a webhook that answers with the given statuses in
+  /// order, and then with a success. It returns the URL and the requests
+  /// received.
+  fn serve(statuses: Vec<StatusCode>) -> (String, Requests) {
+    let requests: Requests = Arc::new(Mutex::new(vec![]));
+    let statuses = Arc::new(Mutex::new(statuses));
+
+    let handler = |Extension(requests): Extension<Requests>,
+                   Extension(statuses): Extension<Arc<Mutex<Vec<StatusCode>>>>,
+                   headers: Headers,
+                   body: String| async move {
+      requests.lock().unwrap().push((headers, body));
+
+      let mut statuses = statuses.lock().unwrap();
+
+      if statuses.is_empty() {
+        StatusCode::OK
+      } else {
+        statuses.remove(0)
+      }
+    };
+
+    let app = Router::new()
+      .route("/hook", post(handler))
+      .layer(Extension(requests.clone()))
+      .layer(Extension(statuses));
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+    (url, requests)
+  }
+
+  fn config(url: &str) -> WebhookConfig {
+    WebhookConfig {
+      url: url.to_string(),
+      headers: HashMap::new(),
+      template: None,
+      secret: None,
+      signature_header: default_signature_header(),
+      retry: None,
+    }
+  }
+
+  fn notification() -> Notification {
+    Notification::new(Kind::AlertFiring, "[low-peers] node", "the node has \"1\" peer", 1000)
+  }
+
+  #[tokio::test]
+  async fn test_send() {
+    let (url, requests) = serve(vec![]);
+
+    let channel = config(&url).as_channel().unwrap();
+
+    let res = channel.send(&notification()).await;
+
+    assert!(matches!(res, Ok(_)));
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(1, requests.len());
+    assert_eq!(serde_json::to_string(&notification()).unwrap(), requests[0].1);
+    assert_eq!("application/json", requests[0].0["content-type"]);
+    assert!(requests[0].0.get(DEFAULT_SIGNATURE_HEADER).is_none());
+  }
+
+  #[tokio::test]
+  async fn test_send_with_template_and_signature() {
+    let (url, requests) = serve(vec![]);
+
+    let mut cfg = config(&url);
+    cfg
+      .headers
+      .insert("authorization".to_string(), "Bearer token".to_string());
+    cfg.template =
+      Some(r#"{"text": "{{title}}: {{message}}", "kind": "{{kind}}", "at": {{timestamp_ms}}}"#.to_string());
+    cfg.secret = Some("secret".to_string());
+
+    let res = cfg.as_channel().unwrap().send(&notification()).await;
+
+    assert!(matches!(res, Ok(_)));
+
+    let requests = requests.lock().unwrap();
+    let (headers, body) = &requests[0];
+
+    assert_eq!(
+      r#"{"text": "[low-peers] node: the node has \"1\" peer", "kind": "alert_firing", "at": 1000}"#,
+      body
+    );
+    assert_eq!("Bearer token", headers["authorization"]);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+    mac.update(body.as_bytes());
+
+    assert_eq!(
+      hex::encode(mac.finalize().into_bytes()),
+      headers[DEFAULT_SIGNATURE_HEADER].to_str().unwrap()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_send_with_retry() {
+    let (url, requests) = serve(vec![StatusCode::SERVICE_UNAVAILABLE, StatusCode::TOO_MANY_REQUESTS]);
+
+    let mut cfg = config(&url);
+    cfg.retry = Some(RetryPolicy {
+      max_retries: 2,
+      initial_backoff_ms: 1,
+      ..RetryPolicy::default()
+    });
+
+    let res = cfg.as_channel().unwrap().send(&notification()).await;
+
+    assert!(matches!(res, Ok(_)));
+    assert_eq!(3, requests.lock().unwrap().len());
+  }
+
+  #[tokio::test]
+  async fn test_send_failure() {
+    let (url, requests) = serve(vec![StatusCode::INTERNAL_SERVER_ERROR, StatusCode::BAD_REQUEST]);
+
+    let mut cfg = config(&url);
+    cfg.retry = Some(RetryPolicy {
+      initial_backoff_ms: 1,
+      ..RetryPolicy::default()
+    });
+
+    let res = cfg.as_channel().unwrap().send(&notification()).await;
+
+    // A client error is not retried.
+    assert!(matches!(res, Err(Error::Http(e)) if e.status() == Some(StatusCode::BAD_REQUEST)));
+    assert_eq!(2, requests.lock().unwrap().len());
+  }
+
+  #[tokio::test]
+  async fn test_send_invalid_template() {
+    let mut cfg = config("http://127.0.0.1:0/hook");
+    cfg.template = Some(r#"{"text": {{title}}}"#.to_string());
+
+    let res = cfg.as_channel().unwrap().send(&notification()).await;
+
+    assert!(matches!(res, Err(Error::Template(_))));
+  }
+
+  #[test]
+  fn test_as_channel_invalid_header() {
+    let mut cfg = config("http://127.0.0.1:0/hook");
+    cfg.headers.insert("invalid header".to_string(), "value".to_string());
+
+    assert!(matches!(cfg.as_channel(), Err(e) if e.kind() == io::ErrorKind::InvalidData));
+  }
+}
@@ -1,16 +1,27 @@
 use super::{AsyncPool, Result};
-use crate::model::{Account, Reward};
+use crate::model::{Account, Reward, Snapshot, Transaction};
 use crate::schema::account_rewards::dsl as reward_dsl;
+use crate::schema::account_snapshots::dsl as snapshot_dsl;
+use crate::schema::account_transactions::dsl as transaction_dsl;
 use crate::schema::accounts::dsl::*;
 use crate::schema::accounts::table;
 use diesel::prelude::*;
 use std::sync::Arc;
 
-pub use models::{AccountFilter, NewAccount, NewReward, RewardKind};
+pub use models::{
+  AccountFilter, BigFloat, NewAccount, NewReward, NewSnapshot, NewTransaction, RewardKind, SnapshotFilter, StakingKind,
+  TransactionFilter,
+};
+
+/// Maximum number of snapshots deleted by a single statement to stay below the
+/// limit of variables of SQLite.
+const DELETE_CHUNK_SIZE: usize = 500;
 
 mod models {
   use crate::model;
   use crate::schema::account_rewards;
+  use crate::schema::account_snapshots;
+  use crate::schema::account_transactions;
   use crate::schema::accounts;
   use diesel::backend;
   use diesel::deserialize as de;
@@ -22,6 +33,12 @@ mod models {
 
   const REWARD_KIND_BAKER: &str = "kind_baker";
   const REWARD_KIND_TRANSACTION_FEE: &str = "kind_transaction_fee";
+  const REWARD_KIND_FINALIZATION: &str = "kind_finalization";
+  const REWARD_KIND_DELEGATION: &str = "kind_delegation";
+
+  const STAKING_NONE: &str = "none";
+  const STAKING_BAKER: &str = "baker";
+  const STAKING_DELEGATOR: &str = "delegator";
 
   #[derive(AsExpression, FromSqlRow, Debug)]
   #[diesel(sql_type = Text)]
@@ -58,6 +75,8 @@ mod models {
     pub balance: BigFloat,
     pub stake: BigFloat,
     pub pending_update: bool,
+    pub staking: StakingKind,
+    pub pool_id: Option<i64>,
   }
 
   #[derive(Default, PartialEq)]
@@ -75,6 +94,50 @@ mod models {
         record.stake.0,
         record.lottery_power,
       )
+      .with_staking(record.staking.into(), record.pool_id.map(|id| id as u64))
+    }
+  }
+
+  // A enumeration of the ways an account takes part in the staking.
+  #[derive(AsExpression, FromSqlRow, Debug)]
+  #[diesel(sql_type = Text)]
+  pub enum StakingKind {
+    None,
+    Baker,
+    Delegator,
+  }
+
+  impl From<StakingKind> for model::StakingKind {
+    /// It converts an SQL staking kind into the model one.
+    fn from(kind: StakingKind) -> Self {
+      match kind {
+        StakingKind::None => Self::None,
+        StakingKind::Baker => Self::Baker,
+        StakingKind::Delegator => Self::Delegator,
+      }
+    }
+  }
+
+  impl se::ToSql<Text, Sqlite> for StakingKind {
+    fn to_sql(&self, out: &mut se::Output<Sqlite>) -> se::Result {
+      let e = match self {
+        Self::None => STAKING_NONE,
+        Self::Baker => STAKING_BAKER,
+        Self::Delegator => STAKING_DELEGATOR,
+      };
+
+      <str as se::ToSql<Text, Sqlite>>::to_sql(e, out)
+    }
+  }
+
+  impl de::FromSql<Text, Sqlite> for StakingKind {
+    fn from_sql(value: backend::RawValue<Sqlite>) -> de::Result<Self> {
+      match <String as de::FromSql<Text, Sqlite>>::from_sql(value)?.as_str() {
+        STAKING_NONE => Ok(StakingKind::None),
+        STAKING_BAKER => Ok(StakingKind::Baker),
+        STAKING_DELEGATOR => Ok(StakingKind::Delegator),
+        x => Err(format!("unrecognized value for enum: {}", x).into()),
+      }
     }
   }
 
@@ -85,13 +148,15 @@ mod models {
   }
 
   #[derive(Insertable, AsChangeset)]
-  #[diesel(table_name = accounts)]
+  #[diesel(table_name = accounts, treat_none_as_null = true)]
   pub struct NewAccount {
     pub address: String,
     pub balance: BigFloat,
     pub stake: BigFloat,
     pub lottery_power: f64,
     pub pending_update: bool,
+    pub staking: StakingKind,
+    pub pool_id: Option<i64>,
   }
 
   impl NewAccount {
@@ -102,6 +167,8 @@ mod models {
         stake: BigFloat(Decimal::ZERO),
         lottery_power: 0.0,
         pending_update,
+        staking: StakingKind::None,
+        pool_id: None,
       }
     }
   }
@@ -136,6 +203,8 @@ mod models {
   pub enum RewardKind {
     Baker,
     TransactionFee,
+    Finalization,
+    Delegation,
   }
 
   impl From<RewardKind> for model::RewardKind {
@@ -144,6 +213,8 @@ mod models {
       match kind {
         RewardKind::Baker => Self::Baker,
         RewardKind::TransactionFee => Self::TransactionFee,
+        RewardKind::Finalization => Self::Finalization,
+        RewardKind::Delegation => Self::Delegation,
       }
     }
   }
@@ -153,6 +224,8 @@ mod models {
       let e = match self {
         Self::Baker => REWARD_KIND_BAKER,
         Self::TransactionFee => REWARD_KIND_TRANSACTION_FEE,
+        Self::Finalization => REWARD_KIND_FINALIZATION,
+        Self::Delegation => REWARD_KIND_DELEGATION,
       };
 
       <str as se::ToSql<Text, Sqlite>>::to_sql(e, out)
@@ -164,6 +237,8 @@ mod models {
       match <String as de::FromSql<Text, Sqlite>>::from_sql(value)?.as_str() {
         REWARD_KIND_BAKER => Ok(RewardKind::Baker),
         REWARD_KIND_TRANSACTION_FEE => Ok(RewardKind::TransactionFee),
+        REWARD_KIND_FINALIZATION => Ok(RewardKind::Finalization),
+        REWARD_KIND_DELEGATION => Ok(RewardKind::Delegation),
         x => Err(format!("unrecognized value for enum: {}", x).into()),
       }
     }
@@ -178,6 +253,100 @@ mod models {
     pub epoch_ms: i64,
     pub kind: RewardKind,
   }
+
+  /// Record of a transaction that touches an account.
+  #[derive(Queryable)]
+  pub struct Transaction {
+    pub id: i32,
+    pub account_id: i32,
+    pub block_hash: String,
+    pub transaction_hash: String,
+    pub epoch_ms: i64,
+    pub kind: String,
+    pub amount: BigFloat,
+    pub counterparty: Option<String>,
+    pub cost: BigFloat,
+  }
+
+  impl From<Transaction> for model::Transaction {
+    fn from(record: Transaction) -> Self {
+      Self::new(
+        record.id,
+        record.account_id,
+        &record.block_hash,
+        &record.transaction_hash,
+        record.epoch_ms,
+        &record.kind,
+      )
+      .with_transfer(record.amount.0, record.counterparty)
+      .with_cost(record.cost.0)
+    }
+  }
+
+  #[derive(Insertable, Debug)]
+  #[diesel(table_name = account_transactions)]
+  pub struct NewTransaction {
+    pub account_id: i32,
+    pub block_hash: String,
+    pub transaction_hash: String,
+    pub epoch_ms: i64,
+    pub kind: String,
+    pub amount: BigFloat,
+    pub counterparty: Option<String>,
+    pub cost: BigFloat,
+  }
+
+  /// A page of the transactions of an account, starting from the most recent
+  /// one.
+  #[derive(PartialEq, Debug)]
+  pub struct TransactionFilter {
+    pub account_id: i32,
+    pub offset: i64,
+    pub limit: i64,
+  }
+
+  /// Record of a snapshot of the state of an account.
+  #[derive(Queryable)]
+  pub struct Snapshot {
+    pub id: i32,
+    pub account_id: i32,
+    pub epoch_ms: i64,
+    pub balance: BigFloat,
+    pub stake: BigFloat,
+    pub lottery_power: f64,
+  }
+
+  impl From<Snapshot> for model::Snapshot {
+    fn from(record: Snapshot) -> Self {
+      Self::new(
+        record.id,
+        record.account_id,
+        record.epoch_ms,
+        record.balance.0,
+        record.stake.0,
+        record.lottery_power,
+      )
+    }
+  }
+
+  #[derive(Insertable, Debug)]
+  #[diesel(table_name = account_snapshots)]
+  pub struct NewSnapshot {
+    pub account_id: i32,
+    pub epoch_ms: i64,
+    pub balance: BigFloat,
+    pub stake: BigFloat,
+    pub lottery_power: f64,
+  }
+
+  /// A time window of the snapshots of an account, where both bounds are
+  /// included.
+  #[derive(PartialEq, Debug)]
+  pub struct SnapshotFilter {
+    pub account_id: i32,
+    pub from_ms: i64,
+    pub to_ms: i64,
+  }
 }
 
 #[cfg_attr(test, mockall::automock)]
@@ -197,10 +366,36 @@ pub trait AccountRepository {
   /// identity it.
   async fn get_rewards(&self, account: &Account) -> Result<Vec<Reward>>;
 
+  /// It returns the time of the most recent reward of an account, or nothing
+  /// when it has not received any reward yet.
+  async fn get_last_reward_ms(&self, account: &Account) -> Result<Option<i64>>;
+
   /// It creates an account reward if it does not exist already. The reward is
   /// identified by the account, the block and its kind.
   async fn set_reward(&self, reward: NewReward) -> Result<()>;
 
+  /// It returns a page of the transactions of an account, starting from the
+  /// most recent one.
+  async fn get_transactions(&self, filter: TransactionFilter) -> Result<Vec<Transaction>>;
+
+  /// It creates a transaction of an account if it does not exist already. The
+  /// transaction is identified by the account and its hash.
+  async fn set_transaction(&self, transaction: NewTransaction) -> Result<()>;
+
+  /// It returns the snapshots of an account in the time window, starting from
+  /// the oldest one.
+  async fn get_snapshots(&self, filter: SnapshotFilter) -> Result<Vec<Snapshot>>;
+
+  /// It stores a new snapshot of an account.
+  async fn add_snapshot(&self, snapshot: NewSnapshot) -> Result<()>;
+
+  /// It keeps only the most recent snapshot of each interval for the snapshots
+  /// taken before the given time.
+  async fn downsample_snapshots(&self, before_ms: i64, interval_ms: i64) -> Result<()>;
+
+  /// It deletes the snapshots taken before the given time.
+  async fn delete_snapshots(&self, before_ms: i64) -> Result<()>;
+
   /// It sets the given either into pending for update, or inversely switch them off.
   async fn set_for_update(&self, addrs: Vec<String>, pending: bool) -> Result<()>;
 
@@ -234,13 +429,7 @@ impl AccountRepository for SqliteAccountRepository {
       .exec(|mut conn| accounts.filter(id.eq(account)).first(&mut conn))
       .await?;
 
-    Ok(Account::new(
-      record.id,
-      &record.address,
-      record.balance.0,
-      record.stake.0,
-      record.lottery_power,
-    ))
+    Ok(Account::from(record))
   }
 
   /// It returns the list of accounts associated with the addresses.
@@ -295,6 +484,23 @@ impl AccountRepository for SqliteAccountRepository {
     Ok(res.into_iter().map(Reward::from).collect())
   }
 
+  /// It returns the time of the most recent reward of an account.
+  async fn get_last_reward_ms(&self, account: &Account) -> Result<Option<i64>> {
+    let account_id = account.get_id();
+
+    let res = self
+      .pool
+      .exec(move |mut conn| {
+        reward_dsl::account_rewards
+          .filter(reward_dsl::account_id.eq(account_id))
+          .select(diesel::dsl::max(reward_dsl::epoch_ms))
+          .first(&mut conn)
+      })
+      .await?;
+
+    Ok(res)
+  }
+
   /// It creates an account reward if it does not exist already. The reward is identified by the
   /// account, the block and its kind.
   async fn set_reward(&self, reward: NewReward) -> Result<()> {
@@ -313,6 +519,117 @@ impl AccountRepository for SqliteAccountRepository {
     Ok(())
   }
 
+  /// It returns a page of the transactions of an account, starting from the
+  /// most recent one.
+  async fn get_transactions(&self, filter: TransactionFilter) -> Result<Vec<Transaction>> {
+    let records: Vec<models::Transaction> = self
+      .pool
+      .exec(move |mut conn| {
+        transaction_dsl::account_transactions
+          .filter(transaction_dsl::account_id.eq(filter.account_id))
+          .order_by((transaction_dsl::epoch_ms.desc(), transaction_dsl::id.desc()))
+          .offset(filter.offset)
+          .limit(filter.limit)
+          .load(&mut conn)
+      })
+      .await?;
+
+    Ok(records.into_iter().map(Transaction::from).collect())
+  }
+
+  /// It creates a transaction of an account if it does not exist already. The
+  /// transaction is identified by the account and its hash.
+  async fn set_transaction(&self, transaction: NewTransaction) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        diesel::insert_into(transaction_dsl::account_transactions)
+          .values(&transaction)
+          .on_conflict((transaction_dsl::account_id, transaction_dsl::transaction_hash))
+          .do_nothing()
+          .execute(&mut conn)
+      })
+      .await?;
+
+    Ok(())
+  }
+
+  /// It returns the snapshots of an account in the time window, starting from
+  /// the oldest one.
+  async fn get_snapshots(&self, filter: SnapshotFilter) -> Result<Vec<Snapshot>> {
+    let records: Vec<models::Snapshot> = self
+      .pool
+      .exec(move |mut conn| {
+        snapshot_dsl::account_snapshots
+          .filter(snapshot_dsl::account_id.eq(filter.account_id))
+          .filter(snapshot_dsl::epoch_ms.between(filter.from_ms, filter.to_ms))
+          .order_by((snapshot_dsl::epoch_ms, snapshot_dsl::id))
+          .load(&mut conn)
+      })
+      .await?;
+
+    Ok(records.into_iter().map(Snapshot::from).collect())
+  }
+
+  /// It stores a new snapshot of an account.
+  async fn add_snapshot(&self, snapshot: NewSnapshot) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        diesel::insert_into(snapshot_dsl::account_snapshots)
+          .values(&snapshot)
+          .execute(&mut conn)
+      })
+      .await?;
+
+    Ok(())
+  }
+
+  /// It keeps only the most recent snapshot of each interval for the snapshots
+  /// taken before the given time.
+  async fn downsample_snapshots(&self, before_ms: i64, interval_ms: i64) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        conn.transaction(|tx| {
+          let records: Vec<(i32, i32, i64)> = snapshot_dsl::account_snapshots
+            .select((snapshot_dsl::id, snapshot_dsl::account_id, snapshot_dsl::epoch_ms))
+            .filter(snapshot_dsl::epoch_ms.lt(before_ms))
+            .order_by((snapshot_dsl::account_id, snapshot_dsl::epoch_ms, snapshot_dsl::id))
+            .load(tx)?;
+
+          // A snapshot is deleted when the next one of the account belongs to
+          // the same interval.
+          let ids: Vec<i32> = records
+            .windows(2)
+            .filter(|w| w[0].1 == w[1].1 && w[0].2.div_euclid(interval_ms) == w[1].2.div_euclid(interval_ms))
+            .map(|w| w[0].0)
+            .collect();
+
+          for chunk in ids.chunks(DELETE_CHUNK_SIZE) {
+            diesel::delete(snapshot_dsl::account_snapshots.filter(snapshot_dsl::id.eq_any(chunk))).execute(tx)?;
+          }
+
+          Ok(())
+        })
+      })
+      .await?;
+
+    Ok(())
+  }
+
+  /// It deletes the snapshots taken before the given time.
+  async fn delete_snapshots(&self, before_ms: i64) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        diesel::delete(snapshot_dsl::account_snapshots.filter(snapshot_dsl::epoch_ms.lt(before_ms))).execute(&mut conn)
+      })
+      .await?;
+
+    Ok(())
+  }
+
   /// It sets the given either into pending for update, or inversely switch them off.
   async fn set_for_update(&self, addrs: Vec<String>, pending: bool) -> Result<()> {
     self
@@ -342,6 +659,7 @@ impl AccountRepository for SqliteAccountRepository {
 mod tests {
   use super::*;
   use crate::repository::{AsyncPool, RepositoryError};
+  use rust_decimal::Decimal;
   use rust_decimal_macros::dec;
 
   #[tokio::test(flavor = "multi_thread")]
@@ -356,6 +674,8 @@ mod tests {
       stake: dec!(50).into(),
       lottery_power: 0.0123,
       pending_update: false,
+      staking: StakingKind::Delegator,
+      pool_id: Some(42),
     };
 
     let repository = SqliteAccountRepository::new(pool);
@@ -368,6 +688,17 @@ mod tests {
 
     assert_eq!(1, res.get_id());
     assert_eq!(":address:", res.get_address());
+    assert_eq!(crate::model::StakingKind::Delegator, res.get_staking());
+    assert_eq!(Some(42), res.get_pool_id());
+
+    // 3. Move the stake to the passive delegation.
+    let mut account = NewAccount::new(":address:", false);
+    account.staking = StakingKind::Delegator;
+
+    let res = repository.set_account(account).await.unwrap();
+
+    assert_eq!(crate::model::StakingKind::Delegator, res.get_staking());
+    assert_eq!(None, res.get_pool_id());
   }
 
   #[tokio::test(flavor = "multi_thread")]
@@ -393,6 +724,8 @@ mod tests {
       stake: dec!(50).into(),
       lottery_power: 0.096,
       pending_update: false,
+      staking: StakingKind::Baker,
+      pool_id: Some(1),
     };
 
     let res = repository.set_account(account).await;
@@ -473,9 +806,55 @@ mod tests {
       .await
       .unwrap();
 
-    let res = repository.get_rewards(&account).await;
+    repository
+      .set_reward(NewReward {
+        account_id: account.get_id(),
+        block_hash: ":hash:".to_string(),
+        amount: dec!(12).into(),
+        epoch_ms: 0,
+        kind: RewardKind::Finalization,
+      })
+      .await
+      .unwrap();
+
+    let res = repository.get_rewards(&account).await.unwrap();
+
+    assert_eq!(3, res.len());
+    let kinds: Vec<_> = res.iter().map(|r| r.get_kind()).collect();
+    assert!(kinds.contains(&&crate::model::RewardKind::Finalization));
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_get_last_reward_ms() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAccountRepository::new(pool);
+
+    repository
+      .set_account(NewAccount::new(":address:", false))
+      .await
+      .unwrap();
+
+    let account = repository.get_account(1).await.unwrap();
+
+    assert_eq!(None, repository.get_last_reward_ms(&account).await.unwrap());
 
-    assert!(matches!(res, Ok(rewards) if rewards.len() == 2));
+    for (hash, epoch_ms) in [(":hash-1:", 2000), (":hash-2:", 3000), (":hash-3:", 1000)] {
+      repository
+        .set_reward(NewReward {
+          account_id: account.get_id(),
+          block_hash: hash.to_string(),
+          amount: dec!(125).into(),
+          epoch_ms,
+          kind: RewardKind::Baker,
+        })
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(Some(3000), repository.get_last_reward_ms(&account).await.unwrap());
   }
 
   #[tokio::test(flavor = "multi_thread")]
@@ -512,6 +891,175 @@ mod tests {
     assert!(matches!(res, Err(RepositoryError::Faillable(_))), "value: {:?}", res);
   }
 
+  fn new_transaction(hash: &str, epoch_ms: i64, amount: i64) -> NewTransaction {
+    NewTransaction {
+      account_id: 1,
+      block_hash: ":block:".to_string(),
+      transaction_hash: hash.to_string(),
+      epoch_ms,
+      kind: "transfer".to_string(),
+      amount: Decimal::from(amount).into(),
+      counterparty: Some(":address-2:".to_string()),
+      cost: dec!(0).into(),
+    }
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_get_transactions() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAccountRepository::new(pool);
+
+    repository.set_account(NewAccount::new(":address-1:", false)).await?;
+
+    repository.set_transaction(new_transaction(":tx-1:", 1000, 250)).await?;
+    repository.set_transaction(new_transaction(":tx-2:", 3000, -50)).await?;
+    repository.set_transaction(new_transaction(":tx-3:", 2000, 10)).await?;
+
+    // A transaction already known is ignored.
+    repository.set_transaction(new_transaction(":tx-1:", 1000, 999)).await?;
+
+    let res = repository
+      .get_transactions(TransactionFilter {
+        account_id: 1,
+        offset: 0,
+        limit: 2,
+      })
+      .await?;
+
+    let hashes: Vec<_> = res
+      .iter()
+      .map(|t| serde_json::to_value(t).unwrap()["transaction_hash"].clone())
+      .collect();
+
+    assert_eq!(vec![":tx-2:", ":tx-3:"], hashes);
+
+    let res = repository
+      .get_transactions(TransactionFilter {
+        account_id: 1,
+        offset: 2,
+        limit: 2,
+      })
+      .await?;
+
+    assert_eq!(1, res.len());
+    assert_eq!("250", serde_json::to_value(&res[0]).unwrap()["amount"]);
+
+    Ok(())
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_set_transaction_failure() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAccountRepository::new(pool);
+
+    let res = repository.set_transaction(new_transaction(":tx-1:", 1000, 250)).await;
+
+    assert!(matches!(res, Err(RepositoryError::Faillable(_))));
+  }
+
+  fn new_snapshot(account_id: i32, epoch_ms: i64) -> NewSnapshot {
+    NewSnapshot {
+      account_id,
+      epoch_ms,
+      balance: dec!(100).into(),
+      stake: Decimal::from(epoch_ms).into(),
+      lottery_power: 0.01,
+    }
+  }
+
+  fn epochs(snapshots: &[Snapshot]) -> Vec<i64> {
+    snapshots.iter().map(|s| s.get_epoch_ms()).collect()
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_get_snapshots() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAccountRepository::new(pool);
+
+    repository.set_account(NewAccount::new(":address-1:", false)).await?;
+    repository.set_account(NewAccount::new(":address-2:", false)).await?;
+
+    repository.add_snapshot(new_snapshot(1, 3000)).await?;
+    repository.add_snapshot(new_snapshot(1, 1000)).await?;
+    repository.add_snapshot(new_snapshot(1, 2000)).await?;
+    repository.add_snapshot(new_snapshot(2, 2000)).await?;
+
+    let res = repository
+      .get_snapshots(SnapshotFilter {
+        account_id: 1,
+        from_ms: 1000,
+        to_ms: 2000,
+      })
+      .await?;
+
+    assert_eq!(vec![1000, 2000], epochs(&res));
+    assert_eq!("2000", serde_json::to_value(&res[1]).unwrap()["stake"]);
+
+    Ok(())
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_downsample_and_delete_snapshots() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAccountRepository::new(pool);
+
+    repository.set_account(NewAccount::new(":address-1:", false)).await?;
+    repository.set_account(NewAccount::new(":address-2:", false)).await?;
+
+    for epoch_ms in [100, 150, 199, 200, 250, 300, 350] {
+      repository.add_snapshot(new_snapshot(1, epoch_ms)).await?;
+    }
+
+    repository.add_snapshot(new_snapshot(2, 120)).await?;
+
+    // The snapshots before 300 are reduced to one per interval of 100.
+    repository.downsample_snapshots(300, 100).await?;
+
+    let filter = |account_id| SnapshotFilter {
+      account_id,
+      from_ms: 0,
+      to_ms: 1000,
+    };
+
+    assert_eq!(
+      vec![199, 250, 300, 350],
+      epochs(&repository.get_snapshots(filter(1)).await?)
+    );
+    assert_eq!(vec![120], epochs(&repository.get_snapshots(filter(2)).await?));
+
+    repository.delete_snapshots(250).await?;
+
+    assert_eq!(vec![250, 300, 350], epochs(&repository.get_snapshots(filter(1)).await?));
+    assert!(repository.get_snapshots(filter(2)).await?.is_empty());
+
+    Ok(())
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_add_snapshot_failure() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAccountRepository::new(pool);
+
+    let res = repository.add_snapshot(new_snapshot(1, 1000)).await;
+
+    assert!(matches!(res, Err(RepositoryError::Faillable(_))));
+  }
+
   #[tokio::test(flavor = "multi_thread")]
   async fn test_set_pending() -> Result<()> {
     let pool = AsyncPool::open(":memory:").unwrap();
@@ -0,0 +1,525 @@
+use super::{AsyncPool, RepositoryError, Result};
+use crate::model::{Alert, Silence};
+use crate::schema::alerts::dsl as alert_dsl;
+use crate::schema::silences::dsl as silence_dsl;
+use diesel::prelude::*;
+use std::sync::Arc;
+
+use models::AlertState;
+
+pub use models::{AlertFilter, NewAlert, NewSilence};
+
+mod models {
+  use crate::model;
+  use crate::schema::{alerts, silences};
+  use diesel::backend;
+  use diesel::deserialize as de;
+  use diesel::serialize as se;
+  use diesel::sql_types::Text;
+  use diesel::sqlite::Sqlite;
+
+  const ALERT_STATE_FIRING: &str = "firing";
+  const ALERT_STATE_RESOLVED: &str = "resolved";
+
+  // A enumeration of the possible states of an alert.
+  #[derive(AsExpression, FromSqlRow, PartialEq, Clone, Copy, Debug)]
+  #[diesel(sql_type = Text)]
+  pub enum AlertState {
+    Firing,
+    Resolved,
+  }
+
+  impl se::ToSql<Text, Sqlite> for AlertState {
+    fn to_sql(&self, out: &mut se::Output<Sqlite>) -> se::Result {
+      let e = match self {
+        Self::Firing => ALERT_STATE_FIRING,
+        Self::Resolved => ALERT_STATE_RESOLVED,
+      };
+
+      <str as se::ToSql<Text, Sqlite>>::to_sql(e, out)
+    }
+  }
+
+  impl From<model::AlertState> for AlertState {
+    fn from(state: model::AlertState) -> Self {
+      match state {
+        model::AlertState::Firing => Self::Firing,
+        model::AlertState::Resolved => Self::Resolved,
+      }
+    }
+  }
+
+  impl de::FromSql<Text, Sqlite> for AlertState {
+    fn from_sql(value: backend::RawValue<Sqlite>) -> de::Result<Self> {
+      match <String as de::FromSql<Text, Sqlite>>::from_sql(value)?.as_str() {
+        ALERT_STATE_FIRING => Ok(AlertState::Firing),
+        ALERT_STATE_RESOLVED => Ok(AlertState::Resolved),
+        x => Err(format!("unrecognized value for enum: {}", x).into()),
+      }
+    }
+  }
+
+  /// Record of an alert fired by a rule.
+  #[derive(Queryable)]
+  pub struct Alert {
+    pub id: i32,
+    pub rule: String,
+    pub subject: String,
+    pub message: String,
+    pub state: AlertState,
+    pub fired_ms: i64,
+    pub resolved_ms: Option<i64>,
+    pub acked_ms: Option<i64>,
+    pub acked_by: Option<i32>,
+  }
+
+  impl From<Alert> for model::Alert {
+    fn from(record: Alert) -> Self {
+      let alert = Self::new(
+        record.id,
+        &record.rule,
+        &record.subject,
+        &record.message,
+        record.fired_ms,
+      );
+
+      let alert = match (record.state, record.resolved_ms) {
+        (AlertState::Resolved, Some(resolved_ms)) => alert.with_resolution(resolved_ms),
+        _ => alert,
+      };
+
+      match record.acked_ms {
+        Some(acked_ms) => alert.with_ack(acked_ms, record.acked_by),
+        None => alert,
+      }
+    }
+  }
+
+  #[derive(Insertable, PartialEq, Debug)]
+  #[diesel(table_name = alerts)]
+  pub struct NewAlert {
+    pub rule: String,
+    pub subject: String,
+    pub message: String,
+    pub state: AlertState,
+    pub fired_ms: i64,
+  }
+
+  impl NewAlert {
+    /// It creates an alert firing from the given time.
+    pub fn firing(rule: &str, subject: &str, message: &str, fired_ms: i64) -> Self {
+      Self {
+        rule: rule.to_string(),
+        subject: subject.to_string(),
+        message: message.to_string(),
+        state: AlertState::Firing,
+        fired_ms,
+      }
+    }
+  }
+
+  /// A filter of the alerts by state and by the time they fired.
+  #[derive(Default, PartialEq, Debug)]
+  pub struct AlertFilter {
+    pub state: Option<model::AlertState>,
+    pub since_ms: Option<i64>,
+    pub until_ms: Option<i64>,
+  }
+
+  /// Record of a silence of the notifications of some alerts.
+  #[derive(Queryable)]
+  pub struct Silence {
+    pub id: i32,
+    pub rule: Option<String>,
+    pub subject: Option<String>,
+    pub reason: String,
+    pub created_by: Option<i32>,
+    pub starts_ms: i64,
+    pub ends_ms: i64,
+  }
+
+  impl From<Silence> for model::Silence {
+    fn from(record: Silence) -> Self {
+      Self::new(
+        record.id,
+        record.rule.as_deref(),
+        record.subject.as_deref(),
+        &record.reason,
+        record.starts_ms,
+        record.ends_ms,
+      )
+      .with_creator(record.created_by)
+    }
+  }
+
+  #[derive(Insertable, PartialEq, Debug)]
+  #[diesel(table_name = silences)]
+  pub struct NewSilence {
+    pub rule: Option<String>,
+    pub subject: Option<String>,
+    pub reason: String,
+    pub created_by: Option<i32>,
+    pub starts_ms: i64,
+    pub ends_ms: i64,
+  }
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait AlertRepository {
+  /// It returns the alerts that are currently firing.
+  async fn get_firing_alerts(&self) -> Result<Vec<Alert>>;
+
+  /// It stores a new alert and returns it. It fails when an alert is already
+  /// firing for the same rule and subject.
+  async fn fire_alert(&self, alert: NewAlert) -> Result<Alert>;
+
+  /// It marks the alert as resolved at the given time.
+  async fn resolve_alert(&self, alert_id: i32, resolved_at: i64) -> Result<()>;
+
+  /// It returns the alerts matching the filter, starting from the most recent
+  /// one.
+  async fn get_alerts(&self, filter: AlertFilter) -> Result<Vec<Alert>>;
+
+  /// It records that a user acknowledged the alert and returns it. The first
+  /// acknowledgement is kept.
+  async fn ack_alert(&self, alert_id: i32, user_id: Option<i32>, acked_at: i64) -> Result<Alert>;
+
+  /// It stores a new silence and returns it.
+  async fn create_silence(&self, silence: NewSilence) -> Result<Silence>;
+
+  /// It returns the silences that are not over at the given time, including
+  /// the ones starting later.
+  async fn get_silences(&self, at_ms: i64) -> Result<Vec<Silence>>;
+
+  /// It deletes a silence, or fails when it does not exist.
+  async fn delete_silence(&self, silence_id: i32) -> Result<()>;
+}
+
+/// An alias of a singleton of an alert repository shared in the application.
+pub type DynAlertRepository = Arc<dyn AlertRepository + Send + Sync>;
+
+pub struct SqliteAlertRepository {
+  pool: AsyncPool,
+}
+
+impl SqliteAlertRepository {
+  pub fn new(pool: AsyncPool) -> Self {
+    Self {
+      pool,
+    }
+  }
+}
+
+#[async_trait]
+impl AlertRepository for SqliteAlertRepository {
+  async fn get_firing_alerts(&self) -> Result<Vec<Alert>> {
+    let res: Vec<models::Alert> = self
+      .pool
+      .exec(|mut conn| {
+        alert_dsl::alerts
+          .filter(alert_dsl::state.eq(AlertState::Firing))
+          .order_by(alert_dsl::fired_ms)
+          .load(&mut conn)
+      })
+      .await?;
+
+    Ok(res.into_iter().map(Alert::from).collect())
+  }
+
+  async fn fire_alert(&self, alert: NewAlert) -> Result<Alert> {
+    let res: models::Alert = self
+      .pool
+      .exec(move |mut conn| {
+        conn.transaction(|tx| {
+          diesel::insert_into(alert_dsl::alerts).values(&alert).execute(tx)?;
+
+          alert_dsl::alerts.order_by(alert_dsl::id.desc()).first(tx)
+        })
+      })
+      .await?;
+
+    Ok(Alert::from(res))
+  }
+
+  async fn resolve_alert(&self, alert_id: i32, resolved_at: i64) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        diesel::update(alert_dsl::alerts.filter(alert_dsl::id.eq(alert_id)))
+          .set((
+            alert_dsl::state.eq(AlertState::Resolved),
+            alert_dsl::resolved_ms.eq(resolved_at),
+          ))
+          .execute(&mut conn)
+      })
+      .await?;
+
+    Ok(())
+  }
+
+  async fn get_alerts(&self, filter: AlertFilter) -> Result<Vec<Alert>> {
+    let res: Vec<models::Alert> = self
+      .pool
+      .exec(move |mut conn| {
+        let mut query = alert_dsl::alerts.into_boxed();
+
+        if let Some(state) = filter.state {
+          query = query.filter(alert_dsl::state.eq(AlertState::from(state)));
+        }
+
+        if let Some(since_ms) = filter.since_ms {
+          query = query.filter(alert_dsl::fired_ms.ge(since_ms));
+        }
+
+        if let Some(until_ms) = filter.until_ms {
+          query = query.filter(alert_dsl::fired_ms.lt(until_ms));
+        }
+
+        query
+          .order_by((alert_dsl::fired_ms.desc(), alert_dsl::id.desc()))
+          .load(&mut conn)
+      })
+      .await?;
+
+    Ok(res.into_iter().map(Alert::from).collect())
+  }
+
+  async fn ack_alert(&self, alert_id: i32, user_id: Option<i32>, acked_at: i64) -> Result<Alert> {
+    let res: models::Alert = self
+      .pool
+      .exec(move |mut conn| {
+        conn.transaction(|tx| {
+          diesel::update(
+            alert_dsl::alerts
+              .filter(alert_dsl::id.eq(alert_id))
+              .filter(alert_dsl::acked_ms.is_null()),
+          )
+          .set((alert_dsl::acked_ms.eq(acked_at), alert_dsl::acked_by.eq(user_id)))
+          .execute(tx)?;
+
+          alert_dsl::alerts.find(alert_id).first(tx)
+        })
+      })
+      .await?;
+
+    Ok(Alert::from(res))
+  }
+
+  async fn create_silence(&self, silence: NewSilence) -> Result<Silence> {
+    let res: models::Silence = self
+      .pool
+      .exec(move |mut conn| {
+        conn.transaction(|tx| {
+          diesel::insert_into(silence_dsl::silences)
+            .values(&silence)
+            .execute(tx)?;
+
+          silence_dsl::silences.order_by(silence_dsl::id.desc()).first(tx)
+        })
+      })
+      .await?;
+
+    Ok(Silence::from(res))
+  }
+
+  async fn get_silences(&self, at_ms: i64) -> Result<Vec<Silence>> {
+    let res: Vec<models::Silence> = self
+      .pool
+      .exec(move |mut conn| {
+        silence_dsl::silences
+          .filter(silence_dsl::ends_ms.gt(at_ms))
+          .order_by(silence_dsl::starts_ms)
+          .load(&mut conn)
+      })
+      .await?;
+
+    Ok(res.into_iter().map(Silence::from).collect())
+  }
+
+  async fn delete_silence(&self, silence_id: i32) -> Result<()> {
+    let count = self
+      .pool
+      .exec(move |mut conn| {
+        diesel::delete(silence_dsl::silences.filter(silence_dsl::id.eq(silence_id))).execute(&mut conn)
+      })
+      .await?;
+
+    if count == 0 {
+      return Err(RepositoryError::NotFound);
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::model::AlertState as State;
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_fire_and_resolve_alert() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAlertRepository::new(pool);
+
+    let alert = repository
+      .fire_alert(NewAlert::firing("node-down", "node", "the node is unreachable", 1000))
+      .await?;
+
+    assert_eq!("node-down", alert.get_rule());
+
+    let res = repository.get_firing_alerts().await?;
+
+    assert_eq!(1, res.len());
+
+    repository.resolve_alert(alert.get_id(), 2000).await?;
+
+    let res = repository.get_firing_alerts().await?;
+
+    assert_eq!(0, res.len());
+
+    // The alert can fire again once resolved.
+    repository
+      .fire_alert(NewAlert::firing("node-down", "node", "the node is unreachable", 3000))
+      .await?;
+
+    assert_eq!(1, repository.get_firing_alerts().await?.len());
+
+    Ok(())
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_fire_alert_duplicate() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAlertRepository::new(pool);
+
+    repository
+      .fire_alert(NewAlert::firing("low-peers", "node", "2 peers", 1000))
+      .await?;
+
+    let res = repository
+      .fire_alert(NewAlert::firing("low-peers", "node", "1 peer", 2000))
+      .await;
+
+    assert!(matches!(res, Err(RepositoryError::Faillable(_))));
+
+    Ok(())
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_get_and_ack_alerts() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAlertRepository::new(pool);
+
+    let alert = repository
+      .fire_alert(NewAlert::firing("node-down", "node", "the node is unreachable", 1000))
+      .await?;
+
+    repository.resolve_alert(alert.get_id(), 1500).await?;
+
+    repository
+      .fire_alert(NewAlert::firing("low-peers", "node", "1 peer", 2000))
+      .await?;
+
+    let all = repository.get_alerts(AlertFilter::default()).await?;
+
+    assert_eq!(
+      vec!["low-peers", "node-down"],
+      all.iter().map(|a| a.get_rule()).collect::<Vec<_>>()
+    );
+
+    let firing = repository
+      .get_alerts(AlertFilter {
+        state: Some(State::Firing),
+        ..AlertFilter::default()
+      })
+      .await?;
+
+    assert_eq!(1, firing.len());
+    assert_eq!("low-peers", firing[0].get_rule());
+
+    let window = repository
+      .get_alerts(AlertFilter {
+        state: None,
+        since_ms: Some(500),
+        until_ms: Some(2000),
+      })
+      .await?;
+
+    assert_eq!(1, window.len());
+    assert_eq!("node-down", window[0].get_rule());
+
+    let expected = format!(
+      "{:?}",
+      Alert::new(1, "node-down", "node", "the node is unreachable", 1000)
+        .with_resolution(1500)
+        .with_ack(3000, Some(42))
+    );
+
+    let acked = repository.ack_alert(alert.get_id(), Some(42), 3000).await?;
+
+    assert_eq!(expected, format!("{:?}", acked));
+
+    // The first acknowledgement is kept.
+    let acked = repository.ack_alert(alert.get_id(), Some(7), 4000).await?;
+
+    assert_eq!(expected, format!("{:?}", acked));
+
+    let res = repository.ack_alert(42, Some(42), 3000).await;
+
+    assert!(matches!(res, Err(RepositoryError::NotFound)));
+
+    Ok(())
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_silences() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteAlertRepository::new(pool);
+
+    let silence = repository
+      .create_silence(NewSilence {
+        rule: Some("low-peers".to_string()),
+        subject: None,
+        reason: "maintenance".to_string(),
+        created_by: Some(1),
+        starts_ms: 1000,
+        ends_ms: 2000,
+      })
+      .await?;
+
+    assert_eq!(
+      format!(
+        "{:?}",
+        Silence::new(1, Some("low-peers"), None, "maintenance", 1000, 2000).with_creator(Some(1))
+      ),
+      format!("{:?}", silence)
+    );
+
+    assert_eq!(1, repository.get_silences(1500).await?.len());
+    assert_eq!(0, repository.get_silences(2000).await?.len());
+
+    repository.delete_silence(1).await?;
+
+    assert_eq!(0, repository.get_silences(1500).await?.len());
+
+    let res = repository.delete_silence(1).await;
+
+    assert!(matches!(res, Err(RepositoryError::NotFound)));
+
+    Ok(())
+  }
+}
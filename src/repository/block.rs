@@ -19,11 +19,13 @@ mod models {
     pub hash: String,
     pub slot_time_ms: i64,
     pub baker: i64,
+    pub genesis_index: i32,
+    pub protocol_version: i32,
   }
 
   impl From<Block> for model::Block {
     fn from(b: Block) -> Self {
-      Self::new(b.id, b.height, &b.hash, b.slot_time_ms, b.baker)
+      Self::new(b.id, b.height, &b.hash, b.slot_time_ms, b.baker).with_era(b.genesis_index, b.protocol_version)
     }
   }
 
@@ -34,6 +36,8 @@ mod models {
     pub hash: String,
     pub slot_time_ms: i64,
     pub baker: i64,
+    pub genesis_index: i32,
+    pub protocol_version: i32,
   }
 
   #[derive(PartialEq, Debug)]
@@ -150,19 +154,25 @@ mod tests {
 
     let repository = SqliteBlockRepository::new(pool);
 
+    assert!(matches!(
+      repository.get_last_block().await,
+      Err(RepositoryError::NotFound)
+    ));
+
     let new_block = models::NewBlock {
-      // Previous block is inserted by the migration.
       height: 2840312,
       hash: ":hash:".to_string(),
       slot_time_ms: 123,
       baker: 2,
+      genesis_index: 4,
+      protocol_version: 5,
     };
 
     assert!(matches!(repository.store(new_block).await, Ok(_)));
 
     let res = repository.get_last_block().await;
 
-    assert!(matches!(res, Ok(block) if block.get_height() == 2840312));
+    assert!(matches!(res, Ok(block) if block == Block::new(2, 2840312, ":hash:", 123, 2).with_era(4, 5)));
   }
 
   #[tokio::test(flavor = "multi_thread")]
@@ -190,18 +200,24 @@ mod tests {
         hash: ":hash-block-1:".into(),
         slot_time_ms: 1000,
         baker: 42,
+        genesis_index: 0,
+        protocol_version: 1,
       },
       models::NewBlock {
         height: 2,
         hash: ":hash-block-2:".into(),
         slot_time_ms: 1200,
         baker: 43,
+        genesis_index: 0,
+        protocol_version: 1,
       },
       models::NewBlock {
         height: 3,
         hash: ":hash-block-3:".into(),
         slot_time_ms: 1500,
         baker: 42,
+        genesis_index: 0,
+        protocol_version: 1,
       },
     ];
 
@@ -225,7 +241,7 @@ mod tests {
       })
       .await;
 
-    assert!(matches!(&res, Ok(bb) if bb.len() == 4), "wrong result: {:?}", res);
+    assert!(matches!(&res, Ok(bb) if bb.len() == 3), "wrong result: {:?}", res);
   }
 
   #[tokio::test(flavor = "multi_thread")]
@@ -257,6 +273,8 @@ mod tests {
       hash: ":hash-block-1:".into(),
       slot_time_ms: 1000,
       baker: 42,
+      genesis_index: 0,
+      protocol_version: 1,
     };
 
     let res = repository.store(new_block.clone()).await;
@@ -276,6 +294,17 @@ mod tests {
 
     let repository = SqliteBlockRepository::new(pool);
 
+    let new_block = models::NewBlock {
+      height: 2840311,
+      hash: ":hash:".to_string(),
+      slot_time_ms: 123,
+      baker: 2,
+      genesis_index: 0,
+      protocol_version: 1,
+    };
+
+    assert!(matches!(repository.store(new_block).await, Ok(_)));
+
     assert!(matches!(repository.garbage_collect(2840312).await, Ok(_)));
 
     assert!(matches!(
@@ -0,0 +1,147 @@
+use super::{AsyncPool, Result};
+use crate::schema::block_cache::dsl::*;
+use diesel::prelude::*;
+use std::sync::Arc;
+
+pub use models::NewCachedBlock;
+
+mod models {
+  use crate::schema::block_cache;
+
+  #[derive(Insertable)]
+  #[diesel(table_name = block_cache)]
+  pub struct NewCachedBlock {
+    pub block_hash: String,
+    pub kind: String,
+    pub content: String,
+  }
+}
+
+/// A repository to persist the data of finalized blocks fetched from the node
+/// so that it survives a restart of the application.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait BlockCacheRepository {
+  /// It returns the content of a kind of data stored for the block, if any.
+  async fn get(&self, hash: &str, data_kind: &str) -> Result<Option<String>>;
+
+  /// It stores the content of a kind of data of a block. An existing entry is
+  /// replaced, as it could not be decoded anymore.
+  async fn store(&self, entry: models::NewCachedBlock) -> Result<()>;
+
+  /// It keeps the most recent nth entries and deletes the other ones.
+  async fn garbage_collect(&self, after_nth: i64) -> Result<()>;
+}
+
+pub type DynBlockCacheRepository = Arc<dyn BlockCacheRepository + Sync + Send>;
+
+/// A repository supported by SQLite to persist the data of finalized blocks.
+pub struct SqliteBlockCacheRepository {
+  pool: AsyncPool,
+}
+
+impl SqliteBlockCacheRepository {
+  /// It creates a new repository with connections managed by the pool.
+  pub fn new(pool: AsyncPool) -> Self {
+    Self {
+      pool,
+    }
+  }
+}
+
+#[async_trait]
+impl BlockCacheRepository for SqliteBlockCacheRepository {
+  async fn get(&self, hash: &str, data_kind: &str) -> Result<Option<String>> {
+    let hash = hash.to_string();
+    let data_kind = data_kind.to_string();
+
+    let res = self
+      .pool
+      .exec(move |mut conn| {
+        block_cache
+          .select(content)
+          .filter(block_hash.eq(hash))
+          .filter(kind.eq(data_kind))
+          .first(&mut conn)
+          .optional()
+      })
+      .await?;
+
+    Ok(res)
+  }
+
+  async fn store(&self, entry: models::NewCachedBlock) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| diesel::replace_into(block_cache).values(&entry).execute(&mut conn))
+      .await?;
+
+    Ok(())
+  }
+
+  async fn garbage_collect(&self, after_nth: i64) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        diesel::delete(block_cache)
+          .filter(id.ne_all(block_cache.select(id).order_by(id.desc()).limit(after_nth).into_boxed()))
+          .execute(&mut conn)
+      })
+      .await?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entry(hash: &str, data_kind: &str, value: &str) -> NewCachedBlock {
+    NewCachedBlock {
+      block_hash: hash.to_string(),
+      kind: data_kind.to_string(),
+      content: value.to_string(),
+    }
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_store_and_get() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteBlockCacheRepository::new(pool);
+
+    assert!(matches!(repository.get(":hash:", "info").await, Ok(None)));
+
+    repository.store(entry(":hash:", "info", "{}")).await.unwrap();
+
+    // A second entry for the same block and kind replaces the first one.
+    repository.store(entry(":hash:", "info", "[]")).await.unwrap();
+
+    let res = repository.get(":hash:", "info").await;
+    assert!(matches!(res, Ok(Some(value)) if value == "[]"));
+
+    assert!(matches!(repository.get(":hash:", "summary").await, Ok(None)));
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_garbage_collect() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteBlockCacheRepository::new(pool);
+
+    repository.store(entry(":hash-1:", "info", "1")).await.unwrap();
+    repository.store(entry(":hash-2:", "info", "2")).await.unwrap();
+    repository.store(entry(":hash-3:", "info", "3")).await.unwrap();
+
+    repository.garbage_collect(2).await.unwrap();
+
+    assert!(matches!(repository.get(":hash-1:", "info").await, Ok(None)));
+    assert!(matches!(repository.get(":hash-2:", "info").await, Ok(Some(_))));
+    assert!(matches!(repository.get(":hash-3:", "info").await, Ok(Some(_))));
+  }
+}
@@ -1,5 +1,8 @@
 mod account;
+mod alert;
 mod block;
+mod block_cache;
+mod pool;
 mod price;
 mod status;
 mod user;
@@ -13,7 +16,7 @@ use r2d2::CustomizeConnection;
 use std::fmt;
 use std::time::Duration;
 
-pub use self::{account::*, block::*, price::*, status::*, user::*};
+pub use self::{account::*, alert::*, block::*, block_cache::*, pool::*, price::*, status::*, user::*};
 
 /// A embedding of the migrations of the application to package them alongside
 /// the binary.
@@ -0,0 +1,311 @@
+use super::{AsyncPool, PoolError, RepositoryError, Result};
+use crate::model::Pool;
+use crate::schema::pools::dsl::*;
+use crate::schema::pools::table;
+use diesel::prelude::*;
+use diesel::result::Error;
+use std::sync::Arc;
+
+pub use models::{NewPool, PoolFilter};
+
+mod models {
+  use super::super::BigFloat;
+  use crate::model;
+  use crate::schema::pools;
+
+  /// Record of a snapshot of the status of a pool.
+  #[derive(Queryable)]
+  pub struct Pool {
+    pub id: i32,
+    pub baker_id: i64,
+    pub epoch_ms: i64,
+    pub open_status: String,
+    pub baking_commission: f64,
+    pub finalization_commission: f64,
+    pub transaction_commission: f64,
+    pub equity_capital: BigFloat,
+    pub delegated_capital: BigFloat,
+    pub delegated_capital_cap: BigFloat,
+    pub blocks_baked: Option<i64>,
+    pub finalization_live: Option<bool>,
+    pub transaction_fees_earned: Option<BigFloat>,
+    pub effective_stake: Option<BigFloat>,
+    pub lottery_power: Option<f64>,
+  }
+
+  impl From<Pool> for model::Pool {
+    /// It creates a pool from a record of the storage layer. The statistics of
+    /// the payday are known only when all of them are present.
+    fn from(record: Pool) -> Self {
+      let payday = match (
+        record.blocks_baked,
+        record.finalization_live,
+        record.transaction_fees_earned,
+        record.effective_stake,
+        record.lottery_power,
+      ) {
+        (Some(blocks_baked), Some(finalization_live), Some(fees), Some(stake), Some(lottery_power)) => {
+          Some(model::PoolPayday {
+            blocks_baked,
+            finalization_live,
+            transaction_fees_earned: fees.0,
+            effective_stake: stake.0,
+            lottery_power,
+          })
+        }
+        _ => None,
+      };
+
+      Self::new(
+        record.id,
+        record.baker_id,
+        record.epoch_ms,
+        &record.open_status,
+        model::CommissionRates {
+          baking: record.baking_commission,
+          finalization: record.finalization_commission,
+          transaction: record.transaction_commission,
+        },
+        model::PoolCapital {
+          equity: record.equity_capital.0,
+          delegated: record.delegated_capital.0,
+          delegated_cap: record.delegated_capital_cap.0,
+        },
+        payday,
+      )
+    }
+  }
+
+  #[derive(Insertable)]
+  #[diesel(table_name = pools)]
+  pub struct NewPool {
+    pub baker_id: i64,
+    pub epoch_ms: i64,
+    pub open_status: String,
+    pub baking_commission: f64,
+    pub finalization_commission: f64,
+    pub transaction_commission: f64,
+    pub equity_capital: BigFloat,
+    pub delegated_capital: BigFloat,
+    pub delegated_capital_cap: BigFloat,
+    pub blocks_baked: Option<i64>,
+    pub finalization_live: Option<bool>,
+    pub transaction_fees_earned: Option<BigFloat>,
+    pub effective_stake: Option<BigFloat>,
+    pub lottery_power: Option<f64>,
+  }
+
+  #[derive(PartialEq, Debug)]
+  pub struct PoolFilter {
+    pub baker_id: i64,
+    pub since_ms: Option<i64>,
+  }
+}
+
+/// A repository to store the snapshots of the status of the pools of the
+/// followed bakers.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait PoolRepository {
+  /// It returns the most recent snapshot of the pool of the baker.
+  async fn get_last(&self, baker: i64) -> Result<Pool>;
+
+  /// It returns the snapshots of the pool of a baker, starting from the most
+  /// recent one.
+  async fn get_history(&self, filter: models::PoolFilter) -> Result<Vec<Pool>>;
+
+  /// It stores a new snapshot of a pool.
+  async fn store(&self, pool: models::NewPool) -> Result<()>;
+
+  /// It keeps the most recent nth snapshots and deletes the other ones.
+  async fn garbage_collect(&self, after_nth: i64) -> Result<()>;
+}
+
+pub type DynPoolRepository = Arc<dyn PoolRepository + Sync + Send>;
+
+/// A repository supported by SQLite to store the snapshots of the pools.
+pub struct SqlitePoolRepository {
+  pool: AsyncPool,
+}
+
+impl SqlitePoolRepository {
+  /// It creates a new repository with connections managed by the pool.
+  pub fn new(pool: AsyncPool) -> Self {
+    Self {
+      pool,
+    }
+  }
+}
+
+#[async_trait]
+impl PoolRepository for SqlitePoolRepository {
+  async fn get_last(&self, baker: i64) -> Result<Pool> {
+    let record: models::Pool = self
+      .pool
+      .exec(move |mut conn| {
+        pools
+          .filter(baker_id.eq(baker))
+          .order_by(epoch_ms.desc())
+          .first(&mut conn)
+      })
+      .await
+      .map_err(|e| match e {
+        PoolError::Driver(Error::NotFound) => RepositoryError::NotFound,
+        _ => RepositoryError::from(e),
+      })?;
+
+    Ok(Pool::from(record))
+  }
+
+  async fn get_history(&self, filter: models::PoolFilter) -> Result<Vec<Pool>> {
+    let records: Vec<models::Pool> = self
+      .pool
+      .exec(move |mut conn| {
+        let mut query = table.into_boxed().filter(baker_id.eq(filter.baker_id));
+
+        if let Some(since_ms) = filter.since_ms {
+          query = query.filter(epoch_ms.ge(since_ms));
+        }
+
+        query.order_by(epoch_ms.desc()).load(&mut conn)
+      })
+      .await?;
+
+    Ok(records.into_iter().map(Pool::from).collect())
+  }
+
+  async fn store(&self, new_pool: models::NewPool) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| diesel::insert_into(pools).values(&new_pool).execute(&mut conn))
+      .await?;
+
+    Ok(())
+  }
+
+  async fn garbage_collect(&self, after_nth: i64) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        diesel::delete(pools)
+          .filter(id.ne_all(pools.select(id).order_by(epoch_ms.desc()).limit(after_nth).into_boxed()))
+          .execute(&mut conn)
+      })
+      .await?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rust_decimal_macros::dec;
+
+  fn new_pool(baker: i64, at: i64, payday: bool) -> NewPool {
+    NewPool {
+      baker_id: baker,
+      epoch_ms: at,
+      open_status: "open_for_all".to_string(),
+      baking_commission: 0.1,
+      finalization_commission: 1.0,
+      transaction_commission: 0.1,
+      equity_capital: dec!(1000).into(),
+      delegated_capital: dec!(250).into(),
+      delegated_capital_cap: dec!(3000).into(),
+      blocks_baked: payday.then(|| 12),
+      finalization_live: payday.then(|| true),
+      transaction_fees_earned: payday.then(|| dec!(25).into()),
+      effective_stake: payday.then(|| dec!(1250).into()),
+      lottery_power: payday.then(|| 0.002),
+    }
+  }
+
+  fn epochs(snapshots: &[Pool]) -> Vec<i64> {
+    snapshots
+      .iter()
+      .map(|p| serde_json::to_value(p).unwrap()["epoch_ms"].as_i64().unwrap())
+      .collect()
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_store_and_get_last() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqlitePoolRepository::new(pool);
+
+    assert!(matches!(repository.get_last(42).await, Err(RepositoryError::NotFound)));
+
+    repository.store(new_pool(42, 1000, false)).await.unwrap();
+    repository.store(new_pool(42, 2000, true)).await.unwrap();
+    repository.store(new_pool(7, 3000, true)).await.unwrap();
+
+    let res = repository.get_last(42).await.unwrap();
+
+    let value = serde_json::to_value(&res).unwrap();
+    assert_eq!(42, value["baker_id"]);
+    assert_eq!(2000, value["epoch_ms"]);
+    assert_eq!(12, value["payday"]["blocks_baked"]);
+    assert_eq!("1000", value["capital"]["equity"]);
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_get_history() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqlitePoolRepository::new(pool);
+
+    repository.store(new_pool(42, 1000, false)).await.unwrap();
+    repository.store(new_pool(42, 2000, true)).await.unwrap();
+    repository.store(new_pool(7, 3000, true)).await.unwrap();
+
+    let res = repository
+      .get_history(PoolFilter {
+        baker_id: 42,
+        since_ms: None,
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(vec![2000, 1000], epochs(&res));
+
+    let res = repository
+      .get_history(PoolFilter {
+        baker_id: 42,
+        since_ms: Some(1500),
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(1, res.len());
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_garbage_collect() {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqlitePoolRepository::new(pool);
+
+    repository.store(new_pool(42, 1000, false)).await.unwrap();
+    repository.store(new_pool(42, 2000, false)).await.unwrap();
+    repository.store(new_pool(42, 3000, false)).await.unwrap();
+
+    repository.garbage_collect(2).await.unwrap();
+
+    let res = repository
+      .get_history(PoolFilter {
+        baker_id: 42,
+        since_ms: None,
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(vec![3000, 2000], epochs(&res));
+  }
+}
@@ -1,10 +1,11 @@
 use super::{AsyncPool, Result};
-use crate::model::Status;
+use crate::model::{Peer, Status};
+use crate::schema::peers::dsl as peer_dsl;
 use crate::schema::statuses::dsl::*;
 use diesel::prelude::*;
 use std::sync::Arc;
 
-pub use models::{NewStatus, NodeStatusJson, ResourceStatusJson};
+pub use models::{ConsensusStatusJson, NewPeer, NewStatus, NodeStatusJson, ResourceStatusJson};
 
 mod models {
   use crate::model;
@@ -53,6 +54,62 @@ mod models {
     pub peer_type: String,
     pub peer_average_latency: f64,
     pub peer_count: usize,
+
+    /// The node endpoint used by the daemon when the report was made.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// The number of calls to the node retried by the daemon since it started.
+    #[serde(default)]
+    pub client_retries: u64,
+
+    /// The number of calls to the node answered by the block cache.
+    #[serde(default)]
+    pub cache_hits: u64,
+
+    /// The number of calls to the node that missed the block cache.
+    #[serde(default)]
+    pub cache_misses: u64,
+
+    /// The state of the node in the committee of the bakers, which tells a
+    /// baker added with wrong keys apart. It is missing from older reports.
+    #[serde(default)]
+    pub baker_committee: Option<String>,
+
+    #[serde(default)]
+    pub consensus_baker_running: Option<bool>,
+
+    #[serde(default)]
+    pub consensus_running: Option<bool>,
+
+    #[serde(default)]
+    pub consensus_type: Option<String>,
+
+    /// The state of the consensus seen by the node. It is missing from older
+    /// reports.
+    #[serde(default)]
+    pub consensus: Option<ConsensusStatusJson>,
+  }
+
+  /// A JSON blob of the state of the consensus, and how far behind the chain
+  /// the node is.
+  #[derive(Serialize, Deserialize, Debug)]
+  pub struct ConsensusStatusJson {
+    pub best_block_height: i64,
+    pub last_finalized_block_height: i64,
+    pub last_finalized_ms: Option<i64>,
+    pub epoch: Option<u64>,
+    pub slot_duration_ms: u64,
+    pub protocol_version: u64,
+
+    /// The number of blocks between the best block and the last finalized one.
+    pub finalization_lag: i64,
+
+    /// The time elapsed since the last finalized block, if there is one.
+    pub sync_lag_ms: Option<i64>,
+
+    /// It tells if the sync lag is above the maximum tolerated.
+    pub is_lagging: bool,
   }
 
   impl se::ToSql<Nullable<Text>, Sqlite> for NodeStatusJson {
@@ -91,6 +148,37 @@ mod models {
     pub resources: ResourceStatusJson,
     pub node: Option<NodeStatusJson>,
   }
+
+  #[derive(Queryable)]
+  pub struct Peer {
+    pub id: i32,
+    pub node_id: String,
+    pub packets_sent: i64,
+    pub packets_received: i64,
+    pub latency: i64,
+    pub connected_ms: i64,
+    pub last_seen_ms: i64,
+    pub disconnected_ms: Option<i64>,
+  }
+
+  impl From<Peer> for model::Peer {
+    fn from(p: Peer) -> Self {
+      Self::new(p.id, &p.node_id, p.packets_sent, p.packets_received, p.latency).with_connection(
+        p.connected_ms,
+        p.last_seen_ms,
+        p.disconnected_ms,
+      )
+    }
+  }
+
+  /// The statistics of a peer of the node at the time of a report.
+  #[derive(PartialEq, Debug)]
+  pub struct NewPeer {
+    pub node_id: String,
+    pub packets_sent: i64,
+    pub packets_received: i64,
+    pub latency: i64,
+  }
 }
 
 #[cfg_attr(test, mockall::automock)]
@@ -101,6 +189,17 @@ pub trait StatusRepository {
   async fn report(&self, status: models::NewStatus) -> Result<()>;
 
   async fn garbage_collect(&self, after_nth: i64) -> Result<()>;
+
+  /// It returns the peers of the node, starting with the connected ones.
+  async fn get_peers(&self) -> Result<Vec<Peer>>;
+
+  /// It updates the peers with the ones reported at the given time. A peer
+  /// missing from the report is marked as disconnected, and a peer reported
+  /// again after a disconnection starts a new connection.
+  async fn set_peers(&self, peers: Vec<NewPeer>, now_ms: i64) -> Result<()>;
+
+  /// It deletes the peers disconnected before the given time.
+  async fn delete_peers(&self, before_ms: i64) -> Result<()>;
 }
 
 pub type DynStatusRepository = Arc<dyn StatusRepository + Sync + Send>;
@@ -158,6 +257,83 @@ impl StatusRepository for SqliteStatusRepository {
 
     Ok(())
   }
+
+  async fn get_peers(&self) -> Result<Vec<Peer>> {
+    let res: Vec<models::Peer> = self
+      .pool
+      .exec(|mut conn| {
+        peer_dsl::peers
+          .order_by((peer_dsl::disconnected_ms.is_not_null(), peer_dsl::node_id))
+          .load(&mut conn)
+      })
+      .await?;
+
+    Ok(res.into_iter().map(Peer::from).collect())
+  }
+
+  async fn set_peers(&self, new_peers: Vec<NewPeer>, now_ms: i64) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        conn.transaction(|tx| {
+          let node_ids: Vec<&str> = new_peers.iter().map(|p| p.node_id.as_str()).collect();
+
+          diesel::update(
+            peer_dsl::peers
+              .filter(peer_dsl::node_id.eq_any(&node_ids))
+              .filter(peer_dsl::disconnected_ms.is_not_null()),
+          )
+          .set(peer_dsl::connected_ms.eq(now_ms))
+          .execute(tx)?;
+
+          for peer in &new_peers {
+            diesel::insert_into(peer_dsl::peers)
+              .values((
+                peer_dsl::node_id.eq(&peer.node_id),
+                peer_dsl::packets_sent.eq(peer.packets_sent),
+                peer_dsl::packets_received.eq(peer.packets_received),
+                peer_dsl::latency.eq(peer.latency),
+                peer_dsl::connected_ms.eq(now_ms),
+                peer_dsl::last_seen_ms.eq(now_ms),
+              ))
+              .on_conflict(peer_dsl::node_id)
+              .do_update()
+              .set((
+                peer_dsl::packets_sent.eq(peer.packets_sent),
+                peer_dsl::packets_received.eq(peer.packets_received),
+                peer_dsl::latency.eq(peer.latency),
+                peer_dsl::last_seen_ms.eq(now_ms),
+                peer_dsl::disconnected_ms.eq(None::<i64>),
+              ))
+              .execute(tx)?;
+          }
+
+          diesel::update(
+            peer_dsl::peers
+              .filter(peer_dsl::node_id.ne_all(&node_ids))
+              .filter(peer_dsl::disconnected_ms.is_null()),
+          )
+          .set(peer_dsl::disconnected_ms.eq(now_ms))
+          .execute(tx)?;
+
+          Ok(())
+        })
+      })
+      .await?;
+
+    Ok(())
+  }
+
+  async fn delete_peers(&self, before_ms: i64) -> Result<()> {
+    self
+      .pool
+      .exec(move |mut conn| {
+        diesel::delete(peer_dsl::peers.filter(peer_dsl::disconnected_ms.lt(before_ms))).execute(&mut conn)
+      })
+      .await?;
+
+    Ok(())
+  }
 }
 
 #[cfg(test)]
@@ -188,6 +364,25 @@ mod tests {
         peer_type: "peer".to_string(),
         peer_average_latency: 0.0,
         peer_count: 5,
+        endpoint: Some("http://127.0.0.1:10000/".to_string()),
+        client_retries: 2,
+        cache_hits: 10,
+        cache_misses: 1,
+        baker_committee: Some("active".to_string()),
+        consensus_baker_running: Some(true),
+        consensus_running: Some(true),
+        consensus_type: Some("Active".to_string()),
+        consensus: Some(models::ConsensusStatusJson {
+          best_block_height: 125,
+          last_finalized_block_height: 123,
+          last_finalized_ms: Some(900),
+          epoch: Some(5),
+          slot_duration_ms: 250,
+          protocol_version: 4,
+          finalization_lag: 2,
+          sync_lag_ms: Some(100),
+          is_lagging: false,
+        }),
       }),
       timestamp_ms: 1000,
     };
@@ -242,4 +437,99 @@ mod tests {
 
     assert!(matches!(count, Ok(1)));
   }
+
+  fn new_peer(node_id: &str, latency: i64) -> NewPeer {
+    NewPeer {
+      node_id: node_id.to_string(),
+      packets_sent: 10,
+      packets_received: 20,
+      latency,
+    }
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_set_peers() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteStatusRepository::new(pool.clone());
+
+    repository
+      .set_peers(vec![new_peer("peer-1", 100), new_peer("peer-2", 200)], 1000)
+      .await?;
+
+    // The second peer disconnects and a third one connects.
+    repository
+      .set_peers(vec![new_peer("peer-1", 150), new_peer("peer-3", 50)], 2000)
+      .await?;
+
+    let res = pool
+      .exec(|mut conn| {
+        peer_dsl::peers
+          .select((
+            peer_dsl::node_id,
+            peer_dsl::latency,
+            peer_dsl::connected_ms,
+            peer_dsl::last_seen_ms,
+            peer_dsl::disconnected_ms,
+          ))
+          .order_by(peer_dsl::node_id)
+          .load::<(String, i64, i64, i64, Option<i64>)>(&mut conn)
+      })
+      .await
+      .unwrap();
+
+    assert_eq!(
+      vec![
+        ("peer-1".to_string(), 150, 1000, 2000, None),
+        ("peer-2".to_string(), 200, 1000, 1000, Some(2000)),
+        ("peer-3".to_string(), 50, 2000, 2000, None),
+      ],
+      res
+    );
+
+    // The second peer reconnects.
+    repository.set_peers(vec![new_peer("peer-2", 300)], 3000).await?;
+
+    let res = pool
+      .exec(|mut conn| {
+        peer_dsl::peers
+          .select((peer_dsl::connected_ms, peer_dsl::disconnected_ms))
+          .filter(peer_dsl::node_id.eq("peer-2"))
+          .first::<(i64, Option<i64>)>(&mut conn)
+      })
+      .await
+      .unwrap();
+
+    assert_eq!((3000, None), res);
+
+    let res = repository.get_peers().await?;
+
+    assert_eq!(3, res.len());
+
+    Ok(())
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn test_delete_peers() -> Result<()> {
+    let pool = AsyncPool::open(":memory:").unwrap();
+
+    pool.run_migrations().await.unwrap();
+
+    let repository = SqliteStatusRepository::new(pool);
+
+    repository
+      .set_peers(vec![new_peer("peer-1", 100), new_peer("peer-2", 200)], 1000)
+      .await?;
+    repository.set_peers(vec![new_peer("peer-1", 100)], 2000).await?;
+
+    repository.delete_peers(1500).await?;
+    assert_eq!(2, repository.get_peers().await?.len());
+
+    repository.delete_peers(2500).await?;
+    assert_eq!(1, repository.get_peers().await?.len());
+
+    Ok(())
+  }
 }
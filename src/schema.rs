@@ -9,6 +9,31 @@ table! {
     }
 }
 
+table! {
+    account_snapshots (id) {
+        id -> Integer,
+        account_id -> Integer,
+        epoch_ms -> BigInt,
+        balance -> Text,
+        stake -> Text,
+        lottery_power -> Double,
+    }
+}
+
+table! {
+    account_transactions (id) {
+        id -> Integer,
+        account_id -> Integer,
+        block_hash -> Text,
+        transaction_hash -> Text,
+        epoch_ms -> BigInt,
+        kind -> Text,
+        amount -> Text,
+        counterparty -> Nullable<Text>,
+        cost -> Text,
+    }
+}
+
 table! {
     accounts (id) {
         id -> Integer,
@@ -17,6 +42,31 @@ table! {
         balance -> Text,
         stake -> Text,
         pending_update -> Bool,
+        staking -> Text,
+        pool_id -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    alerts (id) {
+        id -> Integer,
+        rule -> Text,
+        subject -> Text,
+        message -> Text,
+        state -> Text,
+        fired_ms -> BigInt,
+        resolved_ms -> Nullable<BigInt>,
+        acked_ms -> Nullable<BigInt>,
+        acked_by -> Nullable<Integer>,
+    }
+}
+
+table! {
+    block_cache (id) {
+        id -> Integer,
+        block_hash -> Text,
+        kind -> Text,
+        content -> Text,
     }
 }
 
@@ -27,6 +77,8 @@ table! {
         hash -> Text,
         slot_time_ms -> BigInt,
         baker -> BigInt,
+        genesis_index -> Integer,
+        protocol_version -> Integer,
     }
 }
 
@@ -47,6 +99,39 @@ table! {
     }
 }
 
+table! {
+    peers (id) {
+        id -> Integer,
+        node_id -> Text,
+        packets_sent -> BigInt,
+        packets_received -> BigInt,
+        latency -> BigInt,
+        connected_ms -> BigInt,
+        last_seen_ms -> BigInt,
+        disconnected_ms -> Nullable<BigInt>,
+    }
+}
+
+table! {
+    pools (id) {
+        id -> Integer,
+        baker_id -> BigInt,
+        epoch_ms -> BigInt,
+        open_status -> Text,
+        baking_commission -> Double,
+        finalization_commission -> Double,
+        transaction_commission -> Double,
+        equity_capital -> Text,
+        delegated_capital -> Text,
+        delegated_capital_cap -> Text,
+        blocks_baked -> Nullable<BigInt>,
+        finalization_live -> Nullable<Bool>,
+        transaction_fees_earned -> Nullable<Text>,
+        effective_stake -> Nullable<Text>,
+        lottery_power -> Nullable<Double>,
+    }
+}
+
 table! {
     prices (pair_id) {
         pair_id -> Integer,
@@ -58,6 +143,18 @@ table! {
     }
 }
 
+table! {
+    silences (id) {
+        id -> Integer,
+        rule -> Nullable<Text>,
+        subject -> Nullable<Text>,
+        reason -> Text,
+        created_by -> Nullable<Integer>,
+        starts_ms -> BigInt,
+        ends_ms -> BigInt,
+    }
+}
+
 table! {
     statuses (id) {
         id -> Integer,
@@ -85,17 +182,26 @@ table! {
 }
 
 joinable!(account_rewards -> accounts (account_id));
+joinable!(account_snapshots -> accounts (account_id));
+joinable!(account_transactions -> accounts (account_id));
 joinable!(hist_prices -> pairs (pair_id));
 joinable!(prices -> pairs (pair_id));
 joinable!(user_sessions -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
   account_rewards,
+  account_snapshots,
+  account_transactions,
   accounts,
+  alerts,
+  block_cache,
   blocks,
   hist_prices,
   pairs,
+  peers,
+  pools,
   prices,
+  silences,
   statuses,
   user_sessions,
   users,